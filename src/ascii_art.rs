@@ -2,8 +2,8 @@
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub fn show_ascii_art_header() {
-  println!();
-  println!(
+  eprintln!();
+  eprintln!(
 "\
  ██▀███  ▓█████  ███▄ ▄███▓ ███▄ ▄███▓ ██▓ ███▄    █  ▄▄▄         ▄▄▄█████▓ ▒█████     ▄▄▄█████▓ ▄▄▄       ▄▄▄▄    ▄▄▄▄ ▓██   ██▓
 ▓██ ▒ ██▒▓█   ▀ ▓██▒▀█▀ ██▒▓██▒▀█▀ ██▒▓██▒ ██ ▀█   █ ▒████▄       ▓  ██▒ ▓▒▒██▒  ██▒   ▓  ██▒ ▓▒▒████▄    ▓█████▄ ▓█████▄▒██  ██▒
@@ -0,0 +1,259 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::protocols_types::ConnectionProtocols;
+use crate::remmina_secrets::Secret;
+
+/// Placeholders of profile name templates (`--name-template`, `rename` rules): every `Connection` field,
+/// `server` as an alias of `host`, `exec` of `remote_command`, `tunnel` (jump host:port) and `stem`
+/// (file name without extension)
+pub const TEMPLATE_PLACEHOLDERS: &[&str] = &[
+    "name", "server", "host", "port", "group", "protocol", "user", "auth",
+    "private_key", "proxy_command", "compression", "strict_host_key_check", "charset",
+    "kex_algorithms", "ciphers", "host_key_types", "exec", "forward_x11", "tunnel",
+    "color", "icon", "path", "source_id", "stem",
+];
+
+/// Scheme of a source id: "ssh_config" for "ssh_config:web", None for the ids of .remmina files
+///
+/// Notes:
+///     Connections read from other formats than Remmina use "scheme:id" source ids, so pruning after
+///     an import only removes profiles coming from the same format
+pub fn source_scheme(source_id: &str) -> Option<&str> {
+    let (scheme, _) = source_id.split_once(':')?;
+    let valid = !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    valid.then_some(scheme)
+}
+
+/// Splits a template into literal text and `{placeholder}` names
+///
+/// # Returns
+/// * `Vec<(&str, Option<&str>)>` - Literal text, each followed by the placeholder after it (None for the trailing text)
+pub fn template_parts(template: &str) -> Vec<(&str, Option<&str>)> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else { break };
+        parts.push((&rest[..start], Some(&rest[start + 1..start + len])));
+        rest = &rest[start + len + 1..];
+    }
+    parts.push((rest, None));
+    parts
+}
+
+/// Checks that a name template only uses known placeholders (clap value parser of `--name-template`)
+pub fn check_template(template: &str) -> Result<String, String> {
+    for (_, placeholder) in template_parts(template) {
+        if let Some(name) = placeholder
+            && !TEMPLATE_PLACEHOLDERS.contains(&name)
+        {
+            return Err(format!("unknown placeholder {{{name}}}, expected one of: {}", TEMPLATE_PLACEHOLDERS.join(", ")));
+        }
+    }
+    Ok(template.to_string())
+}
+
+/// A connection, the neutral model every format reads and writes
+///
+/// Notes:
+///     Each format converts its own entries to and from connections in its importer and exporter
+///     (`RemminaProfile::to_connection`, `TabbyConfig::connections`, ...); `path` is the file a connection
+///     was read from, if any
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Connection {
+    pub name: Option<String>,
+    pub host: Option<String>,
+    /// None for the default port of the protocol, see `get_default_port_for_protocol`
+    pub port: Option<u16>,
+    /// Group path, segments separated by '/' ("Prod/DB")
+    pub group: Option<String>,
+    pub protocol: ConnectionProtocols,
+    pub user: Option<String>,
+    /// None lets the client try the methods the server offers
+    pub auth: Option<AuthMethod>,
+
+    // SSH settings
+    pub private_key: Option<String>,
+    pub proxy_command: Option<String>,
+    /// SSH jump host (bastion) the host is reached through
+    pub jump_host: Option<JumpHost>,
+    pub compression: Option<bool>,
+    pub strict_host_key_check: Option<bool>,
+    pub charset: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub kex_algorithms: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ciphers: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub host_key_types: Vec<String>,
+    /// Command executed right after login
+    pub remote_command: Option<String>,
+    pub forward_x11: Option<bool>,
+
+    // Secrets, only set when decrypted (Remmina --with-secrets), never serialized
+    #[serde(skip)]
+    pub password: Option<Secret>,
+    #[serde(skip)]
+    pub key_passphrase: Option<Secret>,
+
+    /// Tabby profile color, icon and sorting weight, set by rules and the palette
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<i32>,
+
+    pub path: PathBuf,
+    /// Stable id of the connection used to sync it with Tabby ("scheme:id" outside Remmina, see `source_scheme`)
+    pub source_id: Option<String>,
+}
+
+/// SSH host a connection goes through
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JumpHost {
+    pub host: String,
+    pub port: u16,
+    pub user: Option<String>,
+    pub auth: Option<AuthMethod>,
+    pub private_key: Option<String>,
+    #[serde(skip)]
+    pub password: Option<Secret>,
+}
+
+/// Methods for JumpHost
+impl JumpHost {
+    /// Split a jump host address ("host", "host:port" or "[v6]:port") into host and port
+    ///
+    /// # Arguments
+    /// * `value` - The address, e.g. a Remmina ssh_tunnel_server value
+    /// # Returns
+    /// * `(String, u16)` - Host and port, port defaults to 22
+    pub fn parse_server(value: &str) -> (String, u16) {
+        let value = value.trim();
+        if let Some(rest) = value.strip_prefix('[')
+            && let Some((host, tail)) = rest.split_once(']') {
+            let port = tail.strip_prefix(':').and_then(|p| p.parse().ok()).unwrap_or(22);
+            return (host.to_string(), port);
+        }
+        match value.rsplit_once(':') {
+            // A single ':' separates host and port, more than one means a bare IPv6 address
+            Some((host, port)) if !host.contains(':') => {
+                (host.to_string(), port.parse().unwrap_or(22))
+            }
+            _ => (value.to_string(), 22),
+        }
+    }
+}
+
+/// How a connection authenticates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMethod {
+    Password,
+    /// A key pair, the `private_key` file if set, the default keys otherwise
+    PublicKey,
+    /// The keys of the SSH agent
+    Agent,
+    KeyboardInteractive,
+    /// Kerberos (gssapi-with-mic)
+    Gssapi,
+}
+
+/// Methods for AuthMethod
+impl AuthMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthMethod::Password => "password",
+            AuthMethod::PublicKey => "public_key",
+            AuthMethod::Agent => "agent",
+            AuthMethod::KeyboardInteractive => "keyboard_interactive",
+            AuthMethod::Gssapi => "gssapi",
+        }
+    }
+
+    /// Parse a method name, case-insensitive
+    ///
+    /// # Returns
+    /// * `Option<AuthMethod>` - The method of an `as_str` name, or of the ssh names ("publickey",
+    ///   "keyboard-interactive", "gssapi-with-mic"); None for unknown names
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace('-', "_").as_str() {
+            "password" => Some(AuthMethod::Password),
+            "public_key" | "publickey" => Some(AuthMethod::PublicKey),
+            "agent" => Some(AuthMethod::Agent),
+            "keyboard_interactive" => Some(AuthMethod::KeyboardInteractive),
+            "gssapi" | "gssapi_with_mic" => Some(AuthMethod::Gssapi),
+            _ => None,
+        }
+    }
+}
+
+/// Methods for Connection
+impl Connection {
+    /// Value of a name template placeholder, see `TEMPLATE_PLACEHOLDERS`
+    ///
+    /// Notes:
+    ///     `name` falls back to the host when the connection has no name
+    pub fn template_value(&self, placeholder: &str) -> Option<String> {
+        let flag = |value: Option<bool>| value.map(|v| if v { "1" } else { "0" }.to_string());
+        let list = |values: &[String]| Some(values.join(",")).filter(|l| !l.is_empty());
+        match placeholder {
+            "name" => self.name.clone().filter(|n| !n.trim().is_empty()).or_else(|| self.host.clone()),
+            "server" | "host" => self.host.clone(),
+            "port" => self.port.map(|p| p.to_string()),
+            "group" => self.group.clone(),
+            "protocol" => Some(self.protocol.as_str().to_uppercase()),
+            "user" => self.user.clone(),
+            "auth" => self.auth.map(|a| a.as_str().to_string()),
+            "private_key" => self.private_key.clone(),
+            "proxy_command" => self.proxy_command.clone(),
+            "compression" => flag(self.compression),
+            "strict_host_key_check" => flag(self.strict_host_key_check),
+            "charset" => self.charset.clone(),
+            "kex_algorithms" => list(&self.kex_algorithms),
+            "ciphers" => list(&self.ciphers),
+            "host_key_types" => list(&self.host_key_types),
+            "exec" => self.remote_command.clone(),
+            "forward_x11" => flag(self.forward_x11),
+            "tunnel" => self.jump_host.as_ref().map(|j| format!("{}:{}", j.host, j.port)),
+            "color" => self.color.clone(),
+            "icon" => self.icon.clone(),
+            "path" => Some(self.path.display().to_string()).filter(|p| !p.is_empty()),
+            "source_id" => self.source_id.clone(),
+            "stem" => self.path.file_stem().map(|s| s.to_string_lossy().into_owned()),
+            _ => None,
+        }
+    }
+
+    /// Expand a name template with the connection fields
+    ///
+    /// # Arguments
+    /// * `template` - e.g. "{group} / {name} ({user}@{server})"
+    /// # Returns
+    /// * `String` - The expanded template, unset fields expand to nothing, unknown placeholders are kept as-is.
+    ///   Falls back to the host, then the file stem, when blank so the name is never empty
+    pub fn render_name(&self, template: &str) -> String {
+        let mut name = String::new();
+        for (text, placeholder) in template_parts(template) {
+            name.push_str(text);
+            match placeholder {
+                Some(key) if TEMPLATE_PLACEHOLDERS.contains(&key) => name.push_str(&self.template_value(key).unwrap_or_default()),
+                Some(key) => name.push_str(&format!("{{{key}}}")),
+                None => {}
+            }
+        }
+        if !name.trim().is_empty() {
+            return name;
+        }
+        self.template_value("host")
+            .or_else(|| self.template_value("stem"))
+            .unwrap_or_else(|| "unnamed".to_string())
+    }
+}
+
+/// A comma separated algorithm list ("aes256-ctr,aes128-ctr") as a list, empty entries removed
+pub fn split_list(list: &str) -> Vec<String> {
+    list.split(',').map(str::trim).filter(|a| !a.is_empty()).map(str::to_string).collect()
+}
@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use crate::error::Result;
+use crate::formats::{Connections, Importer, ReadOptions, RemminaFormat, SshConfigFormat};
+use crate::group_mapping::GroupMapping;
+use crate::palette::Palette;
+use crate::protocols_types::ALLOWED_PROTOCOLS_EXPORT;
+use crate::remmina_secrets::{RemminaPref, SecretReport, SecretSink};
+use crate::report::{Plan, RunReport};
+use crate::rules::RuleSet;
+use crate::tabby_parser::{ImportOptions, TabbyConfig};
+
+/// Options of a conversion
+pub struct ConvertOptions<'a> {
+    /// Protocols of the .remmina files to convert, case-insensitive (default: ALLOWED_PROTOCOLS_EXPORT)
+    pub protocols: Vec<String>,
+    /// Remmina preferences used to decrypt stored passwords, None to leave secrets out
+    pub remmina_pref: Option<&'a RemminaPref>,
+    /// Receives the decrypted secrets of the created profiles (e.g. a `TabbyVault`), None to drop them
+    pub secret_sink: Option<&'a mut dyn SecretSink>,
+    /// Rules transforming the profiles before their import
+    pub rules: Option<&'a RuleSet>,
+    /// Template of the Tabby profile names, see `TEMPLATE_PLACEHOLDERS` (default: "{name}")
+    pub name_template: Option<String>,
+    /// How nested Remmina groups are mapped onto Tabby groups
+    pub groups: GroupMapping,
+    /// Colors, icons and sorting weights of the created profiles
+    pub palette: Palette,
+    /// How profiles already in the Tabby config are handled (sync, conflicts)
+    pub import: ImportOptions,
+    /// Remove the imported profiles whose Remmina file no longer exists, and the groups they leave empty
+    pub prune: bool,
+}
+
+impl Default for ConvertOptions<'_> {
+    fn default() -> Self {
+        ConvertOptions {
+            protocols: ALLOWED_PROTOCOLS_EXPORT.iter().map(|p| p.to_string()).collect(),
+            remmina_pref: None,
+            secret_sink: None,
+            rules: None,
+            name_template: None,
+            groups: GroupMapping::default(),
+            palette: Palette::default(),
+            import: ImportOptions::default(),
+            prune: false,
+        }
+    }
+}
+
+/// Convert the .remmina files of a directory into profiles of a Tabby config
+///
+/// # Arguments
+/// * `source` - The Remmina profiles directory
+/// * `target` - The Tabby config receiving the profiles, only modified in memory
+/// * `options` - Protocol filter, secrets handling, rules, name template, group mapping, palette and import options
+/// # Returns
+/// * `Result<Plan>` - The parsed profiles, skipped files, created groups and profiles, updated and removed profiles,
+///   or an error if the directory cannot be read
+/// # Behavior
+/// * Files that cannot be read, parsed or converted are listed in `Plan::skipped_files`, they do not stop the conversion
+/// * Saving `target` (and sealing a vault used as secret sink) is left to the caller
+///
+/// # Example
+/// ```no_run
+/// use remmina_to_tabby::{ConvertOptions, TabbyConfig, convert};
+///
+/// let mut config = TabbyConfig::load_from_dir("/home/me/.config/tabby")?;
+/// let plan = convert("/home/me/.local/share/remmina", &mut config, ConvertOptions::default())?;
+/// println!("{} profiles created", plan.created_profiles.len());
+/// config.save_to_path("/home/me/.config/tabby/config.yaml")?;
+/// # Ok::<(), remmina_to_tabby::Error>(())
+/// ```
+pub fn convert(source: &str, target: &mut TabbyConfig, options: ConvertOptions<'_>) -> Result<Plan> {
+    let remmina = RemminaFormat { pref: options.remmina_pref.cloned() };
+    convert_from(&remmina, Path::new(source), target, options)
+}
+
+/// Convert the Host blocks of an OpenSSH client config into SSH profiles of a Tabby config
+///
+/// # Arguments
+/// * `path` - The ssh_config file, e.g. ~/.ssh/config
+/// * `target` - The Tabby config receiving the profiles, only modified in memory
+/// * `options` - Same as for `convert`, `remmina_pref` is not used
+/// # Returns
+/// * `Result<Plan>` - Like `convert`, with the SSH profile defaults set from the "Host *" options,
+///   or an error if the file (or a file it includes) cannot be read
+/// # Behavior
+/// * See `SshConfig::load` for the Host, Match, Include and ProxyJump handling
+/// * The profiles have "ssh_config:<alias>" source ids, pruning only removes profiles imported from an ssh_config
+pub fn convert_ssh_config(path: &Path, target: &mut TabbyConfig, options: ConvertOptions<'_>) -> Result<Plan> {
+    convert_from(&SshConfigFormat::default(), path, target, options)
+}
+
+/// Convert what any importer reads into profiles of a Tabby config, `convert` for every format of the `Registry`
+///
+/// # Arguments
+/// * `importer` - The format to read, e.g. `Registry::importer("ssh-config")`
+/// * `source` - File or directory read by the importer
+/// * `target` - The Tabby config receiving the profiles, only modified in memory
+/// * `options` - Same as for `convert`, `protocols` is passed to the importer and `remmina_pref` is not used
+///   (settings of a format are fields of its importer, see `RemminaFormat`)
+/// # Returns
+/// * `Result<Plan>` - Like `convert_connections`, with the skipped files and the secrets summary of the importer,
+///   or an error if the source cannot be read
+pub fn convert_from(importer: &dyn Importer, source: &Path, target: &mut TabbyConfig, options: ConvertOptions<'_>) -> Result<Plan> {
+    // The importer records skipped files in a run report, they are moved into the plan
+    let mut report = RunReport::default();
+    let connections = importer.import(source, &ReadOptions { protocols: options.protocols.clone() }, &mut report)?;
+    let mut plan = convert_connections(connections, target, options)?;
+    plan.skipped_files = report.skipped_files;
+    plan.secrets = report.secrets;
+    Ok(plan)
+}
+
+/// Transform connections read by any importer (rules, name template, group mapping, palette)
+/// and import them into the Tabby config
+///
+/// # Arguments
+/// * `connections` - See `Importer::import`
+/// * `target` - The Tabby config receiving the profiles, only modified in memory
+/// * `options` - Secrets handling, rules, name template, group mapping, palette and import options;
+///   the protocol filter is applied by the importer
+/// # Returns
+/// * `Result<Plan>` - The created, updated and removed groups and profiles, the profile defaults set,
+///   or an error from `TabbyConfig::import_profiles`
+/// # Behavior
+/// * The `defaults` of the connections become Tabby SSH profile defaults, existing ones are kept
+/// * Pruning only removes profiles whose source id has the scheme of the connections
+pub fn convert_connections(connections: Connections, target: &mut TabbyConfig, options: ConvertOptions<'_>) -> Result<Plan> {
+    let Connections { connections: profiles, defaults, sources, scheme } = connections;
+    let profile_defaults = defaults.map(|d| target.set_ssh_defaults(&d)).unwrap_or_default();
+
+    let mut dropped_secrets = SecretReport::default();
+    let secret_sink: &mut dyn SecretSink = match options.secret_sink {
+        Some(sink) => sink,
+        None => &mut dropped_secrets,
+    };
+
+    // Rules see the Remmina groups, before they are mapped
+    let mut rule_changes = Vec::new();
+    let mut dropped = Vec::new();
+    let mut mapped = match options.rules {
+        Some(rules) => rules.apply(profiles.clone(), &mut rule_changes, &mut dropped),
+        None => profiles.clone(),
+    };
+    // Names are never empty, "{name}" falls back to the host
+    let template = options.name_template.as_deref().unwrap_or("{name}");
+    for profile in &mut mapped {
+        let name = profile.render_name(template);
+        if profile.name.as_deref() != Some(name.as_str()) {
+            eprintln!(" └── Profile '{}' named '{name}'", profile.name.as_deref().unwrap_or_default());
+        }
+        profile.name = Some(name);
+    }
+    options.groups.apply(&mut mapped);
+    // Colors follow the Tabby groups, after the mapping
+    options.palette.apply(&mut mapped);
+
+    let mut plan = target.import_profiles(mapped, &options.import, secret_sink)?;
+    plan.rule_changes = rule_changes;
+    plan.skipped_profiles.splice(0..0, dropped);
+    if options.prune {
+        target.prune_profiles(&sources, scheme, &mut plan);
+    }
+    plan.profiles = profiles;
+    plan.profile_defaults = profile_defaults;
+    Ok(plan)
+}
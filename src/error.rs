@@ -0,0 +1,150 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Exit codes of the command outcome, 2 is the one of clap for usage errors
+pub const EXIT_OK: u8 = 0;
+pub const EXIT_USAGE: u8 = 2;
+pub const EXIT_CHECK_FAILED: u8 = 3;
+pub const EXIT_INVALID_CONFIG: u8 = 4;
+pub const EXIT_PENDING_CHANGES: u8 = 5;
+
+/// Errors of the converter
+///
+/// Notes:
+///     Every variant maps to its own exit code (see `exit_code`), so scripts can tell failures apart.
+///     Errors about a single .remmina file are not fatal: they are reported in the run report
+///     (skipped files) and the other files are still processed
+#[derive(Debug)]
+pub enum Error {
+    /// A file or directory cannot be read or written
+    Io { path: PathBuf, source: io::Error },
+    /// config.yaml (or another YAML document) is not valid YAML or does not match the Tabby format
+    YamlParse { path: PathBuf, line: Option<usize>, column: Option<usize>, message: String },
+    /// A .remmina (or remmina.pref) file is not a valid key file or lacks a required key
+    InvalidRemminaFile { path: PathBuf, line: Option<usize>, message: String },
+    /// A .remmina file uses a protocol that cannot be converted
+    UnsupportedProtocol { path: PathBuf, protocol: String },
+    /// An imported or exported item clashes with an existing one
+    Conflict { name: String, reason: String },
+    /// A secret cannot be decrypted or stored (remmina.pref secret, Tabby vault)
+    Secret(String),
+    /// The rules file is not valid YAML/TOML, or has an unknown key, glob or regex error
+    InvalidRules { path: PathBuf, message: String },
+    /// A session file of another client (PuTTY .reg, KiTTY session, ...) cannot be parsed
+    InvalidSessionFile { path: PathBuf, line: Option<usize>, message: String },
+    /// The arguments do not fit together beyond what clap checks (no default location for a format,
+    /// a directory option pointing to a file, ...)
+    Usage(String),
+    /// The user answered 'q' to a confirmation
+    Cancelled,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Methods for Error
+impl Error {
+    pub fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        Error::Io { path: path.as_ref().to_path_buf(), source }
+    }
+
+    /// Build a YamlParse error, keeping the location reported by the parser
+    pub fn yaml(path: impl AsRef<Path>, error: &serde_yaml_ng::Error) -> Self {
+        let location = error.location();
+        Error::YamlParse {
+            path: path.as_ref().to_path_buf(),
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+            message: error.to_string(),
+        }
+    }
+
+    /// Set the path of errors raised while parsing content not yet tied to a file
+    pub fn with_path(self, path: &Path) -> Self {
+        match self {
+            Error::YamlParse { line, column, message, .. } => {
+                Error::YamlParse { path: path.to_path_buf(), line, column, message }
+            }
+            Error::InvalidRemminaFile { line, message, .. } => {
+                Error::InvalidRemminaFile { path: path.to_path_buf(), line, message }
+            }
+            Error::InvalidSessionFile { line, message, .. } => {
+                Error::InvalidSessionFile { path: path.to_path_buf(), line, message }
+            }
+            other => other,
+        }
+    }
+
+    /// Short machine readable name of the error, used in structured output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Io { .. } => "io",
+            Error::YamlParse { .. } => "yaml_parse",
+            Error::InvalidRemminaFile { .. } => "invalid_remmina_file",
+            Error::UnsupportedProtocol { .. } => "unsupported_protocol",
+            Error::Conflict { .. } => "conflict",
+            Error::Secret(_) => "secret",
+            Error::InvalidRules { .. } => "invalid_rules",
+            Error::InvalidSessionFile { .. } => "invalid_session_file",
+            Error::Usage(_) => "usage",
+            Error::Cancelled => "cancelled",
+        }
+    }
+
+    /// Process exit code for the error
+    ///
+    /// # Returns
+    /// * `u8` - 10 I/O, 11 YAML, 12 invalid Remmina file, 13 unsupported protocol, 14 conflict, 15 secret,
+    ///   16 invalid rules, 17 invalid session file, 2 usage (as clap), 0 when the user cancelled (not a failure)
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Io { .. } => 10,
+            Error::YamlParse { .. } => 11,
+            Error::InvalidRemminaFile { .. } => 12,
+            Error::UnsupportedProtocol { .. } => 13,
+            Error::Conflict { .. } => 14,
+            Error::Secret(_) => 15,
+            Error::InvalidRules { .. } => 16,
+            Error::InvalidSessionFile { .. } => 17,
+            Error::Usage(_) => EXIT_USAGE,
+            Error::Cancelled => EXIT_OK,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Error::YamlParse { path, line: Some(line), column: Some(column), message } => {
+                write!(f, "{}:{line}:{column}: invalid YAML: {message}", path.display())
+            }
+            Error::YamlParse { path, message, .. } => write!(f, "{}: invalid YAML: {message}", path.display()),
+            Error::InvalidRemminaFile { path, line: Some(line), message } => {
+                write!(f, "{}: line {line}: {message}", path.display())
+            }
+            Error::InvalidRemminaFile { path, message, .. } => write!(f, "{}: {message}", path.display()),
+            Error::UnsupportedProtocol { path, protocol } => {
+                write!(f, "{}: protocol {protocol} cannot be imported", path.display())
+            }
+            Error::Conflict { name, reason } => write!(f, "'{name}': {reason}"),
+            Error::Secret(message) => f.write_str(message),
+            Error::InvalidRules { path, message } => write!(f, "{}: invalid rules: {message}", path.display()),
+            Error::InvalidSessionFile { path, line: Some(line), message } => {
+                write!(f, "{}: line {line}: {message}", path.display())
+            }
+            Error::InvalidSessionFile { path, message, .. } => write!(f, "{}: {message}", path.display()),
+            Error::Usage(message) => f.write_str(message),
+            Error::Cancelled => f.write_str("Operation cancelled by user"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
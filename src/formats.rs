@@ -0,0 +1,636 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub use crate::connection::Connection;
+use crate::convert::{ConvertOptions, convert_connections};
+use crate::error::{Error, Result};
+use crate::group_mapping::GroupMapping;
+use crate::mobaxterm_parser::{MOBAXTERM_SCHEME, MobaXtermSessions};
+use crate::mremoteng_parser::{MREMOTENG_SCHEME, MRemoteNgConnections};
+use crate::palette::Palette;
+use crate::protocols_types::ALLOWED_PROTOCOLS_EXPORT;
+use crate::putty_parser::{KITTY_SCHEME, PUTTY_SCHEME, PuttySessions};
+use crate::remmina_secrets::RemminaPref;
+use crate::remmina_types::{RemminaFiles, RemminaProfile};
+use crate::report::{RunReport, SkippedFile};
+use crate::rules::RuleSet;
+use crate::ssh_config::SshConfigExport;
+use crate::ssh_config_parser::{SSH_CONFIG_SCHEME, SshConfig};
+use crate::tabby_parser::{ImportOptions, TabbyConfig};
+use crate::termius_parser::{TERMIUS_SCHEME, TermiusHosts};
+use crate::windows_terminal_parser::{WINDOWS_TERMINAL_SCHEME, WindowsTerminalProfiles};
+
+/// Connections read by an importer
+#[derive(Debug, Default)]
+pub struct Connections {
+    pub connections: Vec<Connection>,
+    /// Options shared by every SSH connection (e.g. "Host *" of an ssh_config), used as Tabby profile defaults
+    pub defaults: Option<Connection>,
+    /// Source ids of every connection still in the source, including the ones left out by the protocol filter,
+    /// see `TabbyConfig::prune_profiles`
+    pub sources: HashSet<String>,
+    /// Scheme of the source ids ("ssh_config:web"), None for Remmina, see `source_scheme`
+    pub scheme: Option<&'static str>,
+}
+
+/// Options of every importer
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    /// Protocols of the connections to read, case-insensitive (default: ALLOWED_PROTOCOLS_EXPORT)
+    pub protocols: Vec<String>,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions { protocols: ALLOWED_PROTOCOLS_EXPORT.iter().map(|p| p.to_string()).collect() }
+    }
+}
+
+/// Options of every exporter
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    /// If false, nothing is written (dry-run)
+    pub execute: bool,
+}
+
+/// A format connections can be read from
+pub trait Importer {
+    /// Name of the format, used with --from
+    fn name(&self) -> &'static str;
+
+    /// One line describing what is read
+    fn description(&self) -> &'static str;
+
+    /// Usual location of the file or directory, None if the format has none
+    fn default_path(&self) -> Option<PathBuf>;
+
+    /// Read the connections
+    ///
+    /// # Arguments
+    /// * `source` - File or directory to read
+    /// * `options` - Protocol filter; settings of a single format (e.g. secrets handling) are fields of its importer
+    /// * `report` - Receives the files that cannot be read (skipped files) and the secrets summary
+    /// # Returns
+    /// * `Result<Connections>` - The connections, or an error if the source itself cannot be read
+    fn import(&self, source: &Path, options: &ReadOptions, report: &mut RunReport) -> Result<Connections>;
+}
+
+/// A format connections can be written to
+pub trait Exporter {
+    /// Name of the format, used with --to
+    fn name(&self) -> &'static str;
+
+    /// One line describing what is written
+    fn description(&self) -> &'static str;
+
+    /// Usual location of the file or directory, None if the format has none
+    fn default_path(&self) -> Option<PathBuf>;
+
+    /// Write the connections
+    ///
+    /// # Arguments
+    /// * `connections` - The connections read by an importer
+    /// * `target` - File or directory to write
+    /// * `options` - Dry-run; settings of a single format (e.g. the rules of the Tabby merge) are fields of its exporter
+    /// * `report` - Receives the plan or the exported files
+    /// # Returns
+    /// * `Result<()>` - An error if the target cannot be read or written
+    fn export(&self, connections: Connections, target: &Path, options: &WriteOptions, report: &mut RunReport) -> Result<()>;
+}
+
+/// The importers and exporters selectable by name
+///
+/// # Example
+/// ```no_run
+/// use std::path::Path;
+/// use remmina_to_tabby::formats::{ReadOptions, Registry, WriteOptions};
+/// use remmina_to_tabby::report::RunReport;
+///
+/// let registry = Registry::default();
+/// let mut report = RunReport::new("convert", true);
+/// let importer = registry.importer("ssh-config").unwrap();
+/// let connections = importer.import(Path::new("/home/me/.ssh/config"), &ReadOptions::default(), &mut report)?;
+/// let exporter = registry.exporter("remmina").unwrap();
+/// exporter.export(connections, Path::new("/home/me/.local/share/remmina"), &WriteOptions { execute: true }, &mut report)?;
+/// # Ok::<(), remmina_to_tabby::Error>(())
+/// ```
+pub struct Registry {
+    importers: Vec<Box<dyn Importer>>,
+    exporters: Vec<Box<dyn Exporter>>,
+}
+
+impl Default for Registry {
+    /// The built-in formats
+    fn default() -> Self {
+        let mut registry = Registry::new();
+        registry.register_importer(Box::new(RemminaFormat::default()));
+        registry.register_importer(Box::new(TabbyFormat::default()));
+        registry.register_importer(Box::new(SshConfigFormat::default()));
+        registry.register_importer(Box::new(PuttyFormat));
+        registry.register_importer(Box::new(KittyFormat));
+        registry.register_importer(Box::new(MobaXtermFormat));
+        registry.register_importer(Box::new(MRemoteNgFormat));
+        registry.register_importer(Box::new(TermiusFormat));
+        registry.register_importer(Box::new(WindowsTerminalFormat));
+        registry.register_exporter(Box::new(RemminaFormat::default()));
+        registry.register_exporter(Box::new(TabbyFormat::default()));
+        registry.register_exporter(Box::new(SshConfigFormat::default()));
+        registry
+    }
+}
+
+/// Methods for Registry
+impl Registry {
+    /// An empty registry, see `Registry::default` for the built-in formats
+    pub fn new() -> Self {
+        Registry { importers: Vec::new(), exporters: Vec::new() }
+    }
+
+    /// Add an importer, replacing the one with the same name
+    pub fn register_importer(&mut self, importer: Box<dyn Importer>) {
+        self.importers.retain(|i| i.name() != importer.name());
+        self.importers.push(importer);
+    }
+
+    /// Add an exporter, replacing the one with the same name
+    pub fn register_exporter(&mut self, exporter: Box<dyn Exporter>) {
+        self.exporters.retain(|e| e.name() != exporter.name());
+        self.exporters.push(exporter);
+    }
+
+    /// The importer of a format, by case-insensitive name
+    pub fn importer(&self, name: &str) -> Option<&dyn Importer> {
+        self.importers.iter().find(|i| i.name().eq_ignore_ascii_case(name.trim())).map(|i| i.as_ref())
+    }
+
+    /// The exporter of a format, by case-insensitive name
+    pub fn exporter(&self, name: &str) -> Option<&dyn Exporter> {
+        self.exporters.iter().find(|e| e.name().eq_ignore_ascii_case(name.trim())).map(|e| e.as_ref())
+    }
+
+    pub fn importers(&self) -> impl Iterator<Item = &dyn Importer> {
+        self.importers.iter().map(|i| i.as_ref())
+    }
+
+    pub fn exporters(&self) -> impl Iterator<Item = &dyn Exporter> {
+        self.exporters.iter().map(|e| e.as_ref())
+    }
+}
+
+/// .remmina files of a Remmina profiles directory
+#[derive(Default)]
+pub struct RemminaFormat {
+    /// Remmina preferences used to decrypt stored passwords, None to leave secrets out
+    pub pref: Option<RemminaPref>,
+}
+
+impl Importer for RemminaFormat {
+    fn name(&self) -> &'static str {
+        "remmina"
+    }
+
+    fn description(&self) -> &'static str {
+        "Remmina profiles directory (.remmina files)"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        Some(PathBuf::from(default_remmina_dir()))
+    }
+
+    /// Notes:
+    ///     Files left out by the protocol filter or that cannot be parsed still count as sources,
+    ///     see `RemminaFiles::source_ids`
+    fn import(&self, source: &Path, options: &ReadOptions, report: &mut RunReport) -> Result<Connections> {
+        check_dir("Remmina", source)?;
+        let protocols: Vec<String> = options.protocols.iter().map(|p| p.trim().to_uppercase()).collect();
+        let files = RemminaFiles::find(&source.to_string_lossy())?;
+        let sources = files.source_ids();
+        let files = files.filter_by_protocols(&protocols, report);
+        let connections = files.export_profiles(self.pref.as_ref(), report)
+            .into_iter()
+            .map(RemminaProfile::to_connection)
+            .collect();
+        Ok(Connections { connections, defaults: None, sources, scheme: None })
+    }
+}
+
+impl Exporter for RemminaFormat {
+    fn name(&self) -> &'static str {
+        "remmina"
+    }
+
+    fn description(&self) -> &'static str {
+        "One .remmina file per connection, existing files are kept"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        Some(PathBuf::from(default_remmina_dir()))
+    }
+
+    fn export(&self, connections: Connections, target: &Path, options: &WriteOptions, report: &mut RunReport) -> Result<()> {
+        check_dir("Remmina", target)?;
+        let count = RemminaFiles::write_profiles(target, &connections.connections, options.execute, report)?;
+        eprintln!("\n✅ {count} of {} connections exported to Remmina files.\n", connections.connections.len());
+        Ok(())
+    }
+}
+
+/// Tabby config.yaml of a Tabby config directory
+///
+/// Notes:
+///     The fields only apply to the export, they are the `ConvertOptions` of the merge into the config
+#[derive(Default)]
+pub struct TabbyFormat {
+    /// Rules transforming the connections before their import
+    pub rules: Option<RuleSet>,
+    /// Template of the Tabby profile names, see `TEMPLATE_PLACEHOLDERS` (default: "{name}")
+    pub name_template: Option<String>,
+    /// How nested groups are mapped onto Tabby groups
+    pub groups: GroupMapping,
+    /// Colors, icons and sorting weights of the created profiles
+    pub palette: Palette,
+    /// How profiles already in the Tabby config are handled (sync, conflicts)
+    pub import: ImportOptions,
+    /// Remove the imported profiles whose connection no longer exists, and the groups they leave empty
+    pub prune: bool,
+}
+
+impl Importer for TabbyFormat {
+    fn name(&self) -> &'static str {
+        "tabby"
+    }
+
+    fn description(&self) -> &'static str {
+        "SSH profiles of a Tabby config directory (config.yaml)"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        Some(PathBuf::from(default_tabby_dir()))
+    }
+
+    /// Notes:
+    ///     Connections keep the source id of the Tabby profile (`remminaSource`), and Tabby profile ids are no
+    ///     source ids, so pruning a target with connections read from Tabby removes nothing
+    fn import(&self, source: &Path, options: &ReadOptions, _report: &mut RunReport) -> Result<Connections> {
+        check_dir("Tabby", source)?;
+        let config = TabbyConfig::load_from_dir(&source.to_string_lossy())?;
+        let ssh_selected = options.protocols.iter().any(|p| p.trim().eq_ignore_ascii_case("SSH"));
+        let connections = if ssh_selected { config.connections() } else { Vec::new() };
+        Ok(Connections { connections, defaults: None, sources: HashSet::new(), scheme: Some(Importer::name(self)) })
+    }
+}
+
+impl Exporter for TabbyFormat {
+    fn name(&self) -> &'static str {
+        "tabby"
+    }
+
+    fn description(&self) -> &'static str {
+        "Merge into the Tabby config.yaml, after a copy to config.yaml.bak"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        Some(PathBuf::from(default_tabby_dir()))
+    }
+
+    fn export(&self, connections: Connections, target: &Path, options: &WriteOptions, report: &mut RunReport) -> Result<()> {
+        check_dir("Tabby", target)?;
+        let mut config = TabbyConfig::load_from_dir(&target.to_string_lossy())?;
+        let merge = ConvertOptions {
+            rules: self.rules.as_ref(),
+            name_template: self.name_template.clone(),
+            groups: self.groups.clone(),
+            palette: self.palette.clone(),
+            import: self.import.clone(),
+            prune: self.prune,
+            ..Default::default()
+        };
+        let plan = convert_connections(connections, &mut config, merge)?;
+        let changes = plan.changes();
+        report.plan = Some(plan);
+        if changes == 0 {
+            eprintln!("\n✅ Tabby config is up to date.\n");
+            return Ok(());
+        }
+
+        let config_path = target.join("config.yaml");
+        if !options.execute {
+            eprintln!("Dry-run would save {changes} changes to {}", config_path.display());
+            return Ok(());
+        }
+        let backup_path = target.join("config.yaml.bak");
+        fs::copy(&config_path, &backup_path).map_err(|e| Error::io(&config_path, e))?;
+        eprintln!("\nBackup of {} created: {}\n", config_path.display(), backup_path.display());
+        config.save_to_path(&config_path.to_string_lossy())?;
+        eprintln!("Tabby config saved to {}", config_path.display());
+        report.saved_to = Some(config_path);
+        Ok(())
+    }
+}
+
+/// OpenSSH client config (~/.ssh/config)
+///
+/// Notes:
+///     The field only applies to the export, see `SshConfigExport`
+#[derive(Default)]
+pub struct SshConfigFormat {
+    /// Group layout and managed region of the written file
+    pub export: SshConfigExport,
+}
+
+impl Importer for SshConfigFormat {
+    fn name(&self) -> &'static str {
+        "ssh-config"
+    }
+
+    fn description(&self) -> &'static str {
+        "OpenSSH client config, Host blocks and the files it includes"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        Some(PathBuf::from(default_ssh_config()))
+    }
+
+    /// Notes:
+    ///     See `SshConfig::load`, the options of every host become the `defaults`
+    fn import(&self, source: &Path, options: &ReadOptions, _report: &mut RunReport) -> Result<Connections> {
+        let config = SshConfig::load(source)?;
+        let sources = config.profiles.iter().filter_map(|p| p.source_id.clone()).collect();
+        let ssh_selected = options.protocols.iter().any(|p| p.trim().eq_ignore_ascii_case("SSH"));
+        let connections = if ssh_selected { config.profiles } else { Vec::new() };
+        Ok(Connections { connections, defaults: Some(config.defaults), sources, scheme: Some(SSH_CONFIG_SCHEME) })
+    }
+}
+
+impl Exporter for SshConfigFormat {
+    fn name(&self) -> &'static str {
+        "ssh-config"
+    }
+
+    fn description(&self) -> &'static str {
+        "OpenSSH client config, the file (or only its managed region) is replaced after a copy to <file>.bak"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        Some(PathBuf::from(default_ssh_config()))
+    }
+
+    /// Notes:
+    ///     With `managed` the existing file is read, only the region between the markers is replaced
+    fn export(&self, connections: Connections, target: &Path, options: &WriteOptions, report: &mut RunReport) -> Result<()> {
+        let existing = if self.export.managed && target.exists() {
+            Some(fs::read_to_string(target).map_err(|e| Error::io(target, e))?)
+        } else {
+            None
+        };
+        let files = self.export.files(&connections.connections, target, existing.as_deref())?;
+        self.export.write(&files, options.execute, report)
+    }
+}
+
+/// PuTTY sessions exported from the registry (.reg)
+pub struct PuttyFormat;
+
+impl Importer for PuttyFormat {
+    fn name(&self) -> &'static str {
+        "putty"
+    }
+
+    fn description(&self) -> &'static str {
+        "PuTTY (or KiTTY) sessions exported from the Windows registry (.reg)"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Notes:
+    ///     See `PuttySessions::load_reg`
+    fn import(&self, source: &Path, options: &ReadOptions, report: &mut RunReport) -> Result<Connections> {
+        let sessions = PuttySessions::load_reg(source)?;
+        Ok(Connections {
+            connections: filter_protocols(sessions.profiles, options, report),
+            defaults: None,
+            sources: sessions.sources,
+            scheme: Some(PUTTY_SCHEME),
+        })
+    }
+}
+
+/// Sessions directory of a portable KiTTY
+pub struct KittyFormat;
+
+impl Importer for KittyFormat {
+    fn name(&self) -> &'static str {
+        "kitty"
+    }
+
+    fn description(&self) -> &'static str {
+        "KiTTY portable Sessions directory (one file per session)"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Notes:
+    ///     See `PuttySessions::load_kitty_dir`
+    fn import(&self, source: &Path, options: &ReadOptions, report: &mut RunReport) -> Result<Connections> {
+        check_dir("KiTTY sessions", source)?;
+        let sessions = PuttySessions::load_kitty_dir(source, report)?;
+        Ok(Connections {
+            connections: filter_protocols(sessions.profiles, options, report),
+            defaults: None,
+            sources: sessions.sources,
+            scheme: Some(KITTY_SCHEME),
+        })
+    }
+}
+
+/// MobaXterm bookmarks export (.mxtsessions)
+pub struct MobaXtermFormat;
+
+impl Importer for MobaXtermFormat {
+    fn name(&self) -> &'static str {
+        "mobaxterm"
+    }
+
+    fn description(&self) -> &'static str {
+        "MobaXterm sessions export (.mxtsessions)"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Notes:
+    ///     See `MobaXtermSessions::load`
+    fn import(&self, source: &Path, options: &ReadOptions, report: &mut RunReport) -> Result<Connections> {
+        let sessions = MobaXtermSessions::load(source, report)?;
+        Ok(Connections {
+            connections: filter_protocols(sessions.profiles, options, report),
+            defaults: None,
+            sources: sessions.sources,
+            scheme: Some(MOBAXTERM_SCHEME),
+        })
+    }
+}
+
+/// mRemoteNG connection file (confCons.xml)
+pub struct MRemoteNgFormat;
+
+impl Importer for MRemoteNgFormat {
+    fn name(&self) -> &'static str {
+        "mremoteng"
+    }
+
+    fn description(&self) -> &'static str {
+        "mRemoteNG connection file (confCons.xml, not fully encrypted)"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Notes:
+    ///     See `MRemoteNgConnections::load`
+    fn import(&self, source: &Path, options: &ReadOptions, report: &mut RunReport) -> Result<Connections> {
+        let file = MRemoteNgConnections::load(source, report)?;
+        Ok(Connections {
+            connections: filter_protocols(file.profiles, options, report),
+            defaults: None,
+            sources: file.sources,
+            scheme: Some(MREMOTENG_SCHEME),
+        })
+    }
+}
+
+/// Termius hosts exported as JSON
+pub struct TermiusFormat;
+
+impl Importer for TermiusFormat {
+    fn name(&self) -> &'static str {
+        "termius"
+    }
+
+    fn description(&self) -> &'static str {
+        "Termius hosts and groups exported as JSON"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Notes:
+    ///     See `TermiusHosts::load`
+    fn import(&self, source: &Path, options: &ReadOptions, report: &mut RunReport) -> Result<Connections> {
+        let hosts = TermiusHosts::load(source)?;
+        Ok(Connections {
+            connections: filter_protocols(hosts.profiles, options, report),
+            defaults: None,
+            sources: hosts.sources,
+            scheme: Some(TERMIUS_SCHEME),
+        })
+    }
+}
+
+/// ssh command lines of the Windows Terminal profiles
+pub struct WindowsTerminalFormat;
+
+impl Importer for WindowsTerminalFormat {
+    fn name(&self) -> &'static str {
+        "windows-terminal"
+    }
+
+    fn description(&self) -> &'static str {
+        "Windows Terminal settings.json, the profiles running ssh"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Notes:
+    ///     See `WindowsTerminalProfiles::load`
+    fn import(&self, source: &Path, options: &ReadOptions, report: &mut RunReport) -> Result<Connections> {
+        let terminal = WindowsTerminalProfiles::load(source, report)?;
+        Ok(Connections {
+            connections: filter_protocols(terminal.profiles, options, report),
+            defaults: None,
+            sources: terminal.sources,
+            scheme: Some(WINDOWS_TERMINAL_SCHEME),
+        })
+    }
+}
+
+/// Keep the connections of the selected protocols, the others are reported as filtered like .remmina files
+fn filter_protocols(connections: Vec<Connection>, options: &ReadOptions, report: &mut RunReport) -> Vec<Connection> {
+    let (kept, filtered): (Vec<_>, Vec<_>) = connections.into_iter().partition(|connection| {
+        options.protocols.iter().any(|p| p.trim().eq_ignore_ascii_case(connection.protocol.as_str()))
+    });
+    for connection in filtered {
+        let protocol = connection.protocol.as_str().to_uppercase();
+        eprintln!(" └── '{}' uses protocol {protocol}, filtered out.", connection.name.as_deref().unwrap_or_default());
+        report.skipped_files.push(SkippedFile {
+            path: connection.path,
+            kind: "filtered".to_string(),
+            reason: format!("'{}': protocol {protocol} not selected", connection.name.unwrap_or_default()),
+        });
+    }
+    kept
+}
+
+/// Fail if `dir` is not an existing directory: Io if it cannot be read, Usage if it is a file
+pub fn check_dir(kind: &str, dir: impl AsRef<Path>) -> Result<()> {
+    let dir = dir.as_ref();
+    let metadata = fs::metadata(dir).map_err(|e| Error::io(dir, e))?;
+    if !metadata.is_dir() {
+        return Err(Error::Usage(format!("{}: the {kind} directory is a file", dir.display())));
+    }
+    Ok(())
+}
+
+/// Get default Remmina directory based on OS (Currently only Linux supported)
+pub fn default_remmina_dir() -> String {
+    #[cfg(target_os = "linux")]
+    {
+        format!("{}/.local/share/remmina", std::env::var("HOME").unwrap_or_default())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        String::from("./remmina")
+    }
+}
+
+/// Get default OpenSSH client configuration path (~/.ssh/config)
+pub fn default_ssh_config() -> String {
+    format!("{}/.ssh/config", std::env::var("HOME").unwrap_or_default())
+}
+
+/// Get default Tabby directory based on OS
+/// # Returns
+/// * `String` - Default Tabby configuration directory path
+/// # Behavior
+/// * On Linux: ~/.config/tabby
+/// * On Windows: %APPDATA%\Tabby
+/// * On macOS: ~/Library/Application Support/tabby
+/// * On other OS: ./tabby
+pub fn default_tabby_dir() -> String {
+    #[cfg(target_os = "linux")]
+    {
+        format!("{}/.config/tabby", std::env::var("HOME").unwrap_or_default())
+    }
+    #[cfg(target_os = "windows")]
+    {
+        format!("{}/Tabby", std::env::var("APPDATA").unwrap_or_default())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        format!("{}/Library/Application Support/tabby", std::env::var("HOME").unwrap_or_default())
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    {
+        String::from("./tabby")
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use clap::ValueEnum;
+
+use crate::connection::Connection;
+
+/// How nested Remmina groups ("Prod/DB/Primary") are mapped onto Tabby groups
+///
+/// Notes:
+///     Tabby has no sub groups (see `TabbyConfig::add_group`), every mode but `literal`
+///     turns the Remmina group path into a single level
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum GroupMode {
+    /// Group named after the full path: "Prod/DB/Primary"
+    #[default]
+    Literal,
+    /// Path joined with the separator: "Prod › DB › Primary"
+    Flatten,
+    /// Last segment only: "Primary", parents are added back when two paths share their leaf
+    Leaf,
+    /// First segment only: "Prod"
+    Root,
+    /// First segment as group, the rest of the path moved into the profile name: "DB › Primary › db-01"
+    PrefixName,
+}
+
+/// Group mode with its separator
+#[derive(Debug, Clone)]
+pub struct GroupMapping {
+    pub mode: GroupMode,
+    /// Joins path segments in group (flatten, leaf) and profile names (prefix-name)
+    pub separator: String,
+}
+
+impl Default for GroupMapping {
+    fn default() -> Self {
+        GroupMapping { mode: GroupMode::Literal, separator: " › ".to_string() }
+    }
+}
+
+/// Methods for GroupMapping
+impl GroupMapping {
+    /// Rewrites the group (and for prefix-name the name) of the profiles
+    ///
+    /// # Arguments
+    /// * `profiles` - The connections about to be imported
+    /// # Behavior
+    /// * Segments are split on "/" and trimmed, empty segments are dropped
+    /// * flatten: two different paths giving the same group get a counter ("A › B (2)")
+    /// * leaf: two different paths with the same leaf keep as many parents as needed to tell them apart
+    /// * root, prefix-name: profiles that end up with the same name while coming from different paths
+    ///   get their original path appended ("web (Prod › A)"), or a counter if that is not enough
+    pub fn apply(&self, profiles: &mut [Connection]) {
+        if self.mode == GroupMode::Literal {
+            return;
+        }
+
+        let paths: BTreeSet<Vec<String>> = profiles.iter().map(|p| segments(p.group.as_deref())).collect();
+        let groups = match self.mode {
+            GroupMode::Leaf => self.unique_leaves(&paths),
+            _ => self.flattened(&paths),
+        };
+
+        let originals: Vec<(Option<String>, Option<String>)> = profiles.iter().map(|p| (p.group.clone(), p.name.clone())).collect();
+        for profile in profiles.iter_mut() {
+            let path = segments(profile.group.as_deref());
+            if path.is_empty() {
+                continue;
+            }
+            let group = match self.mode {
+                GroupMode::Root | GroupMode::PrefixName => path[0].clone(),
+                _ => groups[&path].clone(),
+            };
+            if self.mode == GroupMode::PrefixName && path.len() > 1 {
+                let mut name_parts = path[1..].to_vec();
+                name_parts.push(profile.name.clone().unwrap_or_default());
+                profile.name = Some(name_parts.join(&self.separator));
+            }
+            eprintln!(" └── Group '{}' mapped to '{group}'", profile.group.as_deref().unwrap_or_default());
+            profile.group = Some(group);
+        }
+
+        if matches!(self.mode, GroupMode::Root | GroupMode::PrefixName) {
+            self.rename_merged(profiles, &originals);
+        }
+    }
+
+    /// Group of every path joined with the separator, with a counter for the paths giving an existing group
+    fn flattened(&self, paths: &BTreeSet<Vec<String>>) -> BTreeMap<Vec<String>, String> {
+        let mut groups = BTreeMap::new();
+        let mut used = BTreeSet::new();
+        for path in paths.iter().filter(|p| !p.is_empty()) {
+            let base = path.join(&self.separator);
+            let mut group = base.clone();
+            let mut counter = 2;
+            while !used.insert(group.clone()) {
+                group = format!("{base} ({counter})");
+                counter += 1;
+            }
+            groups.insert(path.clone(), group);
+        }
+        groups
+    }
+
+    /// Shortest trailing part of every path that no other path ends with
+    fn unique_leaves(&self, paths: &BTreeSet<Vec<String>>) -> BTreeMap<Vec<String>, String> {
+        let tail = |path: &[String], len: usize| path[path.len() - len.min(path.len())..].to_vec();
+        paths.iter()
+            .filter(|p| !p.is_empty())
+            .map(|path| {
+                let len = (1..=path.len())
+                    .find(|&len| !paths.iter().any(|other| other != path && tail(other, len) == tail(path, len)))
+                    .unwrap_or(path.len());
+                (path.clone(), tail(path, len).join(&self.separator))
+            })
+            .collect()
+    }
+
+    /// Makes the names unique again among profiles that were in different groups before the mapping
+    fn rename_merged(&self, profiles: &mut [Connection], originals: &[(Option<String>, Option<String>)]) {
+        let mut by_name: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (index, profile) in profiles.iter().enumerate() {
+            by_name.entry(profile.name.clone().unwrap_or_default()).or_default().push(index);
+        }
+
+        let mut taken: BTreeSet<String> = by_name.keys().cloned().collect();
+        for (name, indexes) in by_name {
+            let sources: BTreeSet<&Option<String>> = indexes.iter().map(|&i| &originals[i].0).collect();
+            if sources.len() < 2 {
+                continue;
+            }
+            for &index in &indexes {
+                let path = segments(originals[index].0.as_deref()).join(&self.separator);
+                let base = format!("{name} ({path})");
+                let mut renamed = base.clone();
+                let mut counter = 2;
+                while !taken.insert(renamed.clone()) {
+                    renamed = format!("{base} {counter}");
+                    counter += 1;
+                }
+                eprintln!(" └── Profile '{name}' renamed to '{renamed}' (same name in another group)");
+                profiles[index].name = Some(renamed);
+            }
+        }
+    }
+}
+
+/// Segments of a Remmina group path
+fn segments(group: Option<&str>) -> Vec<String> {
+    group.unwrap_or_default()
+        .split('/')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,41 @@
+//! Convert Remmina connection profiles into Tabby profiles
+//!
+//! The `remmina-to-tabby` binary is a thin client of this library, other tools can use
+//! [`convert`] for a whole directory or the parser ([`RemminaFiles`], [`RemminaProfile`])
+//! and the importer ([`TabbyConfig`]) directly.
+//!
+//! Every format is an [`Importer`] and/or an [`Exporter`] of [`Connection`]s, the [`Registry`]
+//! selects them by name so any two formats can be converted into each other. A [`Connection`]
+//! is format-neutral, each format converts its own entries to and from it.
+
+pub mod connection;
+pub mod convert;
+pub mod error;
+pub mod formats;
+pub mod group_mapping;
+pub mod mobaxterm_parser;
+pub mod mremoteng_parser;
+pub mod palette;
+pub mod protocols_types;
+pub mod putty_parser;
+pub mod remmina_keyfile;
+mod remmina_parser;
+pub mod remmina_secrets;
+pub mod remmina_types;
+pub mod report;
+pub mod rules;
+pub mod ssh_config;
+pub mod ssh_config_parser;
+pub mod tabby_parser;
+pub mod tabby_vault;
+pub mod termius_parser;
+pub mod windows_terminal_parser;
+
+pub use convert::{ConvertOptions, convert, convert_from, convert_ssh_config};
+pub use error::{Error, Result};
+pub use connection::{AuthMethod, Connection, JumpHost};
+pub use formats::{Connections, Exporter, Importer, ReadOptions, Registry, WriteOptions};
+pub use protocols_types::ConnectionProtocols;
+pub use remmina_types::{RemminaFiles, RemminaProfile};
+pub use report::Plan;
+pub use tabby_parser::TabbyConfig;
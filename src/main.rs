@@ -1,261 +1,814 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 // use std::path::Path;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::io::{self, Write};
-
-mod remmina_parser;
-mod remmina_types;
-use remmina_types::{RemminaFiles, RemminaProfile};
-mod tabby_parser;
-use tabby_parser::TabbyConfig;
+use std::io::{self, IsTerminal, Write};
+use std::process::ExitCode;
+
+use remmina_to_tabby::formats::{Importer, ReadOptions, Registry, RemminaFormat, SshConfigFormat, TabbyFormat, WriteOptions, check_dir, default_remmina_dir, default_ssh_config, default_tabby_dir};
+use remmina_to_tabby::error::{EXIT_CHECK_FAILED, EXIT_INVALID_CONFIG, EXIT_OK, EXIT_PENDING_CHANGES};
+use remmina_to_tabby::remmina_secrets::{RemminaPref, SecretReport, SecretSink};
+use remmina_to_tabby::report::{self, OutputFormat, RunReport};
+use remmina_to_tabby::tabby_vault::TabbyVault;
+use remmina_to_tabby::group_mapping::{GroupMapping, GroupMode};
+use remmina_to_tabby::palette::{ColorMode, Palette, WeightMode, parse_group_color, parse_mapping};
+use remmina_to_tabby::connection::check_template;
+use remmina_to_tabby::rules::RuleSet;
+use remmina_to_tabby::ssh_config::{GroupLayout, SshConfigExport};
+use remmina_to_tabby::tabby_parser::{ConflictPrompt, ConflictStrategy, ImportOptions, Profile};
+use remmina_to_tabby::{Connection, ConvertOptions, Error, RemminaFiles, Result, TabbyConfig, convert_from};
 
 mod ascii_art;
 use ascii_art::show_ascii_art_header;
-mod protocols_types;
 
 /// Remmina to Tabby converter
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
-struct Args {
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Format of the report written to stdout, logs always go to stderr
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show the profiles parsed from Remmina files
+    List(ListArgs),
+    /// Check the protocol of every Remmina file (exit code 3 if some cannot be imported)
+    Check(CheckArgs),
+    /// Import Remmina profiles into the Tabby config
+    Import(ImportArgs),
+    /// Show the changes an import would make to the Tabby config (exit code 5 if any)
+    Diff(DiffArgs),
+    /// Check the Tabby config for inconsistencies (exit code 4 if any)
+    Validate(ValidateArgs),
+    /// Export Tabby SSH profiles to .remmina files
+    Export(ExportArgs),
+    /// Export SSH profiles (Remmina files or Tabby config) as OpenSSH Host blocks
+    ExportSshConfig(ExportSshConfigArgs),
+    /// Convert connections from any supported format to another (--from, --to)
+    Convert(ConvertArgs),
+}
+
+/// Remmina profiles to read
+#[derive(Args, Debug)]
+struct RemminaSource {
     /// Path to Remmina directory
     #[arg(long, default_value_t = default_remmina_dir())]
     remmina_dir: String,
 
+    /// Protocol to filter (e.g. SSH, RDP, VNC)
+    #[arg(long, default_value = "SSH")]
+    protocol: String,
+}
+
+/// Tabby configuration to use
+#[derive(Args, Debug)]
+struct TabbyTarget {
     /// Path to Tabby directory
     #[arg(long, default_value_t = default_tabby_dir())]
     tabby_dir: String,
+}
 
-    /// Protocol to filter (e.g. SSH, RDP, VNC)
-    #[arg(long, default_value = "SSH")]
-    protocol: String,
+#[derive(Args, Debug)]
+struct ListArgs {
+    #[command(flatten)]
+    remmina: RemminaSource,
+}
+
+#[derive(Args, Debug)]
+struct CheckArgs {
+    /// Path to Remmina directory
+    #[arg(long, default_value_t = default_remmina_dir())]
+    remmina_dir: String,
+}
 
-    /// Check protocols in Remmina files
+/// How the Remmina profiles are merged into the Tabby config, shared by import and diff
+#[derive(Args, Debug)]
+struct MergeArgs {
+    /// Update profiles imported earlier from the same Remmina file (host, port, user, auth, group)
     #[arg(long, default_value_t = false)]
-    remmina_check: bool,
+    sync: bool,
 
-    /// Really execute export (otherwise dry-run)
+    /// Remove imported profiles whose .remmina file no longer exists, and the groups left empty
+    #[arg(long, default_value_t = false)]
+    prune: bool,
+
+    /// YAML or TOML file of rules transforming the profiles before their import
+    #[arg(long)]
+    rules: Option<PathBuf>,
+
+    /// Template of the Tabby profile names, e.g. "{group} / {name} ({user}@{server})"
+    #[arg(long, value_parser = check_template)]
+    name_template: Option<String>,
+
+    /// How nested Remmina groups (Prod/DB) are mapped onto Tabby groups
+    #[arg(long, value_enum, default_value_t = GroupMode::Literal)]
+    group_mode: GroupMode,
+
+    /// Separator of the path segments for --group-mode flatten, leaf and prefix-name
+    #[arg(long, default_value = " › ")]
+    group_separator: String,
+
+    /// Color of the created profiles: the same for all, or derived from their group
+    #[arg(long, value_enum, default_value_t = ColorMode::Default)]
+    colors: ColorMode,
+
+    /// Color of the profiles of a group, e.g. "Production=#d9534f" (repeatable, wins over --colors)
+    #[arg(long, value_name = "GROUP=COLOR", value_parser = parse_group_color)]
+    group_color: Vec<(String, String)>,
+
+    /// Icon of the profiles of a protocol, e.g. "RDP=fab fa-windows" (repeatable)
+    #[arg(long, value_name = "PROTOCOL=ICON", value_parser = parse_mapping)]
+    protocol_icon: Vec<(String, String)>,
+
+    /// Sorting weight of the created profiles: the same for all, or following the Remmina order within groups
+    #[arg(long, value_enum, default_value_t = WeightMode::Default)]
+    weights: WeightMode,
+
+    /// What to do with a profile whose name is already used in Tabby
+    #[arg(long, value_enum, default_value_t = ConflictStrategy::Skip)]
+    on_conflict: ConflictStrategy,
+
+    /// Also detect conflicts with profiles having the same host, port and user
+    #[arg(long, default_value_t = false)]
+    match_identity: bool,
+
+    /// Suffix appended by --on-conflict rename, {group} is replaced by the Remmina group
+    #[arg(long, default_value = "(remmina)")]
+    rename_suffix: String,
+}
+
+impl MergeArgs {
+    /// Import options, `ask` is None for commands that must not prompt (conflicts are then skipped)
+    fn import_options(&self, ask: Option<ConflictPrompt>) -> ImportOptions {
+        ImportOptions {
+            sync: self.sync,
+            on_conflict: self.on_conflict,
+            match_identity: self.match_identity,
+            rename_suffix: self.rename_suffix.clone(),
+            ask,
+        }
+    }
+
+    fn load_rules(&self) -> Result<Option<RuleSet>> {
+        self.rules.as_deref().map(RuleSet::load).transpose()
+    }
+
+    fn group_mapping(&self) -> GroupMapping {
+        GroupMapping { mode: self.group_mode, separator: self.group_separator.clone() }
+    }
+
+    fn palette(&self) -> Palette {
+        Palette {
+            colors: self.colors,
+            group_colors: self.group_color.iter().cloned().collect(),
+            protocol_icons: self.protocol_icon.iter().cloned().collect(),
+            weights: self.weights,
+        }
+    }
+
+    /// The options set to something else than their default, they only apply to a merge into Tabby
+    fn given(&self) -> Vec<&'static str> {
+        let options = [
+            ("--sync", self.sync),
+            ("--prune", self.prune),
+            ("--rules", self.rules.is_some()),
+            ("--name-template", self.name_template.is_some()),
+            ("--group-mode", self.group_mode != GroupMode::Literal),
+            ("--group-separator", self.group_separator != " › "),
+            ("--colors", self.colors != ColorMode::Default),
+            ("--group-color", !self.group_color.is_empty()),
+            ("--protocol-icon", !self.protocol_icon.is_empty()),
+            ("--weights", self.weights != WeightMode::Default),
+            ("--on-conflict", self.on_conflict != ConflictStrategy::Skip),
+            ("--match-identity", self.match_identity),
+            ("--rename-suffix", self.rename_suffix != "(remmina)"),
+        ];
+        options.into_iter().filter(|(_, given)| *given).map(|(option, _)| option).collect()
+    }
+}
+
+/// Format the connections are read from, shared by import and diff
+#[derive(Args, Debug)]
+struct FormatSource {
+    /// Format to read, e.g. remmina or ssh-config (an unknown name lists the available ones)
+    #[arg(long, value_parser = importer_name, default_value = "remmina")]
+    from: String,
+
+    /// File or directory to read (default: --remmina-dir for remmina, the usual location of the other formats)
+    #[arg(long)]
+    from_path: Option<PathBuf>,
+}
+
+impl FormatSource {
+    /// The --from importer of the registry
+    fn importer<'a>(&self, registry: &'a Registry) -> &'a dyn Importer {
+        let Some(importer) = registry.importer(&self.from) else {
+            unreachable!("formats are validated by the argument parser");
+        };
+        importer
+    }
+
+    /// --from-path, or the Remmina directory, or the usual location of the format
+    fn path(&self, registry: &Registry, remmina: &RemminaSource) -> Result<PathBuf> {
+        let importer = self.importer(registry);
+        let default = match importer.name() {
+            "remmina" => Some(PathBuf::from(&remmina.remmina_dir)),
+            _ => importer.default_path(),
+        };
+        self.from_path.clone().or(default).ok_or_else(|| missing_path(importer.name(), "--from-path"))
+    }
+}
+
+/// How the ssh_config file is written, shared by export-ssh-config and convert --to ssh-config
+#[derive(Args, Debug)]
+struct SshConfigLayoutArgs {
+    /// Groups as comments in the file, or one file per group included from it ("<ssh-config>.d/group.conf")
+    #[arg(long, value_enum, default_value_t = GroupLayout::Comments)]
+    groups: GroupLayout,
+
+    /// Only update the region between the remmina-to-tabby markers, keeping the rest of the file
+    #[arg(long, default_value_t = false)]
+    managed: bool,
+}
+
+impl SshConfigLayoutArgs {
+    fn export(&self) -> SshConfigExport {
+        SshConfigExport { layout: self.groups, managed: self.managed }
+    }
+
+    /// The options set to something else than their default, they only apply to the ssh-config exporter
+    fn given(&self) -> Vec<&'static str> {
+        let options = [("--groups", self.groups != GroupLayout::Comments), ("--managed", self.managed)];
+        options.into_iter().filter(|(_, given)| *given).map(|(option, _)| option).collect()
+    }
+}
+
+#[derive(Args, Debug)]
+struct ImportArgs {
+    #[command(flatten)]
+    remmina: RemminaSource,
+
+    #[command(flatten)]
+    source: FormatSource,
+
+    #[command(flatten)]
+    tabby: TabbyTarget,
+
+    #[command(flatten)]
+    merge: MergeArgs,
+
+    /// Really execute import (otherwise dry-run)
     #[arg(long, default_value_t = false)]
     execute: bool,
 
     /// Skip all confirmations
     #[arg(long, default_value_t = false)]
     yes: bool,
+
+    /// Decrypt passwords stored in Remmina profiles (kept in memory, never printed, --from remmina only)
+    #[arg(long, default_value_t = false)]
+    with_secrets: bool,
+
+    /// Path to remmina.pref (default: next to the profiles, then ~/.config/remmina/remmina.pref)
+    #[arg(long)]
+    remmina_pref: Option<String>,
+
+    /// Store decrypted secrets in the Tabby vault (passphrase from TABBY_VAULT_PASSPHRASE or prompted)
+    #[arg(long, default_value_t = false, requires = "with_secrets")]
+    vault: bool,
 }
 
-fn main() {
-    clear_screen();
-    show_ascii_art_header();
+#[derive(Args, Debug)]
+struct DiffArgs {
+    #[command(flatten)]
+    remmina: RemminaSource,
 
-    let args = Args::parse();
+    #[command(flatten)]
+    source: FormatSource,
 
-    let remmina_dir = &args.remmina_dir;
-    let tabby_dir = &args.tabby_dir;
-    let protocol_arg = &args.protocol;
-    
-    println!("Remmina dir: {remmina_dir}");
-    println!("Tabby dir: {tabby_dir}");
-    println!("Protocol filter: {protocol_arg}");
-
-    if !Path::new(remmina_dir).is_dir() {
-        eprintln!("\n 🚫 Error: Remmina directory '{remmina_dir}' does not exist or is not a directory.\n");
-        std::process::exit(1);
-    }
+    #[command(flatten)]
+    tabby: TabbyTarget,
 
-    if !Path::new(tabby_dir).is_dir() {
-        eprintln!("\n 🚫 Error: Tabby directory '{tabby_dir}' does not exist or is not a directory.\n");
-        std::process::exit(1);
-    }
+    #[command(flatten)]
+    merge: MergeArgs,
+}
+
+#[derive(Args, Debug)]
+struct ValidateArgs {
+    #[command(flatten)]
+    tabby: TabbyTarget,
+}
+
+#[derive(Args, Debug)]
+struct ExportArgs {
+    #[command(flatten)]
+    tabby: TabbyTarget,
+
+    /// Directory receiving the .remmina files
+    #[arg(long, default_value_t = default_remmina_dir())]
+    remmina_dir: String,
+
+    /// Really write the files (otherwise dry-run)
+    #[arg(long, default_value_t = false)]
+    execute: bool,
+}
+
+/// Where the profiles exported to ssh_config are read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ProfileSource {
+    Remmina,
+    Tabby,
+}
+
+#[derive(Args, Debug)]
+struct ExportSshConfigArgs {
+    /// Read the profiles from the .remmina files or from the Tabby config
+    #[arg(long, value_enum, default_value_t = ProfileSource::Remmina)]
+    source: ProfileSource,
+
+    /// Path to Remmina directory (--source remmina)
+    #[arg(long, default_value_t = default_remmina_dir())]
+    remmina_dir: String,
+
+    #[command(flatten)]
+    tabby: TabbyTarget,
 
-    let mut tabby_config = match TabbyConfig::load_from_dir(tabby_dir) {
-        Ok(config) => {
-            println!("\nLoaded Tabby config from {tabby_dir}\n");
+    /// ssh_config file to write
+    #[arg(long, default_value_t = default_ssh_config())]
+    ssh_config: String,
 
-            config
+    #[command(flatten)]
+    layout: SshConfigLayoutArgs,
+
+    /// Really write the files (otherwise dry-run)
+    #[arg(long, default_value_t = false)]
+    execute: bool,
+}
+
+#[derive(Args, Debug)]
+struct ConvertArgs {
+    /// Format to read, e.g. remmina or ssh-config (an unknown name lists the available ones)
+    #[arg(long, value_parser = importer_name)]
+    from: String,
+
+    /// File or directory to read (default: the usual location of the --from format)
+    #[arg(long)]
+    from_path: Option<PathBuf>,
+
+    /// Format to write, e.g. tabby or remmina (an unknown name lists the available ones)
+    #[arg(long, value_parser = exporter_name, default_value = "tabby")]
+    to: String,
+
+    /// File or directory to write (default: the usual location of the --to format)
+    #[arg(long)]
+    to_path: Option<PathBuf>,
+
+    /// Protocol to filter (e.g. SSH, RDP, VNC)
+    #[arg(long, default_value = "SSH")]
+    protocol: String,
+
+    #[command(flatten)]
+    merge: MergeArgs,
+
+    #[command(flatten)]
+    ssh_config: SshConfigLayoutArgs,
+
+    /// Really write the target (otherwise dry-run)
+    #[arg(long, default_value_t = false)]
+    execute: bool,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::List(args) => run_list(&args),
+        Command::Check(args) => run_check(&args),
+        Command::Import(args) => run_import(&args),
+        Command::Diff(args) => run_diff(&args),
+        Command::Validate(args) => run_validate(&args),
+        Command::Export(args) => run_export(&args),
+        Command::ExportSshConfig(args) => run_export_ssh_config(&args),
+        Command::Convert(args) => run_convert(&args),
+    };
+    let result = result.and_then(|(report, code)| report.print(cli.output).map(|_| code));
+    match result {
+        Ok(code) => ExitCode::from(code),
+        Err(Error::Cancelled) => {
+            eprintln!("🛑 Operation cancelled by user.");
+            ExitCode::from(Error::Cancelled.exit_code())
         }
         Err(err) => {
-            eprintln!("{err}");
-            std::process::exit(1);
+            eprintln!("\n 🚫 Error: {err}\n");
+            if let Err(print_err) = report::print_error(&err, cli.output) {
+                eprintln!("{print_err}");
+            }
+            ExitCode::from(err.exit_code())
         }
-    };
+    }
+}
 
-    println!("Current number of Tabby profiles: {} and {} groups.", tabby_config.profiles.len(), tabby_config.groups.as_ref().map_or(0, |g| g.len()));
-    if !args.yes { confirm_continue(Some("\nDo you want to continue with export from Remmina?")); }
-    
-
-    // Split protocol argument by comma and trim whitespace
-    let protocols: Vec<String> = protocol_arg
-        .split(',')
-        .map(|s| s.trim().to_uppercase())
-        .collect();
-
-    // Find and print .remmina files filtered by protocols
-    // let remmina_files = RemminaFiles::find(remmina_dir).filter_by_protocols(&protocols);
-
-    // Find .remmina files with proper error handling
-    let remmina_files = match RemminaFiles::find(remmina_dir) {
-        Ok(files) => {
-            println!("\nFound {} .remmina files\n", files.files.len());
-            let filtered_files = files.filter_by_protocols(&protocols);
-            println!("After filtering, {} .remmina files match protocols: {:?}\n", filtered_files.files.len(), protocols);
-            filtered_files
-        }
-        Err(e) => {
-            eprintln!("\n🚫 Error reading Remmina directory '{remmina_dir}': {e}\n");
-            std::process::exit(1);
-        }
-    };
+/// Show the Remmina profiles that would be imported
+fn run_list(args: &ListArgs) -> Result<(RunReport, u8)> {
+    let mut report = RunReport::new("list", false);
+    let remmina_files = find_remmina_files(&args.remmina, &mut report)?;
+    report.profiles = remmina_files.export_profiles(None, &mut report);
+    eprintln!("\n✅ Found {} importable profiles.\n", report.profiles.len());
+    Ok((report, EXIT_OK))
+}
+
+/// Check the protocol of every .remmina file
+fn run_check(args: &CheckArgs) -> Result<(RunReport, u8)> {
+    let remmina_dir = &args.remmina_dir;
+    check_dir("Remmina", remmina_dir)?;
+    let remmina_files = RemminaFiles::find(remmina_dir)?;
+
+    let mut report = RunReport::new("check", false);
+    let not_available = remmina_files.check_protocols(&mut report);
+    if not_available > 0 {
+        eprintln!("\n🟡 {not_available} of {} .remmina files cannot be imported.\n", remmina_files.files.len());
+        return Ok((report, EXIT_CHECK_FAILED));
+    }
+    eprintln!("\n✅ All {} .remmina files can be imported.\n", remmina_files.files.len());
+    Ok((report, EXIT_OK))
+}
 
+/// Import Remmina profiles into the Tabby config
+fn run_import(args: &ImportArgs) -> Result<(RunReport, u8)> {
+    clear_screen();
+    show_ascii_art_header();
 
-    if args.remmina_check {
-        remmina_files.check_protocols();
+    let mut registry = Registry::default();
+    let source = args.source.path(&registry, &args.remmina)?;
+    let source_kind = args.source.importer(&registry).name();
+    let tabby_dir = &args.tabby.tabby_dir;
+    if args.with_secrets && source_kind != "remmina" {
+        return Err(Error::Usage(format!("--with-secrets only applies to --from remmina, not to {source_kind}")));
     }
 
-    // if args.execute {
-    //     println!("Executing export_profiles...");
-    //     remmina_files.export_profiles(true);
-    // } else {
-    //     println!("Dry-run would export the following profiles:");
-    //     remmina_files.export_profiles(false);
-    // }
-
-    // for remmina_file in remmina_files.files {
-    //     println!("Found Remmina file: {}", remmina_file.display());
-    // }
-
-    let remmina_profiles: Vec<RemminaProfile>  = remmina_files.export_profiles();
-    if remmina_profiles.is_empty() {
-        println!("\n🟡 No Remmina profiles found with protocol(s): {protocols:?}\n");
-        return;
+    eprintln!("From {source_kind}: {}", source.display());
+    eprintln!("Tabby dir: {tabby_dir}");
+    eprintln!("Protocol filter: {}", args.remmina.protocol);
+
+    fs::metadata(&source).map_err(|e| Error::io(&source, e))?;
+    let mut tabby_config = load_tabby_config(tabby_dir)?;
+    let rules = args.merge.load_rules()?;
+
+    eprintln!("Current number of Tabby profiles: {} and {} groups.", tabby_config.profiles.len(), tabby_config.groups.as_ref().map_or(0, |g| g.len()));
+    if !args.yes { confirm_continue(Some(&format!("\nDo you want to continue with export from {source_kind}?")))?; }
+
+    let mut report = RunReport::new("import", args.execute);
+
+    // Load the Remmina secret used to decrypt stored passwords, the Remmina importer keeps it
+    if args.with_secrets {
+        let pref_path = args.remmina_pref.as_ref()
+            .map(PathBuf::from)
+            .or_else(|| RemminaPref::find(&source.to_string_lossy()));
+        let Some(pref_path) = pref_path else {
+            return Err(Error::Secret("--with-secrets requires remmina.pref, none found (use --remmina-pref)".to_string()));
+        };
+        let pref = RemminaPref::load(&pref_path)?;
+        eprintln!("\n🔑 Loaded Remmina secret from {}\n", pref_path.display());
+        registry.register_importer(Box::new(RemminaFormat { pref: Some(pref) }));
     }
-    println!("\n✅ Exported {} profiles from Remmina files.\n", remmina_profiles.len());
-    
-    
-    // for profile in &remmina_profiles {
-    //     println!(
-    //         "Profile: name={:?}, server={:?}, group={:?}, protocol={:?}, path={}",
-    //         profile.name,
-    //         profile.server,
-    //         profile.group,
-    //         profile.protocol,
-    //         profile.path.display()
-    //     );
-    // }
-
-    if !args.yes { confirm_continue(Some("\nDo you want to continue with import into Tabby config?")); }
 
-    // Make a backup copy of config.yaml for Tabby
-    let config_path = PathBuf::from(tabby_dir).join("config.yaml");
-    let backup_path = PathBuf::from(tabby_dir).join("config.yaml.bak");
-    
-    if args.execute {
-        if config_path.exists() {
-            match fs::copy(&config_path, &backup_path) {
-                Ok(_) => println!("\nBackup of {} created: {}\n", config_path.display(), backup_path.display()),
-                Err(e) => eprintln!("Failed to create backup: {e}"),
+    // Unlock the Tabby vault, or create it, when secrets must be stored there
+    let mut tabby_vault = if args.vault {
+        let passphrase = read_vault_passphrase()?;
+        match &tabby_config.vault {
+            Some(stored) => Some(TabbyVault::unlock(stored, &passphrase)?),
+            None => {
+                eprintln!("\n🔒 No Tabby vault found, a new one will be created");
+                Some(TabbyVault::create(&passphrase))
             }
-        } else {
-            println!("❗❗❗ No config.yaml found to backup in {tabby_dir}");
         }
     } else {
-        println!("Dry-run would create backup of {} to {}", config_path.display(), backup_path.display());
-    }
+        None
+    };
 
-    // Import Remmina profiles into Tabby config
-    let imported_count = tabby_config.import_profiles(remmina_profiles);
-    if imported_count == 0 {
-        println!("\n🟡 No new profiles were imported into Tabby config (all already exist).\n");
-        return;
+    // Convert Remmina profiles into the in-memory Tabby config
+    let mut secret_report = SecretReport::default();
+    let secret_sink: &mut dyn SecretSink = match tabby_vault.as_mut() {
+        Some(vault) => vault,
+        None => &mut secret_report,
+    };
+    let options = ConvertOptions {
+        protocols: parse_protocols(&args.remmina.protocol),
+        secret_sink: Some(secret_sink),
+        rules: rules.as_ref(),
+        name_template: args.merge.name_template.clone(),
+        groups: args.merge.group_mapping(),
+        palette: args.merge.palette(),
+        import: args.merge.import_options(if args.yes { None } else { Some(ask_conflict) }),
+        prune: args.merge.prune,
+        ..Default::default()
+    };
+    let plan = convert_from(args.source.importer(&registry), &source, &mut tabby_config, options)?;
+    let imported_count = plan.created_profiles.len();
+    let updated_count = plan.updated_profiles.len();
+    let removed_count = plan.removed_profiles.len();
+    let changes = plan.changes();
+    let found_count = plan.profiles.len();
+    report.plan = Some(plan);
+
+    if found_count == 0 && changes == 0 {
+        eprintln!("\n🟡 No {source_kind} profiles found with protocol(s): {}\n", args.remmina.protocol);
+        return Ok((report, EXIT_OK));
+    }
+    eprintln!("\n✅ Exported {found_count} profiles from {source_kind} files.\n");
+
+    if let Some(vault) = &tabby_vault {
+        eprintln!("\n🔒 {} secrets added to the Tabby vault (values not shown)", vault.added);
+    } else if args.with_secrets {
+        eprintln!("\n🔑 {} secrets handed over for imported profiles (values not shown):", secret_report.stored.len());
+        for (profile_id, kind) in &secret_report.stored {
+            eprintln!(" └── {profile_id}: {}", kind.as_str());
+        }
+    }
+    if changes == 0 {
+        eprintln!("\n🟡 No new profiles were imported into Tabby config (all already exist).\n");
+        return Ok((report, EXIT_OK));
     } else {
-        println!("\n✅ Imported {imported_count} new profiles into Tabby config.\n");
+        eprintln!("\n✅ Imported {imported_count} new profiles, updated {updated_count} and removed {removed_count} profiles in Tabby config.\n");
     }
 
-    if args.execute {
-        // Save updated Tabby config back to config.yaml
-        tabby_config.save_to_path(&config_path.to_string_lossy())
-            .unwrap_or_else(|err| {
-                eprintln!("Failed to save Tabby config: {err}");
-                std::process::exit(1);
-            });
-        println!("Tabby config saved to {}", config_path.display());
+    if !args.yes { confirm_continue(Some("\nDo you want to continue with saving the Tabby config?"))?; }
+
+    let config_path = PathBuf::from(tabby_dir).join("config.yaml");
+    let backup_path = PathBuf::from(tabby_dir).join("config.yaml.bak");
+    if !args.execute {
+        eprintln!("Dry-run would create backup of {} to {}", config_path.display(), backup_path.display());
+        eprintln!("Dry-run would save updated Tabby config to {}", config_path.display());
+        return Ok((report, EXIT_OK));
+    }
+
+    // Make a backup copy of config.yaml for Tabby
+    if config_path.exists() {
+        match fs::copy(&config_path, &backup_path) {
+            Ok(_) => eprintln!("\nBackup of {} created: {}\n", config_path.display(), backup_path.display()),
+            Err(e) => eprintln!("Failed to create backup: {e}"),
+        }
     } else {
-        println!("Dry-run would save updated Tabby config to {}", config_path.display());
+        eprintln!("❗❗❗ No config.yaml found to backup in {tabby_dir}");
     }
 
+    // Save updated Tabby config back to config.yaml
+    if let Some(vault) = &tabby_vault {
+        tabby_config.vault = Some(vault.seal()?);
+    }
+    tabby_config.save_to_path(&config_path.to_string_lossy())?;
+    eprintln!("Tabby config saved to {}", config_path.display());
+    report.saved_to = Some(config_path);
 
-    // if let Some(profile) = tabby_config.get_profile("host011") {
-    //     println!("Found profile: {:#?}", profile);
-    //     let group_id = profile.group.as_deref().unwrap_or("No group");
-    //     if let Some(groups) = &tabby_config.groups {
-    //         if let Some(group) = groups.iter().find(|g| g.id == group_id) {
-    //             println!("Profile belongs to group: {}", group.name);  
-    //         } else {
-    //             println!("Group with id '{}' not found", group_id);
-    //         }
-    //     } else {
-    //         println!("No groups defined in the config");
-    //     }
-    // } else {
-    //     println!("Profile {} not found", "host011");
-    // }
+    Ok((report, EXIT_OK))
+}
 
+/// Show the profiles and groups an import would add, without saving anything
+fn run_diff(args: &DiffArgs) -> Result<(RunReport, u8)> {
+    let mut report = RunReport::new("diff", false);
+    let registry = Registry::default();
+    let source = args.source.path(&registry, &args.remmina)?;
+    fs::metadata(&source).map_err(|e| Error::io(&source, e))?;
+    let mut tabby_config = load_tabby_config(&args.tabby.tabby_dir)?;
+    let rules = args.merge.load_rules()?;
+
+    let options = ConvertOptions {
+        protocols: parse_protocols(&args.remmina.protocol),
+        rules: rules.as_ref(),
+        name_template: args.merge.name_template.clone(),
+        groups: args.merge.group_mapping(),
+        palette: args.merge.palette(),
+        import: args.merge.import_options(None),
+        prune: args.merge.prune,
+        ..Default::default()
+    };
+    let plan = convert_from(args.source.importer(&registry), &source, &mut tabby_config, options)?;
 
+    let changes = plan.changes();
+    report.plan = Some(plan);
+    if changes == 0 {
+        eprintln!("\n✅ Tabby config is up to date.\n");
+        return Ok((report, EXIT_OK));
+    }
+    eprintln!("\n🟡 {changes} pending changes.\n");
+    Ok((report, EXIT_PENDING_CHANGES))
+}
 
+/// Lint the Tabby config
+fn run_validate(args: &ValidateArgs) -> Result<(RunReport, u8)> {
+    let tabby_config = load_tabby_config(&args.tabby.tabby_dir)?;
 
+    let mut report = RunReport::new("validate", false);
+    report.problems = tabby_config.validate();
+    if !report.problems.is_empty() {
+        eprintln!("\n🟡 {} problems found in Tabby config.\n", report.problems.len());
+        return Ok((report, EXIT_INVALID_CONFIG));
+    }
+    eprintln!("✅ Tabby config is valid ({} profiles).", tabby_config.profiles.len());
+    Ok((report, EXIT_OK))
 }
 
-/// Get default Remmina directory based on OS (Currently only Linux supported)
-fn default_remmina_dir() -> String {
-    #[cfg(target_os = "linux")]
-    {
-        format!("{}/.local/share/remmina", std::env::var("HOME").unwrap_or_default())
+/// Export Tabby SSH profiles to .remmina files
+fn run_export(args: &ExportArgs) -> Result<(RunReport, u8)> {
+    let tabby_config = load_tabby_config(&args.tabby.tabby_dir)?;
+    check_dir("Remmina", &args.remmina_dir)?;
+
+    let mut report = RunReport::new("export", args.execute);
+    let profiles = tabby_config.connections();
+    let count = RemminaFiles::write_profiles(Path::new(&args.remmina_dir), &profiles, args.execute, &mut report)?;
+    if args.execute {
+        eprintln!("\n✅ {count} of {} Tabby SSH profiles exported.\n", profiles.len());
+    } else {
+        eprintln!("\nDry-run would export {count} of {} Tabby SSH profiles.\n", profiles.len());
     }
+    Ok((report, EXIT_OK))
 }
 
-/// Get default Tabby directory based on OS
-/// # Returns
-/// * `String` - Default Tabby configuration directory path
-/// # Behavior
-/// * On Linux: ~/.config/tabby
-/// * On Windows: %APPDATA%\Tabby
-/// * On macOS: ~/Library/Application Support/tabby
-/// * On other OS: ./tabby
-fn default_tabby_dir() -> String {
-    #[cfg(target_os = "linux")]
-    {
-        format!("{}/.config/tabby", std::env::var("HOME").unwrap_or_default())
-    }
-    #[cfg(target_os = "windows")]
-    {
-        format!("{}/Tabby", std::env::var("APPDATA").unwrap_or_default())
+/// Write the SSH profiles as OpenSSH Host blocks, the ssh-config exporter of `convert`
+fn run_export_ssh_config(args: &ExportSshConfigArgs) -> Result<(RunReport, u8)> {
+    let mut registry = Registry::default();
+    registry.register_exporter(Box::new(SshConfigFormat { export: args.layout.export() }));
+    let (from, source) = match args.source {
+        ProfileSource::Remmina => ("remmina", &args.remmina_dir),
+        ProfileSource::Tabby => ("tabby", &args.tabby.tabby_dir),
+    };
+    let (Some(importer), Some(exporter)) = (registry.importer(from), registry.exporter("ssh-config")) else {
+        unreachable!("built-in formats");
+    };
+
+    let mut report = RunReport::new("export-ssh-config", args.execute);
+    let ssh_only = ReadOptions { protocols: vec!["SSH".to_string()] };
+    let connections = importer.import(Path::new(source), &ssh_only, &mut report)?;
+    exporter.export(connections, Path::new(&args.ssh_config), &WriteOptions { execute: args.execute }, &mut report)?;
+    Ok((report, EXIT_OK))
+}
+
+/// Read connections with the --from importer and write them with the --to exporter
+///
+/// Notes:
+///     The merge options (rules, name template, groups, palette, conflicts, prune) are those of the Tabby exporter,
+///     --groups and --managed those of the ssh-config exporter; they are a usage error with another --to format
+///     rather than silently ignored
+fn run_convert(args: &ConvertArgs) -> Result<(RunReport, u8)> {
+    for (format, options) in [("tabby", args.merge.given()), ("ssh-config", args.ssh_config.given())] {
+        if args.to != format && !options.is_empty() {
+            return Err(Error::Usage(format!("{} only apply to --to {format}, not to {}", options.join(", "), args.to)));
+        }
     }
-    #[cfg(target_os = "macos")]
-    {
-        format!("{}/Library/Application Support/tabby", std::env::var("HOME").unwrap_or_default())
+    let mut registry = Registry::default();
+    registry.register_exporter(Box::new(TabbyFormat {
+        rules: args.merge.load_rules()?,
+        name_template: args.merge.name_template.clone(),
+        groups: args.merge.group_mapping(),
+        palette: args.merge.palette(),
+        import: args.merge.import_options(None),
+        prune: args.merge.prune,
+    }));
+    registry.register_exporter(Box::new(SshConfigFormat { export: args.ssh_config.export() }));
+    let (Some(importer), Some(exporter)) = (registry.importer(&args.from), registry.exporter(&args.to)) else {
+        unreachable!("formats are validated by the argument parser");
+    };
+    let source = args.from_path.clone().or_else(|| importer.default_path()).ok_or_else(|| missing_path(importer.name(), "--from-path"))?;
+    let target = args.to_path.clone().or_else(|| exporter.default_path()).ok_or_else(|| missing_path(exporter.name(), "--to-path"))?;
+    eprintln!("From {}: {}", importer.name(), source.display());
+    eprintln!("To {}: {}", exporter.name(), target.display());
+    eprintln!("Protocol filter: {}", args.protocol);
+
+    let mut report = RunReport::new("convert", args.execute);
+    let read = ReadOptions { protocols: parse_protocols(&args.protocol) };
+    let connections = importer.import(&source, &read, &mut report)?;
+    eprintln!("\n✅ Read {} connections from {}.\n", connections.connections.len(), importer.name());
+    exporter.export(connections, &target, &WriteOptions { execute: args.execute }, &mut report)?;
+    Ok((report, EXIT_OK))
+}
+
+/// Check the --from format against the registry
+fn importer_name(name: &str) -> std::result::Result<String, String> {
+    let registry = Registry::default();
+    match registry.importer(name) {
+        Some(importer) => Ok(importer.name().to_string()),
+        None => {
+            let names: Vec<&str> = registry.importers().map(|i| i.name()).collect();
+            Err(format!("unknown format '{name}', expected one of: {}", names.join(", ")))
+        }
     }
-    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
-    {
-        String::from("./tabby")
+}
+
+/// Check the --to format against the registry
+fn exporter_name(name: &str) -> std::result::Result<String, String> {
+    let registry = Registry::default();
+    match registry.exporter(name) {
+        Some(exporter) => Ok(exporter.name().to_string()),
+        None => {
+            let names: Vec<&str> = registry.exporters().map(|e| e.name()).collect();
+            Err(format!("unknown format '{name}', expected one of: {}", names.join(", ")))
+        }
     }
 }
 
+/// Usage error of a format without default location
+fn missing_path(name: &str, option: &str) -> Error {
+    Error::Usage(format!("the {name} format has no default location, use {option}"))
+}
+
+/// Load config.yaml from the Tabby directory
+fn load_tabby_config(tabby_dir: &str) -> Result<TabbyConfig> {
+    check_dir("Tabby", tabby_dir)?;
+    let config = TabbyConfig::load_from_dir(tabby_dir)?;
+    eprintln!("\nLoaded Tabby config from {tabby_dir}\n");
+    Ok(config)
+}
+
+/// Find .remmina files and keep the ones matching the protocol filter
+fn find_remmina_files(source: &RemminaSource, report: &mut RunReport) -> Result<RemminaFiles> {
+    let remmina_dir = &source.remmina_dir;
+    check_dir("Remmina", remmina_dir)?;
+
+    let protocols = parse_protocols(&source.protocol);
+
+    let files = RemminaFiles::find(remmina_dir)?;
+    eprintln!("\nFound {} .remmina files\n", files.files.len());
+    let filtered_files = files.filter_by_protocols(&protocols, report);
+    eprintln!("After filtering, {} .remmina files match protocols: {:?}\n", filtered_files.files.len(), protocols);
+    Ok(filtered_files)
+}
+
+/// Split the --protocol argument by comma and trim whitespace
+fn parse_protocols(protocol: &str) -> Vec<String> {
+    protocol.split(',').map(|s| s.trim().to_uppercase()).collect()
+}
+
 fn clear_screen() {
     // Clear the terminal screen (works on most Unix terminals)
-    print!("\x1B[2J\x1B[H");
-    std::io::stdout().flush().unwrap();
+    eprint!("\x1B[2J\x1B[H");
+    io::stderr().flush().unwrap();
+}
+
+/// Read the Tabby vault passphrase from TABBY_VAULT_PASSPHRASE, or ask for it
+///
+/// Notes:
+///     On a terminal the passphrase is typed without echo, a piped stdin is read as a line
+fn read_vault_passphrase() -> Result<String> {
+    if let Ok(passphrase) = std::env::var("TABBY_VAULT_PASSPHRASE") {
+        return Ok(passphrase);
+    }
+    if io::stdin().is_terminal() {
+        return rpassword::prompt_password("🔒 Tabby vault passphrase: ").map_err(|e| Error::io("<tty>", e));
+    }
+    eprint!("🔒 Tabby vault passphrase: ");
+    io::stderr().flush().map_err(|e| Error::io("<stderr>", e))?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).map_err(|e| Error::io("<stdin>", e))?;
+    Ok(input.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Ask how to import a profile colliding with an existing one (--on-conflict ask)
+fn ask_conflict(profile: &Connection, existing: &Profile) -> Result<ConflictStrategy> {
+    eprintln!(
+        "\n❓ '{}' ({}@{}) conflicts with existing profile '{}' ({}@{})",
+        profile.name.as_deref().unwrap_or_default(),
+        profile.user.as_deref().unwrap_or_default(),
+        profile.host.as_deref().unwrap_or_default(),
+        existing.name,
+        existing.options().user.as_deref().unwrap_or_default(),
+        existing.options().host.as_deref().unwrap_or_default()
+    );
+    loop {
+        eprint!("   [s]kip, [o]verwrite, [m]erge, [r]ename or 'q' to quit: ");
+        io::stderr().flush().map_err(|e| Error::io("<stderr>", e))?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).map_err(|e| Error::io("<stdin>", e))?;
+        match input.trim().to_lowercase().as_str() {
+            // An empty answer (or a closed stdin) keeps the existing profile
+            "" | "s" => return Ok(ConflictStrategy::Skip),
+            "o" => return Ok(ConflictStrategy::Overwrite),
+            "m" => return Ok(ConflictStrategy::Merge),
+            "r" => return Ok(ConflictStrategy::Rename),
+            "q" => return Err(Error::Cancelled),
+            _ => continue,
+        }
+    }
 }
 
-fn confirm_continue(message: Option<&str>) {
+/// Ask the user to confirm, returns Error::Cancelled if the answer is 'q'
+fn confirm_continue(message: Option<&str>) -> Result<()> {
     if let Some(msg) = message {
-        println!("{msg}");
+        eprintln!("{msg}");
     }
-    print!("⚠️  Press [Enter] to continue or 'q' then [Enter] to quit: ");
-    io::stdout().flush().unwrap();
+    eprint!("⚠️  Press [Enter] to continue or 'q' then [Enter] to quit: ");
+    io::stderr().flush().map_err(|e| Error::io("<stderr>", e))?;
 
     let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
+    io::stdin().read_line(&mut input).map_err(|e| Error::io("<stdin>", e))?;
     if input.trim().eq_ignore_ascii_case("q") {
-        println!("🛑 Operation cancelled by user.");
-        std::process::exit(0);
+        return Err(Error::Cancelled);
     }
+    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::connection::{AuthMethod, Connection, JumpHost};
+use crate::error::{Error, Result};
+use crate::protocols_types::ConnectionProtocols;
+use crate::putty_parser::decode_text;
+use crate::report::{RunReport, SkippedFile};
+
+/// Scheme of the source ids of the MobaXterm sessions ("mobaxterm:folder/name"), see `source_scheme`
+pub const MOBAXTERM_SCHEME: &str = "mobaxterm";
+
+/// Positions of the '%'-separated fields of a session ("#109#0%host%port%user%..."),
+/// host, port and user are at the same place for every protocol, the others are SSH settings
+mod field {
+    pub const HOST: usize = 1;
+    pub const PORT: usize = 2;
+    pub const USER: usize = 3;
+    pub const X11: usize = 5;
+    pub const COMPRESSION: usize = 6;
+    pub const COMMAND: usize = 7;
+    pub const GATEWAY_HOST: usize = 8;
+    pub const GATEWAY_PORT: usize = 9;
+    pub const GATEWAY_USER: usize = 10;
+    pub const PRIVATE_KEY: usize = 14;
+    /// Device of a serial session ("COM3  (USB Serial Port)"), which has no host, port and user
+    pub const SERIAL_DEVICE: usize = 8;
+}
+
+/// Sessions of a MobaXterm bookmarks export, read as connections
+#[derive(Debug, Default)]
+pub struct MobaXtermSessions {
+    /// One profile per connection session, in file order
+    pub profiles: Vec<Connection>,
+    /// Source ids of every connection session, including the ones whose protocol is filtered out later
+    pub sources: HashSet<String>,
+}
+
+/// Methods for MobaXtermSessions
+impl MobaXtermSessions {
+    /// Read a .mxtsessions file (Settings > Export sessions, or the [Bookmarks] sections of MobaXterm.ini)
+    ///
+    /// # Arguments
+    /// * `path` - The .mxtsessions file
+    /// * `report` - Receives the sessions that are no connection (shell, browser, file, ...) and the malformed lines
+    /// # Returns
+    /// * `Result<MobaXtermSessions>` - The sessions, Io if the file cannot be read,
+    ///   InvalidSessionFile if it has no [Bookmarks] section
+    /// # Behavior
+    /// * Each [Bookmarks_N] section is a folder, its `SubRep` path ("Prod\DB") gives the group
+    /// * SSH sessions keep host, port, user, X11 forwarding, compression, remote command, private key and
+    ///   SSH gateway (as jump host); RDP, VNC, Telnet and serial sessions keep host, port and user
+    pub fn load(path: &Path, report: &mut RunReport) -> Result<Self> {
+        let bytes = fs::read(path).map_err(|e| Error::io(path, e))?;
+        let content = decode_text(&bytes);
+
+        let mut result = MobaXtermSessions::default();
+        let mut in_bookmarks = false;
+        let mut found_bookmarks = false;
+        let mut group: Option<String> = None;
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if let Some(section) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                in_bookmarks = section == "Bookmarks" || section.starts_with("Bookmarks_");
+                found_bookmarks |= in_bookmarks;
+                group = None;
+                continue;
+            }
+            if !in_bookmarks || line.is_empty() {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else { continue };
+            match key.trim() {
+                "SubRep" => {
+                    let segments: Vec<&str> = value.split('\\').map(str::trim).filter(|s| !s.is_empty()).collect();
+                    group = (!segments.is_empty()).then(|| segments.join("/"));
+                }
+                "ImgNum" => {}
+                name => {
+                    let Some(session) = value.strip_prefix('#') else {
+                        eprintln!(" └── MobaXterm line {}: '{name}' is not a session, skipped.", index + 1);
+                        let message = format!("session '{name}' does not start with '#<type>#'");
+                        let error = Error::InvalidSessionFile { path: path.to_path_buf(), line: Some(index + 1), message };
+                        report.skipped_files.push(SkippedFile::from_error(path, &error));
+                        continue;
+                    };
+                    let mut parts = session.split('#');
+                    let kind = parts.next().unwrap_or_default();
+                    let fields: Vec<&str> = parts.next().unwrap_or_default().split('%').collect();
+                    match session_protocol(kind) {
+                        Some(protocol) => {
+                            let profile = session_profile(name.trim(), &protocol, &fields, group.clone(), path);
+                            if let Some(source) = &profile.source_id {
+                                result.sources.insert(source.clone());
+                            }
+                            result.profiles.push(profile);
+                        }
+                        None => {
+                            eprintln!(" └── MobaXterm session '{name}' (type {kind}) is not a connection, skipped.");
+                            let error = Error::UnsupportedProtocol { path: path.to_path_buf(), protocol: format!("MobaXterm session type {kind} ('{name}')") };
+                            report.skipped_files.push(SkippedFile::from_error(path, &error));
+                        }
+                    }
+                }
+            }
+        }
+        if !found_bookmarks {
+            return Err(Error::InvalidSessionFile { path: path.to_path_buf(), line: None, message: "no [Bookmarks] section".to_string() });
+        }
+        Ok(result)
+    }
+}
+
+/// Protocol of a MobaXterm session type, None for sessions that are no remote connection
+///
+/// Notes:
+///     109 SSH, 91 RDP, 128 VNC, 98 Telnet, 131 serial; the others are file transfers, shells, browsers, ...
+fn session_protocol(kind: &str) -> Option<ConnectionProtocols> {
+    match kind.trim() {
+        "109" => Some(ConnectionProtocols::Ssh),
+        "91" => Some(ConnectionProtocols::Rdp),
+        "128" => Some(ConnectionProtocols::Vnc),
+        "98" => Some(ConnectionProtocols::Telnet),
+        "131" => Some(ConnectionProtocols::Serial),
+        _ => None,
+    }
+}
+
+/// The fields of a session as a connection
+fn session_profile(name: &str, protocol: &ConnectionProtocols, fields: &[&str], group: Option<String>, path: &Path) -> Connection {
+    let get = |index: usize| fields.get(index).map(|f| f.trim()).filter(|f| !f.is_empty()).map(str::to_string);
+    // Check boxes are saved as -1 (checked) and 0
+    let flag = |index: usize| get(index).map(|f| f == "-1");
+    let source = match &group {
+        Some(group) => format!("{MOBAXTERM_SCHEME}:{group}/{name}"),
+        None => format!("{MOBAXTERM_SCHEME}:{name}"),
+    };
+
+    let mut profile = Connection {
+        name: Some(name.to_string()),
+        host: get(field::HOST),
+        port: get(field::PORT).and_then(|p| p.parse().ok()).filter(|&p| p != 0),
+        group,
+        protocol: protocol.clone(),
+        user: get(field::USER),
+        source_id: Some(source),
+        path: path.to_path_buf(),
+        ..Default::default()
+    };
+    if *protocol == ConnectionProtocols::Serial {
+        profile.host = get(field::SERIAL_DEVICE).and_then(|device| device.split_whitespace().next().map(str::to_string));
+        profile.port = None;
+        profile.user = None;
+    }
+    if *protocol != ConnectionProtocols::Ssh {
+        return profile;
+    }
+
+    profile.forward_x11 = flag(field::X11);
+    profile.compression = flag(field::COMPRESSION);
+    profile.remote_command = get(field::COMMAND);
+    // "_ProfileDir_" is the MobaXterm home directory, the path below it is kept with '/' separators
+    profile.private_key = get(field::PRIVATE_KEY).map(|key| match key.strip_prefix("_ProfileDir_") {
+        Some(relative) => format!("{}{}", std::env::var("HOME").unwrap_or_default(), relative.replace('\\', "/")),
+        None => key,
+    });
+    profile.auth = profile.private_key.as_ref().map(|_| AuthMethod::PublicKey);
+
+    // Several gateways are separated by "__PIPE__", the last one is the one the host is reached from
+    if let Some(gateways) = get(field::GATEWAY_HOST) {
+        let last = |index: usize| get(index).and_then(|f| f.rsplit("__PIPE__").next().map(str::to_string)).filter(|f| !f.is_empty());
+        if gateways.contains("__PIPE__") {
+            eprintln!(" └── MobaXterm session '{name}' has several SSH gateways, only the last one is kept.");
+        }
+        profile.jump_host = last(field::GATEWAY_HOST).map(|host| JumpHost {
+            host,
+            port: last(field::GATEWAY_PORT).and_then(|p| p.parse().ok()).unwrap_or(22),
+            user: last(field::GATEWAY_USER),
+            auth: None,
+            private_key: None,
+            password: None,
+        });
+    }
+    profile
+}
@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use roxmltree::{Document, Node};
+
+use crate::connection::Connection;
+use crate::error::{Error, Result};
+use crate::protocols_types::ConnectionProtocols;
+use crate::putty_parser::decode_text;
+use crate::report::{RunReport, SkippedFile};
+
+/// Scheme of the source ids of the mRemoteNG connections ("mremoteng:<Id>"), see `source_scheme`
+pub const MREMOTENG_SCHEME: &str = "mremoteng";
+
+/// Connections of an mRemoteNG confCons.xml, read as connections
+#[derive(Debug, Default)]
+pub struct MRemoteNgConnections {
+    /// One profile per connection, in document order
+    pub profiles: Vec<Connection>,
+    /// Source ids of every connection, including the ones whose protocol is filtered out later
+    pub sources: HashSet<String>,
+}
+
+/// Methods for MRemoteNgConnections
+impl MRemoteNgConnections {
+    /// Read a confCons.xml (File > Export, or %APPDATA%\mRemoteNG\confCons.xml)
+    ///
+    /// # Arguments
+    /// * `path` - The confCons.xml file
+    /// * `report` - Receives the connections whose protocol has no Tabby equivalent (HTTP, RAW, ...)
+    /// # Returns
+    /// * `Result<MRemoteNgConnections>` - The connections, Io if the file cannot be read,
+    ///   InvalidSessionFile if it is not valid XML, has no <Connections> root or is fully encrypted
+    /// # Behavior
+    /// * `Type="Container"` nodes are folders, their path ("Prod/DB") gives the group
+    /// * An attribute whose `Inherit<Attribute>` is "true" takes the value of the parent container, recursively
+    /// * SSH1/SSH2, RDP, VNC and Telnet connections keep name, host, port and user
+    ///
+    /// Notes:
+    ///     Passwords are encrypted with the mRemoteNG master password and are not imported
+    pub fn load(path: &Path, report: &mut RunReport) -> Result<Self> {
+        let bytes = fs::read(path).map_err(|e| Error::io(path, e))?;
+        let content = decode_text(&bytes);
+        let invalid = |line: Option<u32>, message: String| Error::InvalidSessionFile {
+            path: path.to_path_buf(),
+            line: line.map(|l| l as usize),
+            message,
+        };
+
+        let document = Document::parse(&content).map_err(|e| invalid(Some(e.pos().row), format!("invalid XML: {e}")))?;
+        let root = document.root_element();
+        let root_line = Some(document.text_pos_at(root.range().start).row);
+        if root.tag_name().name() != "Connections" {
+            return Err(invalid(root_line, format!("not an mRemoteNG connection file (root element <{}>)", root.tag_name().name())));
+        }
+        if root.attribute("FullFileEncryption").is_some_and(|v| v.eq_ignore_ascii_case("true")) {
+            return Err(invalid(
+                root_line,
+                "the file is encrypted (FullFileEncryption=\"true\"), disable \"Encrypt complete connection file\" \
+                 in mRemoteNG (Options > Security) and save it again"
+                    .to_string(),
+            ));
+        }
+
+        let mut result = MRemoteNgConnections::default();
+        result.read_nodes(root, &mut Vec::new(), path, report);
+        Ok(result)
+    }
+
+    /// Read the connections of a container, then the ones of its sub-containers
+    fn read_nodes(&mut self, parent: Node, folders: &mut Vec<String>, path: &Path, report: &mut RunReport) {
+        for node in parent.children().filter(|n| n.has_tag_name("Node")) {
+            let name = node.attribute("Name").unwrap_or_default().trim().to_string();
+            if node.attribute("Type").is_some_and(|t| t.eq_ignore_ascii_case("Container")) {
+                folders.push(name);
+                self.read_nodes(node, folders, path, report);
+                folders.pop();
+                continue;
+            }
+
+            let protocol = attribute(node, "Protocol").unwrap_or_default();
+            let Some(protocol) = connection_protocol(protocol) else {
+                eprintln!(" └── mRemoteNG connection '{name}' uses protocol {protocol}, skipped.");
+                let error = Error::UnsupportedProtocol { path: path.to_path_buf(), protocol: format!("{protocol} ('{name}')") };
+                report.skipped_files.push(SkippedFile::from_error(path, &error));
+                continue;
+            };
+            let group = (!folders.is_empty()).then(|| folders.join("/"));
+            let source = match node.attribute("Id").filter(|id| !id.is_empty()) {
+                Some(id) => format!("{MREMOTENG_SCHEME}:{id}"),
+                None => format!("{MREMOTENG_SCHEME}:{}", group.as_ref().map_or(name.clone(), |g| format!("{g}/{name}"))),
+            };
+            self.sources.insert(source.clone());
+            self.profiles.push(Connection {
+                name: Some(name),
+                host: attribute(node, "Hostname").map(str::to_string),
+                port: attribute(node, "Port").and_then(|p| p.parse().ok()).filter(|&p| p != 0),
+                group,
+                protocol,
+                user: attribute(node, "Username").map(str::to_string),
+                source_id: Some(source),
+                path: path.to_path_buf(),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+/// Value of an attribute of a node, taken from the parent containers when `Inherit<name>` is "true"
+///
+/// Notes:
+///     The <Connections> root has no connection attributes, inheriting from it gives no value
+fn attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    let inherit = format!("Inherit{name}");
+    if node.attribute(inherit.as_str()).is_some_and(|v| v.eq_ignore_ascii_case("true")) {
+        return node.parent_element().filter(|p| p.has_tag_name("Node")).and_then(|p| attribute(p, name));
+    }
+    node.attribute(name).map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Protocol of an mRemoteNG connection, None for the ones Tabby cannot open (HTTP, RAW, Rlogin, PowerShell, ...)
+fn connection_protocol(protocol: &str) -> Option<ConnectionProtocols> {
+    match protocol.to_ascii_uppercase().as_str() {
+        "SSH1" | "SSH2" => Some(ConnectionProtocols::Ssh),
+        "RDP" => Some(ConnectionProtocols::Rdp),
+        "VNC" => Some(ConnectionProtocols::Vnc),
+        "TELNET" => Some(ConnectionProtocols::Telnet),
+        _ => None,
+    }
+}
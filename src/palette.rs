@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+
+use crate::connection::Connection;
+
+/// Colors picked by `ColorMode::Group`, distinct enough to tell groups apart in the Tabby profile list
+pub const GROUP_COLORS: &[&str] = &[
+    "#D9534F", "#F0AD4E", "#5CB85C", "#5BC0DE", "#428BCA", "#9B59B6",
+    "#E91E63", "#1ABC9C", "#795548", "#607D8B", "#CDDC39", "#FF5722",
+];
+
+/// Group of the profiles without Remmina group, as created by `TabbyConfig::import_profiles`
+const DEFAULT_GROUP: &str = "Default Group";
+
+/// How the color of imported profiles is chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    /// Same color for every profile (#FF9C00)
+    #[default]
+    Default,
+    /// Color derived from the group name, the same group always gets the same color
+    Group,
+}
+
+/// How the sorting weight of imported profiles is chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum WeightMode {
+    /// Same weight for every profile (-1), Tabby sorts them by name
+    #[default]
+    Default,
+    /// Position of the profile in its group as listed by Remmina (by name, case-insensitive)
+    Remmina,
+}
+
+/// Colors, icons and sorting weights given to the imported profiles
+///
+/// Notes:
+///     A color or icon set by a rule is kept, the palette only fills the unset ones.
+///     Explicit maps win over `colors`, profiles matching nothing get the Tabby defaults (see `Profile::default`)
+#[derive(Debug, Clone, Default)]
+pub struct Palette {
+    pub colors: ColorMode,
+    /// Color of the profiles of a Tabby group, e.g. "Production" -> "#D9534F"
+    pub group_colors: BTreeMap<String, String>,
+    /// Icon of the profiles of a protocol (case-insensitive), e.g. "RDP" -> "fab fa-windows"
+    pub protocol_icons: BTreeMap<String, String>,
+    pub weights: WeightMode,
+}
+
+/// Methods for Palette
+impl Palette {
+    /// Sets the color, icon and weight of the profiles
+    ///
+    /// # Arguments
+    /// * `profiles` - The connections about to be imported, with their Tabby group and name
+    /// # Behavior
+    /// * Color: `group_colors` entry of the group, then the `GROUP_COLORS` entry picked by a hash of the group
+    ///   name with `ColorMode::Group`
+    /// * Icon: `protocol_icons` entry of the protocol
+    /// * Weight with `WeightMode::Remmina`: 0, 1, 2... in the order of the names within each group
+    pub fn apply(&self, profiles: &mut [Connection]) {
+        for profile in profiles.iter_mut() {
+            let group = profile.group.as_deref().unwrap_or(DEFAULT_GROUP);
+            if profile.color.is_none() {
+                profile.color = self.group_colors.get(group).cloned().or_else(|| match self.colors {
+                    ColorMode::Group => Some(group_color(group).to_string()),
+                    ColorMode::Default => None,
+                });
+            }
+            if profile.icon.is_none() {
+                let protocol = profile.protocol.as_str();
+                profile.icon = self.protocol_icons.iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(protocol))
+                    .map(|(_, icon)| icon.clone());
+            }
+        }
+
+        if self.weights == WeightMode::Remmina {
+            let mut order: Vec<usize> = (0..profiles.len()).collect();
+            order.sort_by_cached_key(|&i| {
+                let profile = &profiles[i];
+                (profile.group.clone().unwrap_or_default(), profile.name.clone().unwrap_or_default().to_lowercase())
+            });
+            let mut position: BTreeMap<Option<String>, i32> = BTreeMap::new();
+            for index in order {
+                let next = position.entry(profiles[index].group.clone()).or_default();
+                profiles[index].weight = Some(*next);
+                *next += 1;
+            }
+        }
+    }
+}
+
+/// Color of a group for `ColorMode::Group`
+///
+/// # Returns
+/// * `&str` - A `GROUP_COLORS` entry, from a FNV-1a hash of the name so it does not change between runs or builds
+pub fn group_color(group: &str) -> &'static str {
+    let hash = group.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3));
+    GROUP_COLORS[(hash % GROUP_COLORS.len() as u64) as usize]
+}
+
+/// Parses a "key=value" pair (clap value parser of `--group-color` and `--protocol-icon`)
+pub fn parse_mapping(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() && !value.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{value}'")),
+    }
+}
+
+/// Parses a "group=#rrggbb" pair (clap value parser of `--group-color`)
+pub fn parse_group_color(value: &str) -> Result<(String, String), String> {
+    let (group, color) = parse_mapping(value)?;
+    let hex = color.strip_prefix('#').unwrap_or_default();
+    if !matches!(hex.len(), 3 | 6) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("invalid color '{color}', expected #rgb or #rrggbb"));
+    }
+    Ok((group, color))
+}
@@ -1,3 +1,5 @@
+use serde::{Serialize, Serializer};
+
 /// List of allowed protocols
 /// Notes:
 ///     Currently (01/10/2025) only "SSH" is supported in Tabby, no reason to allow other protocols
@@ -8,20 +10,26 @@
 ///         - https://github.com/Eugeny/tabby/issues/5854
 pub const ALLOWED_PROTOCOLS_EXPORT: &[&str] = &["SSH"];
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub enum ConnectionProtocols {
+    #[default]
     Ssh,
     Rdp,
     Vnc,
+    Telnet,
+    Serial,
     Unknown(String),
 }
 
 impl ConnectionProtocols {
+    #[allow(clippy::should_implement_trait)] // infallible, unknown values are kept in Unknown
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "ssh" => ConnectionProtocols::Ssh,
             "rdp" => ConnectionProtocols::Rdp,
             "vnc" => ConnectionProtocols::Vnc,
+            "telnet" => ConnectionProtocols::Telnet,
+            "serial" => ConnectionProtocols::Serial,
             other => ConnectionProtocols::Unknown(other.to_string()),
         }
     }
@@ -31,16 +39,28 @@ impl ConnectionProtocols {
             ConnectionProtocols::Ssh => "ssh",
             ConnectionProtocols::Rdp => "rdp",
             ConnectionProtocols::Vnc => "vnc",
+            ConnectionProtocols::Telnet => "telnet",
+            ConnectionProtocols::Serial => "serial",
             ConnectionProtocols::Unknown(s) => s.as_str(),
         }
     }
 }
 
+/// Serialized uppercase ("SSH"), as written in .remmina files and given to --protocol
+impl Serialize for ConnectionProtocols {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_str().to_uppercase())
+    }
+}
+
 pub fn get_default_port_for_protocol(protocol: &ConnectionProtocols) -> u16 {
     match protocol {
         ConnectionProtocols::Ssh => 22,
         ConnectionProtocols::Rdp => 3389,
         ConnectionProtocols::Vnc => 5900,
+        ConnectionProtocols::Telnet => 23,
+        // A device, not a network port
+        ConnectionProtocols::Serial => 0,
         ConnectionProtocols::Unknown(_) => 0,
     }
 }
\ No newline at end of file
@@ -0,0 +1,317 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::connection::{AuthMethod, Connection, JumpHost};
+use crate::error::{Error, Result};
+use crate::protocols_types::ConnectionProtocols;
+use crate::report::{RunReport, SkippedFile};
+
+/// Scheme of the source ids of the PuTTY sessions ("putty:name"), see `source_scheme`
+pub const PUTTY_SCHEME: &str = "putty";
+/// Scheme of the source ids of the KiTTY sessions ("kitty:name")
+pub const KITTY_SCHEME: &str = "kitty";
+
+/// Session holding the PuTTY defaults, it is no connection
+const DEFAULT_SESSION: &str = "Default Settings";
+
+/// Values of a session: name and value, DWORDs as decimal strings
+type SessionValues = BTreeMap<String, String>;
+
+/// Sessions of PuTTY or KiTTY, read as connections
+#[derive(Debug, Default)]
+pub struct PuttySessions {
+    /// One profile per session, in file (or file name) order
+    pub profiles: Vec<Connection>,
+    /// Source ids of every session, including the ones whose protocol is filtered out later
+    pub sources: HashSet<String>,
+}
+
+/// Methods for PuttySessions
+impl PuttySessions {
+    /// Read the sessions of a registry export (regedit, "reg export HKCU\Software\SimonTatham\PuTTY")
+    ///
+    /// # Arguments
+    /// * `path` - The .reg file, UTF-16 (as written by regedit) or UTF-8
+    /// # Returns
+    /// * `Result<PuttySessions>` - The sessions, Io if the file cannot be read,
+    ///   InvalidSessionFile if it is not a registry export
+    /// # Behavior
+    /// * Keys ending with "\Sessions\<name>" are sessions (PuTTY, KiTTY in registry mode), other keys are ignored
+    /// * The session names are URL-decoded, '/' and '\' in them (or a KiTTY "Folder" value) give the group
+    /// * "Default Settings" is skipped
+    pub fn load_reg(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path).map_err(|e| Error::io(path, e))?;
+        let content = decode_text(&bytes);
+        let mut lines = content.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+        match lines.next() {
+            Some((_, header)) if header.trim() == "Windows Registry Editor Version 5.00" || header.trim() == "REGEDIT4" => {}
+            other => {
+                return Err(Error::InvalidSessionFile {
+                    path: path.to_path_buf(),
+                    line: other.map(|(index, _)| index + 1),
+                    message: "not a registry export (missing \"Windows Registry Editor Version 5.00\" header)".to_string(),
+                });
+            }
+        }
+
+        let mut sessions: Vec<(String, SessionValues)> = Vec::new();
+        let mut in_session = false;
+        for (index, line) in lines {
+            let line = line.trim();
+            if let Some(key) = line.strip_prefix('[') {
+                let key = key.trim_end_matches(']');
+                in_session = false;
+                // "[-HKEY...]" deletes a key
+                if key.starts_with('-') {
+                    continue;
+                }
+                if let Some((_, name)) = key.rsplit_once("\\Sessions\\") {
+                    sessions.push((name.to_string(), SessionValues::new()));
+                    in_session = true;
+                }
+                continue;
+            }
+            if !in_session || !line.starts_with('"') {
+                continue;
+            }
+            let Some((name, value)) = parse_reg_value(line) else {
+                return Err(Error::InvalidSessionFile { path: path.to_path_buf(), line: Some(index + 1), message: format!("invalid value: {line}") });
+            };
+            if let (Some(value), Some((_, values))) = (value, sessions.last_mut()) {
+                values.insert(name, value);
+            }
+        }
+
+        let mut result = PuttySessions::default();
+        for (name, values) in sessions {
+            result.add(&name, &values, PUTTY_SCHEME, path);
+        }
+        Ok(result)
+    }
+
+    /// Read the sessions of a KiTTY portable "Sessions" directory (one file per session)
+    ///
+    /// # Arguments
+    /// * `dir` - The Sessions directory
+    /// * `report` - Receives the session files that cannot be read
+    /// # Returns
+    /// * `Result<PuttySessions>` - The sessions, Io if the directory cannot be read
+    /// # Behavior
+    /// * Files hold "Key\Value\" lines, the file name is the URL-encoded session name
+    /// * Sessions are sorted by file name, "Default Settings" is skipped
+    pub fn load_kitty_dir(dir: &Path, report: &mut RunReport) -> Result<Self> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(|e| Error::io(dir, e))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect();
+        paths.sort();
+
+        let mut result = PuttySessions::default();
+        for path in paths {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            let content = match fs::read(&path) {
+                Ok(bytes) => decode_text(&bytes),
+                Err(e) => {
+                    let error = Error::io(&path, e);
+                    eprintln!(" └── {error}, skipped.");
+                    report.skipped_files.push(SkippedFile::from_error(&path, &error));
+                    continue;
+                }
+            };
+            let values: SessionValues = content.lines()
+                .filter_map(|line| {
+                    let (key, value) = line.trim_end_matches(['\r', '\n']).split_once('\\')?;
+                    Some((key.to_string(), value.strip_suffix('\\').unwrap_or(value).to_string()))
+                })
+                .collect();
+            result.add(&name, &values, KITTY_SCHEME, &path);
+        }
+        Ok(result)
+    }
+
+    /// Add the profile of a session, unless it is the default one
+    fn add(&mut self, encoded_name: &str, values: &SessionValues, scheme: &str, path: &Path) {
+        let name = decode_session_name(encoded_name);
+        if name == DEFAULT_SESSION {
+            return;
+        }
+        let profile = session_profile(&name, values, scheme, path);
+        if let Some(source) = &profile.source_id {
+            self.sources.insert(source.clone());
+        }
+        self.profiles.push(profile);
+    }
+}
+
+/// Decode a session name: "%XX" escapes (UTF-8 bytes), other characters are kept
+///
+/// # Example
+/// ```
+/// use remmina_to_tabby::putty_parser::decode_session_name;
+///
+/// assert_eq!(decode_session_name("Prod%2Fweb%20server"), "Prod/web server");
+/// ```
+pub fn decode_session_name(name: &str) -> String {
+    let bytes = name.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| name.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// The values of a session as a connection
+fn session_profile(name: &str, values: &SessionValues, scheme: &str, path: &Path) -> Connection {
+    let value = |key: &str| values.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let flag = |key: &str| value(key).map(|v| v == "1");
+
+    // Folders: KiTTY "Folder" value, then the separators of the session name
+    let mut segments: Vec<String> = value("Folder").into_iter()
+        .flat_map(|folder| folder.split(['/', '\\']).map(str::to_string).collect::<Vec<_>>())
+        .collect();
+    let mut name_segments: Vec<&str> = name.split(['/', '\\']).filter(|s| !s.trim().is_empty()).collect();
+    let short_name = name_segments.pop().unwrap_or(name).trim().to_string();
+    segments.extend(name_segments.iter().map(|s| s.to_string()));
+    let segments: Vec<&str> = segments.iter().map(|s| s.trim()).filter(|s| !s.is_empty()).collect();
+    let group = (!segments.is_empty()).then(|| segments.join("/"));
+
+    // "user@host" is accepted as host name
+    let mut user = value("UserName");
+    let mut host = value("HostName");
+    if let Some((host_user, host_name)) = host.clone().as_deref().and_then(|s| s.rsplit_once('@')) {
+        user = user.or_else(|| Some(host_user.to_string()));
+        host = Some(host_name.to_string());
+    }
+
+    let private_key = value("PublicKeyFile");
+    let protocol = ConnectionProtocols::from_str(&value("Protocol").unwrap_or_else(|| "ssh".to_string()));
+    let (proxy_command, jump_host) = proxy(values, &short_name);
+    Connection {
+        name: Some(short_name),
+        host,
+        port: value("PortNumber").and_then(|p| p.parse().ok()).filter(|&p| p != 0),
+        group,
+        protocol,
+        user,
+        auth: private_key.as_ref().map(|_| AuthMethod::PublicKey),
+        private_key,
+        proxy_command,
+        jump_host,
+        compression: flag("Compression"),
+        remote_command: value("RemoteCommand"),
+        forward_x11: flag("X11Forward"),
+        source_id: Some(format!("{scheme}:{name}")),
+        path: path.to_path_buf(),
+        ..Default::default()
+    }
+}
+
+/// Proxy command or SSH jump host of a session's proxy settings
+///
+/// Notes:
+///     ProxyMethod: 0 none, 1 SOCKS 4, 2 SOCKS 5, 3 HTTP CONNECT, 4 Telnet, 5 local command, 6 SSH (PuTTY 0.77+),
+///     7 SSH exec and 8 SSH subsystem (PuTTY 0.79+); Telnet, exec and subsystem proxies are not mapped.
+///     SOCKS and HTTP proxies become a netcat proxy command, as Remmina and ssh use them
+fn proxy(values: &SessionValues, session: &str) -> (Option<String>, Option<JumpHost>) {
+    let value = |key: &str| values.get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let method = value("ProxyMethod").and_then(|m| m.parse::<u8>().ok()).unwrap_or(0);
+    let Some(host) = value("ProxyHost").filter(|_| method != 0) else { return (None, None) };
+    let port = value("ProxyPort").and_then(|p| p.parse::<u16>().ok());
+    let user = value("ProxyUsername");
+
+    let netcat = |kind: &str, default_port: u16| {
+        let credentials = user.as_ref().map(|u| format!(" -P {u}")).unwrap_or_default();
+        format!("nc -X {kind} -x {host}:{}{credentials} %h %p", port.unwrap_or(default_port))
+    };
+    match method {
+        1 => (Some(netcat("4", 1080)), None),
+        2 => (Some(netcat("5", 1080)), None),
+        3 => (Some(netcat("connect", 3128)), None),
+        5 => {
+            let command = value("ProxyTelnetCommand").map(|command| {
+                command.replace("\\n", "").replace("\\r", "")
+                    .replace("%proxyhost", &host)
+                    .replace("%proxyport", &port.unwrap_or_default().to_string())
+                    .replace("%host", "%h")
+                    .replace("%port", "%p")
+                    .replace("%user", "%r")
+            });
+            (command, None)
+        }
+        6 => (None, Some(JumpHost { host, port: port.unwrap_or(22), user, auth: None, private_key: None, password: None })),
+        4 => {
+            eprintln!(" └── Session '{session}': proxy method {method} (Telnet) cannot be mapped, ignored.");
+            (None, None)
+        }
+        7 | 8 => {
+            let kind = if method == 7 { "SSH exec" } else { "SSH subsystem" };
+            eprintln!(" └── Session '{session}': proxy method {method} ({kind}) cannot be mapped, ignored.");
+            (None, None)
+        }
+        _ => {
+            eprintln!(" └── Session '{session}': unknown proxy method {method}, ignored.");
+            (None, None)
+        }
+    }
+}
+
+/// Name and value of a registry value line, the value is None for binary or multi-string values
+///
+/// Notes:
+///     Strings are written "Name"="value" with '\' and '"' escaped, numbers "Name"=dword:0000001f
+fn parse_reg_value(line: &str) -> Option<(String, Option<String>)> {
+    let (name, rest) = parse_reg_string(line)?;
+    let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+    if rest.starts_with('"') {
+        let (value, _) = parse_reg_string(rest)?;
+        return Some((name, Some(value)));
+    }
+    if let Some(hex) = rest.strip_prefix("dword:") {
+        let number = u32::from_str_radix(hex.trim(), 16).ok()?;
+        return Some((name, Some(number.to_string())));
+    }
+    Some((name, None))
+}
+
+/// Unescaped content of the quoted string at the start of `text`, and what follows it
+fn parse_reg_string(text: &str) -> Option<(String, &str)> {
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    let mut value = String::new();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?.1),
+            '"' => return Some((value, &text[index + 2..])),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// Text of a session file: UTF-16 LE with a byte order mark (regedit), UTF-8 otherwise (byte order mark removed)
+pub fn decode_text(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => {
+            let units: Vec<u16> = rest.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+            String::from_utf16_lossy(&units)
+        }
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
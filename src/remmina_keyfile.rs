@@ -0,0 +1,281 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+/// Name of the section holding the connection settings in a .remmina file
+pub const REMMINA_SECTION: &str = "remmina";
+
+/// A single line inside a key file section
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum KeyFileLine {
+    Entry { key: String, value: String },
+    Comment(String),
+    Blank,
+}
+
+/// A `[section]` of a key file, with its lines in file order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyFileSection {
+    pub name: String,
+    lines: Vec<KeyFileLine>,
+}
+
+/// Document model of a .remmina file
+///
+/// Notes:
+///     .remmina files are written by GLib's GKeyFile, so parsing follows the same rules:
+///         - lines starting with '#' are comments, empty lines are kept as is
+///         - `[name]` starts a section, a section appearing twice is merged into the first one
+///         - `key=value`, whitespace around '=' is ignored and only the first '=' splits key and value
+///         - a key appearing twice in a section keeps the last value
+///         - values use the escapes \s \n \t \r \\
+///     Comments and ordering are preserved so a parsed file can be written back with `to_string()`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RemminaKeyFile {
+    /// Comments and blank lines found before the first section
+    leading: Vec<KeyFileLine>,
+    sections: Vec<KeyFileSection>,
+}
+
+/// Methods for KeyFileSection
+impl KeyFileSection {
+    fn new(name: &str) -> Self {
+        KeyFileSection { name: name.to_string(), lines: Vec::new() }
+    }
+
+    /// Returns the unescaped value of `key`, if present
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| match line {
+            KeyFileLine::Entry { key: k, value } if k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Sets `key` to `value` (unescaped), replacing an existing value in place
+    pub fn set(&mut self, key: &str, value: &str) {
+        for line in &mut self.lines {
+            if let KeyFileLine::Entry { key: k, value: v } = line
+                && k == key {
+                *v = value.to_string();
+                return;
+            }
+        }
+        self.lines.push(KeyFileLine::Entry { key: key.to_string(), value: value.to_string() });
+    }
+
+    /// Removes `key`, returns true if it was present
+    pub fn remove(&mut self, key: &str) -> bool {
+        let before = self.lines.len();
+        self.lines.retain(|line| !matches!(line, KeyFileLine::Entry { key: k, .. } if k == key));
+        before != self.lines.len()
+    }
+
+    /// Iterates over (key, value) pairs in file order
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.lines.iter().filter_map(|line| match line {
+            KeyFileLine::Entry { key, value } => Some((key.as_str(), value.as_str())),
+            _ => None,
+        })
+    }
+}
+
+/// Methods for RemminaKeyFile
+impl RemminaKeyFile {
+    /// Read and parse a key file from disk
+    ///
+    /// # Arguments
+    /// * `path` - Path of the .remmina file
+    /// # Returns
+    /// * `Result<Self>` - The parsed document, an I/O error or an InvalidRemminaFile error with the offending line
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        Self::parse(&content).map_err(|e| e.with_path(path))
+    }
+
+    /// Parse key file content
+    ///
+    /// # Arguments
+    /// * `content` - The whole key file content
+    /// # Returns
+    /// * `Result<Self>` - The parsed document, or an InvalidRemminaFile error (without path) with the offending line
+    /// # Errors
+    /// * A key/value pair appears before the first section
+    /// * A line is neither a comment, a section header nor a key/value pair
+    /// * A section header or key is empty or malformed
+    pub fn parse(content: &str) -> Result<Self> {
+        let invalid = |line: usize, message: String| Error::InvalidRemminaFile {
+            path: PathBuf::new(),
+            line: Some(line),
+            message,
+        };
+        let mut keyfile = RemminaKeyFile::default();
+        // Index of the section lines are currently appended to
+        let mut current: Option<usize> = None;
+
+        for (index, raw_line) in content.lines().enumerate() {
+            let line_number = index + 1;
+            // GKeyFile ignores leading whitespace on every line
+            let line = raw_line.trim_start();
+
+            let parsed = if line.is_empty() {
+                KeyFileLine::Blank
+            } else if line.starts_with('#') {
+                KeyFileLine::Comment(line.to_string())
+            } else if let Some(header) = line.strip_prefix('[') {
+                let name = header.trim_end().strip_suffix(']')
+                    .filter(|name| !name.is_empty() && !name.contains(['[', ']']))
+                    .ok_or_else(|| invalid(line_number, format!("invalid section header '{raw_line}'")))?;
+                current = Some(match keyfile.sections.iter().position(|s| s.name == name) {
+                    Some(existing) => existing,
+                    None => {
+                        keyfile.sections.push(KeyFileSection::new(name));
+                        keyfile.sections.len() - 1
+                    }
+                });
+                continue;
+            } else if let Some((key, value)) = line.split_once('=') {
+                let key = key.trim_end();
+                if key.is_empty() {
+                    return Err(invalid(line_number, format!("empty key in '{raw_line}'")));
+                }
+                KeyFileLine::Entry { key: key.to_string(), value: unescape_value(value.trim_start()) }
+            } else {
+                return Err(invalid(line_number, format!("expected 'key=value', section or comment, found '{raw_line}'")));
+            };
+
+            match (current, parsed) {
+                (None, KeyFileLine::Entry { .. }) => {
+                    return Err(invalid(line_number, "key file does not start with a section".to_string()));
+                }
+                (None, other) => keyfile.leading.push(other),
+                (Some(section), KeyFileLine::Entry { key, value }) => {
+                    // Last value wins, like GKeyFile
+                    let lines = &mut keyfile.sections[section].lines;
+                    lines.retain(|l| !matches!(l, KeyFileLine::Entry { key: k, .. } if *k == key));
+                    lines.push(KeyFileLine::Entry { key, value });
+                }
+                (Some(section), other) => keyfile.sections[section].lines.push(other),
+            }
+        }
+
+        Ok(keyfile)
+    }
+
+    /// Returns the section with the given name, if present
+    pub fn section(&self, name: &str) -> Option<&KeyFileSection> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    /// Returns the section with the given name, creating it at the end if missing
+    pub fn section_mut(&mut self, name: &str) -> &mut KeyFileSection {
+        let index = match self.sections.iter().position(|s| s.name == name) {
+            Some(index) => index,
+            None => {
+                self.sections.push(KeyFileSection::new(name));
+                self.sections.len() - 1
+            }
+        };
+        &mut self.sections[index]
+    }
+
+    /// Returns all section names in file order
+    pub fn section_names(&self) -> impl Iterator<Item = &str> {
+        self.sections.iter().map(|s| s.name.as_str())
+    }
+
+    /// Returns the value of `key` in `section` as a string, empty values are treated as missing
+    pub fn get_string(&self, section: &str, key: &str) -> Option<String> {
+        self.section(section)?
+            .get(key)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    }
+
+    /// Returns the value of `key` in `section` as an integer
+    pub fn get_int(&self, section: &str, key: &str) -> Option<i64> {
+        self.section(section)?.get(key)?.trim().parse().ok()
+    }
+
+    /// Returns the value of `key` in `section` as a boolean
+    ///
+    /// Notes:
+    ///     GKeyFile writes booleans as true/false, Remmina mostly stores them as 0/1 integers, both are accepted
+    pub fn get_bool(&self, section: &str, key: &str) -> Option<bool> {
+        match self.section(section)?.get(key)?.trim() {
+            "true" | "1" => Some(true),
+            "false" | "0" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the document back in key file format, escaping values
+impl fmt::Display for RemminaKeyFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_line(f: &mut fmt::Formatter<'_>, line: &KeyFileLine) -> fmt::Result {
+            match line {
+                KeyFileLine::Entry { key, value } => writeln!(f, "{key}={}", escape_value(value)),
+                KeyFileLine::Comment(comment) => writeln!(f, "{comment}"),
+                KeyFileLine::Blank => writeln!(f),
+            }
+        }
+
+        for line in &self.leading {
+            write_line(f, line)?;
+        }
+        for section in &self.sections {
+            writeln!(f, "[{}]", section.name)?;
+            for line in &section.lines {
+                write_line(f, line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decode GKeyFile escape sequences, unknown sequences are kept verbatim
+fn unescape_value(raw: &str) -> String {
+    let mut value = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => value.push(' '),
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some('\\') => value.push('\\'),
+            Some(other) => {
+                value.push('\\');
+                value.push(other);
+            }
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+/// Encode a value with GKeyFile escape sequences (leading spaces become \s)
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let mut leading = true;
+    for c in value.chars() {
+        match c {
+            ' ' if leading => escaped.push_str("\\s"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' => escaped.push_str("\\\\"),
+            other => escaped.push(other),
+        }
+        if c != ' ' {
+            leading = false;
+        }
+    }
+    escaped
+}
@@ -1,12 +1,16 @@
+use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
 
-use std::io::{BufRead, BufReader};
 // use crate::remmina_types::{RemminaProfile,RemminaFiles};
-use crate::remmina_types::{RemminaFiles, RemminaProfile, SshAuthMethod, get_auth_method_as_int};
-use crate::protocols_types::ALLOWED_PROTOCOLS_EXPORT;
+use crate::remmina_types::{RemminaFiles, RemminaProfile, SOURCE_ID_KEY, SshAuthMethod, SshTunnel, get_auth_method_as_int, get_auth_method_from_int};
+use crate::connection::{AuthMethod, Connection, JumpHost, split_list};
+use crate::error::{Error, Result};
+use crate::remmina_keyfile::{RemminaKeyFile, REMMINA_SECTION};
+use crate::remmina_secrets::{DecryptSummary, RemminaPref};
+use crate::protocols_types::{ALLOWED_PROTOCOLS_EXPORT, ConnectionProtocols};
+use crate::report::{CheckedFile, ExportedFile, RunReport, SkippedFile};
 
-#[allow(dead_code)]
 /// Methods for RemminaFiles
 impl RemminaFiles {
     /// Find all .remmina files in the given directory
@@ -17,24 +21,22 @@ impl RemminaFiles {
     /// 
     /// # Returns
     /// 
-    /// * `Result<Self, std::io::Error>` - A RemminaFiles struct or an IO error
+    /// * `Result<Self>` - A RemminaFiles struct or an IO error
     /// 
     /// # Errors
     /// 
-    /// Returns an error if the directory cannot be read or accessed
-    pub fn find(remmina_dir: &str) -> Result<Self, std::io::Error> {
-        let entries = fs::read_dir(remmina_dir)?;
+    /// Returns an error if the directory or one of its entries cannot be read or accessed
+    pub fn find(remmina_dir: &str) -> Result<Self> {
+        let entries = fs::read_dir(remmina_dir).map_err(|e| Error::io(remmina_dir, e))?;
         
-        let files: Vec<PathBuf> = entries
-            .filter_map(|entry| entry.ok())
-            .map(|entry| entry.path())
-            .filter(|path| {
-                path.is_file() && 
-                path.extension()
-                    .and_then(|ext| ext.to_str())
-                    == Some("remmina")
-            })
-            .collect();
+        let mut files = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| Error::io(remmina_dir, e))?.path();
+            if path.is_file() && path.extension().and_then(|ext| ext.to_str()) == Some("remmina") {
+                files.push(path);
+            }
+        }
+        files.sort();
         
         Ok(RemminaFiles { files })
     }
@@ -55,92 +57,160 @@ impl RemminaFiles {
         })
     }
 
+    /// Source ids of all the files, see `RemminaProfile::source_id`
+    ///
+    /// # Returns
+    /// * `HashSet<String>` - The file names, plus the `tabby_source_id` keys of the readable files
+    ///
+    /// Notes:
+    ///     Files left out by the protocol filter or that cannot be parsed still count,
+    ///     so pruning never removes the profile of a file that still exists
+    pub fn source_ids(&self) -> HashSet<String> {
+        let mut ids = HashSet::new();
+        for path in &self.files {
+            if let Some(name) = path.file_name() {
+                ids.insert(name.to_string_lossy().into_owned());
+            }
+            if let Some(id) = RemminaKeyFile::load(path).ok().and_then(|k| k.get_string(REMMINA_SECTION, SOURCE_ID_KEY)) {
+                ids.insert(id);
+            }
+        }
+        ids
+    }
+
     /// Show all found .remmina files
     pub fn show_files(&self) {
         for path in &self.files {
-            println!("Found remmina file: {}", path.display());
+            eprintln!("Found remmina file: {}", path.display());
+        }
+    }
+
+    /// Write connections as .remmina files into a directory
+    ///
+    /// # Arguments
+    /// * `remmina_dir` - Destination directory
+    /// * `connections` - Connections to write, their `path` is ignored, see `RemminaProfile::from_connection`
+    /// * `execute` - If false, only print what would be written (dry-run)
+    /// * `report` - Receives the exported files, and the existing files as skipped files
+    /// # Returns
+    /// * `Result<usize>` - Number of files written (or that would be written), or the first I/O error
+    /// # Behavior
+    /// * File names follow the Remmina convention, see `RemminaProfile::file_name`
+    /// * Existing files are never overwritten, the profile is skipped (kind "exists")
+    pub fn write_profiles(remmina_dir: &Path, connections: &[Connection], execute: bool, report: &mut RunReport) -> Result<usize> {
+        let mut written = 0;
+        for connection in connections {
+            let profile = RemminaProfile::from_connection(connection);
+            let name = profile.name.as_deref().or(profile.server.as_deref()).unwrap_or("profile");
+            let path = remmina_dir.join(profile.file_name());
+
+            if path.exists() {
+                eprintln!(" └── {} already exists. Skipping export of '{name}'.", path.display());
+                report.skipped_files.push(SkippedFile {
+                    reason: format!("'{name}': file already exists, not overwritten"),
+                    path,
+                    kind: "exists".to_string(),
+                });
+                continue;
+            }
+            if execute {
+                fs::write(&path, profile.to_keyfile().to_string())
+                    .map_err(|e| Error::io(&path, e))?;
+                eprintln!(" ⬅️  Exported '{name}' to {} ✅", path.display());
+            } else {
+                eprintln!("Dry-run: would export '{name}' to {}", path.display());
+            }
+            report.exported_files.push(ExportedFile { name: name.to_string(), path, written: execute });
+            written += 1;
         }
+        Ok(written)
+    }
+
+    /// Load a .remmina file and return the protocol of its [remmina] section, uppercased
+    ///
+    /// # Returns
+    /// * `Result<Option<String>>` - The protocol if set, or an error if the file cannot be read or parsed
+    fn read_protocol(path: &Path) -> Result<Option<String>> {
+        let keyfile = RemminaKeyFile::load(path)?;
+        Ok(keyfile.get_string(REMMINA_SECTION, "protocol").map(|p| p.trim().to_uppercase()))
     }
 
-    /// Check if file contains a line starting with "protocol=" and show the value
+    /// Check the protocol set in the [remmina] section of each file and show the value
     /// 
+    /// # Arguments
+    /// * `report` - Receives the result of every file
+    /// # Returns
+    /// * `usize` - Number of files that cannot be imported (unsupported, unknown or missing protocol, unreadable)
     /// # Behavior
     /// * If protocol is "SSH", print available
     /// * If protocol is "RDP" or "VNC", print not implemented
     /// * If protocol is unrecognized, print warning protocol not recognized
-    pub fn check_protocols(&self) {
+    pub fn check_protocols(&self, report: &mut RunReport) -> usize {
+        let mut not_available = 0;
         for path in &self.files {
-            let mut found = false;
-            if let Ok(file) = fs::File::open(path) {
-                let reader = BufReader::new(file);
-                for line_result in reader.lines() {
-                    match line_result {
-                        Ok(line) => {
-                            if let Some(rest) = line.strip_prefix("protocol=") {
-                                let protocol = rest.trim().to_uppercase();
-                                match protocol.as_str() {
-                                    "SSH" => {
-                                        println!("{}: protocol={} ✅ [available]", path.display(), protocol);
-                                    }
-                                    "RDP" | "VNC" => {
-                                        println!("{}: protocol={} ❌ [not implemented]", path.display(), protocol);
-                                    }
-                                    _ => {
-                                        println!("{}: protocol not recognized ({}) ⚠️", path.display(), protocol);
-                                    }
-                                }
-                                found = true;
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!("Warning: Error reading line from {}: {}", path.display(), e);
-                            break; // Stop processing this file on error
-                        }
+            let (protocol, status) = match Self::read_protocol(path) {
+                Ok(Some(protocol)) => match protocol.as_str() {
+                    "SSH" => {
+                        eprintln!("{}: protocol={} ✅ [available]", path.display(), protocol);
+                        (Some(protocol), "available")
+                    }
+                    "RDP" | "VNC" => {
+                        eprintln!("{}: protocol={} ❌ [not implemented]", path.display(), protocol);
+                        (Some(protocol), "not implemented")
                     }
+                    _ => {
+                        eprintln!("{}: protocol not recognized ({}) ⚠️", path.display(), protocol);
+                        (Some(protocol), "not recognized")
+                    }
+                },
+                Ok(None) => {
+                    eprintln!("{}: protocol not found ❌", path.display());
+                    (None, "missing")
                 }
+                Err(e) => {
+                    eprintln!("Warning: {e}");
+                    (None, "unreadable")
+                }
+            };
+            if status != "available" {
+                not_available += 1;
             }
-            if !found {
-                println!("{}: protocol not found ❌", path.display());
-            }
+            report.checked_files.push(CheckedFile { path: path.clone(), protocol, status: status.to_string() });
         }
+        not_available
     }
 
     /// Return a new RemminaFiles containing only files with any of the given protocols (case-insensitive)
     /// 
     /// # Arguments
     /// * `protocols` - A slice of strings representing the protocols to filter by
+    /// * `report` - Receives the files left out, with the reason
     /// # Returns
     /// * `RemminaFiles` - A new RemminaFiles struct containing only the filtered files
     /// # Behavior
-    /// * For each file, read lines and look for "protocol=" line
+    /// * For each file, parse it and read "protocol" from the [remmina] section
     /// * If the protocol matches any in the given list (case-insensitive), include the file in the result
     /// * If no match, exclude the file
-    /// * If file cannot be read, skip it
-    pub fn filter_by_protocols(&self, protocols: &[String]) -> RemminaFiles {
+    /// * If file cannot be read or parsed, skip it
+    pub fn filter_by_protocols(&self, protocols: &[String], report: &mut RunReport) -> RemminaFiles {
         let mut filtered_files = Vec::new();
 
         for path in &self.files {
-            if let Ok(file) = fs::File::open(path) {
-                let reader = BufReader::new(file);
-                for line_result in reader.lines() {
-                    match line_result {
-                        Ok(line) => {
-                            if let Some(rest) = line.strip_prefix("protocol=") {
-                                let proto = rest.trim().to_uppercase();
-                                if protocols.iter().any(|p| p == &proto) {
-                                    filtered_files.push(path.clone());
-                                }
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!("Warning: Error reading line from {}: {}", path.display(), e);
-                            break; // Stop processing this file on error
-                        }
+            let skipped = match Self::read_protocol(path) {
+                Ok(Some(proto)) => {
+                    if protocols.iter().any(|p| p == &proto) {
+                        filtered_files.push(path.clone());
+                        continue;
                     }
+                    SkippedFile { path: path.clone(), kind: "filtered".to_string(), reason: format!("protocol {proto} not selected") }
                 }
-            }
+                Ok(None) => SkippedFile { path: path.clone(), kind: "filtered".to_string(), reason: "protocol not set".to_string() },
+                Err(e) => {
+                    eprintln!("Warning: {e}");
+                    SkippedFile::from_error(path, &e)
+                }
+            };
+            report.skipped_files.push(skipped);
         }
 
         RemminaFiles {
@@ -154,168 +224,429 @@ impl RemminaFiles {
     /// * `execute` - If true, perform the export (currently just prints a message
     /// * If false, just print what would be done (dry-run)
     /// # Behavior
-    /// * For each file, read lines and look for "protocol=" line
+    /// * For each file, parse it and read "protocol" from the [remmina] section
     /// * If the protocol is in ALLOWED_PROTOCOLS_EXPORT, print the file path and protocol
     /// * If `execute` is true, print "Exporting" message
     /// * If `execute` is false, print "Dry-run" message
-    /// * If file cannot be read, skip it
+    /// * If file cannot be read or parsed, skip it
     pub fn export_profiles_base(&self, execute: bool) {
         for path in &self.files {
-            if let Ok(file) = fs::File::open(path) {
-                let reader = BufReader::new(file);
-                for line_result in reader.lines() {
-                    match line_result {
-                        Ok(line) => {
-                            if let Some(rest) = line.strip_prefix("protocol=") {
-                                let protocol = rest.trim().to_uppercase();
-                                if ALLOWED_PROTOCOLS_EXPORT.iter().any(|&p| p == protocol) {
-                                    if execute {
-                                        println!(" ⬅️  Exporting: {} (protocol={}) ✅", path.display(), protocol);
-                                    } else {
-                                        println!("Dry-run: {} (protocol={})", path.display(), protocol);
-                                    }
-                                }
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!("Warning: Error reading line from {}: {}", path.display(), e);
-                            break; // Stop processing this file on error
+            match Self::read_protocol(path) {
+                Ok(Some(protocol)) => {
+                    if ALLOWED_PROTOCOLS_EXPORT.iter().any(|&p| p == protocol) {
+                        if execute {
+                            eprintln!(" ⬅️  Exporting: {} (protocol={}) ✅", path.display(), protocol);
+                        } else {
+                            eprintln!("Dry-run: {} (protocol={})", path.display(), protocol);
                         }
                     }
                 }
+                Ok(None) => {}
+                Err(e) => eprintln!("Warning: {e}"),
             }
         }
     }
 
     /// Extract profiles with name, server, group, protocol from files using ALLOWED_PROTOCOLS_EXPORT
     /// 
+    /// # Arguments
+    /// * `pref` - Remmina preferences used to decrypt stored passwords, None to leave secrets out
+    /// * `report` - Receives the files that could not be turned into a profile and the secrets summary
     /// # Returns
     /// * `Vec<RemminaProfile>` - A vector of RemminaProfile structs containing extracted profile information
     /// # Behavior
-    /// * For each file, read lines and look for "name=", "server=", "group=", "protocol=" lines
-    /// * If the protocol is in ALLOWED_PROTOCOLS_EXPORT, create a RemminaProfile struct with the extracted information
-    /// * Add the RemminaProfile to the result vector
-    /// * If file cannot be read, skip it
-    pub fn export_profiles(&self) -> Vec<RemminaProfile> {
+    /// * For each file, parse it as a key file and build a RemminaProfile from its [remmina] section
+    /// * If the protocol is in ALLOWED_PROTOCOLS_EXPORT, add the RemminaProfile to the result vector
+    /// * If file cannot be read or parsed, skip it
+    /// * Decrypted secrets are never printed, only a summary of the decryption is shown
+    pub fn export_profiles(&self, pref: Option<&RemminaPref>, report: &mut RunReport) -> Vec<RemminaProfile> {
         let mut profiles = Vec::new();
+        let mut secrets_summary = DecryptSummary::default();
 
         for path in &self.files {
-            let mut name = None;
-            let mut server = None;
-            let mut port = None;
-            let mut group = None;
-            let mut protocol = None;
-            let mut user = None;
-            let mut auth_method = None;
-            let mut ssh_auth_value = None;
-            let mut rdp_auth_value = None; // Prototype for RDP auth
-            let mut vnc_auth_value = None; // Prototype for VNC auth
-
-            // First pass: collect all key-value pairs
-            if let Ok(file) = fs::File::open(path) {
-                let reader = BufReader::new(file);
-                for line_result in reader.lines() {
-                    match line_result {
-                        Ok(line) => {
-                            let line = line.trim();
-                            if let Some(rest) = line.strip_prefix("name=") {
-                                name = Some(rest.to_string());
-                            } else if let Some(rest) = line.strip_prefix("server=") {
-                                server = Some(rest.to_string());
-                            } else if let Some(rest) = line.strip_prefix("user=") {
-                                if !rest.is_empty() {
-                                    user = Some(rest.to_string());
-                                }
-                            } else if let Some(rest) = line.strip_prefix("group=") {
-                                group = Some(rest.to_string());
-                            } else if let Some(rest) = line.strip_prefix("protocol=") {
-                                let proto = rest.to_uppercase();
-                                if ALLOWED_PROTOCOLS_EXPORT.iter().any(|&p| p == proto) {
-                                    protocol = Some(proto);
-                                }
-                            } else if let Some(rest) = line.strip_prefix("ssh_auth=") {
-                                ssh_auth_value = Some(rest.to_string());
-                            } else if let Some(rest) = line.strip_prefix("rdp_auth=") {
-                                rdp_auth_value = Some(rest.to_string()); // Store for future use
-                            } else if let Some(rest) = line.strip_prefix("vnc_auth=") {
-                                vnc_auth_value = Some(rest.to_string()); // Store for future use
-                            } else if let Some(rest) = line.strip_prefix("port=") {
-                                port = Some(rest.to_string());
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!("Warning: Error reading line from {}: {}", path.display(), e);
-                            break;
-                        }
-                    }
+            let keyfile = match RemminaKeyFile::load(path) {
+                Ok(keyfile) => keyfile,
+                Err(e) => {
+                    eprintln!("Warning: {e}");
+                    report.skipped_files.push(SkippedFile::from_error(path, &e));
+                    continue;
                 }
+            };
+
+            let mut profile = match RemminaProfile::from_keyfile(&keyfile, path) {
+                Ok(profile) => profile,
+                Err(e) => {
+                    eprintln!("Warning: {e}");
+                    report.skipped_files.push(SkippedFile::from_error(path, &e));
+                    continue;
+                }
+            };
+
+            if let Some(pref) = pref {
+                pref.decrypt_profile(&keyfile, &mut profile, &mut secrets_summary);
             }
 
-            // Now, after protocol is known, handle ssh_auth if protocol is SSH
-            if let (Some(proto), Some(rest)) = (protocol.as_ref(), ssh_auth_value.as_ref()) {
-                if proto.as_str() == "SSH" {
-                    let method = rest.parse::<u8>()
-                        .map(crate::remmina_types::get_auth_method_from_int)
-                        .unwrap_or_else(|_| SshAuthMethod::from_str(rest));
-                    if let SshAuthMethod::Unknown(ref s) = method {
-                        eprintln!("Warning: Unknown SSH auth method '{}' in file {}", s, path.display());
+            eprintln!(" ⬅️  Exporting Profile:");
+            eprintln!("    • Name:     {}", profile.name.as_deref().unwrap_or("<none>"));
+            eprintln!("    • Server:   {}", profile.server.as_deref().unwrap_or("<none>"));
+            eprintln!("    • Port:     {}", profile.port.as_deref().unwrap_or("<none>"));
+            eprintln!("    • User:     {}", profile.user.as_deref().unwrap_or("<none>"));
+            eprintln!("    • Group:    {}", profile.group.as_deref().unwrap_or("<none>"));
+            eprintln!("    • Protocol: {}", profile.protocol.as_deref().unwrap_or("<none>"));
+            eprintln!(
+                "    • Auth Method: {}",
+                match (profile.auth.as_deref(), profile.protocol.as_deref()) {
+                    (Some(m), Some("SSH")) => {
+                        let method = SshAuthMethod::from_str(m);
+                        format!("{:?} [{}]", method, get_auth_method_as_int(&method))
                     }
-                    auth_method = Some(method);
+                    (Some(m), _) => m.to_string(),
+                    (None, _) => "<none>".to_string(),
                 }
+            );
+            let ssh_options = [
+                ("Private key", profile.private_key.as_deref()),
+                ("Proxy command", profile.proxy_command.as_deref()),
+                ("Charset", profile.charset.as_deref()),
+                ("KEX", profile.kex_algorithms.as_deref()),
+                ("Ciphers", profile.ciphers.as_deref()),
+                ("Host keys", profile.host_key_types.as_deref()),
+                ("Exec", profile.exec.as_deref()),
+            ];
+            for (label, value) in ssh_options {
+                if let Some(value) = value {
+                    eprintln!("    • {label}: {value}");
+                }
+            }
+            if let Some(tunnel) = &profile.tunnel {
+                eprintln!(
+                    "    • SSH tunnel: {}@{}:{}",
+                    tunnel.user.as_deref().unwrap_or("<none>"),
+                    tunnel.server,
+                    tunnel.port
+                );
             }
+            eprintln!("    • Path:     {}", profile.path.display());
 
-            // Prototype: Handle RDP auth (future implementation)
-            if let (Some(proto), Some(rest)) = (protocol.as_ref(), rdp_auth_value.as_ref()) {
-                if proto.as_str() == "RDP" {
-                    // TODO: Implement RDP auth method parsing
-                    println!("(Prototype) Found RDP auth method '{}' in file {}", rest, path.display());
-                    // Example: auth_method = Some(RdpAuthMethod::from_str(rest));
-                }
+            profiles.push(profile);
+        }
+
+        if pref.is_some() {
+            eprintln!(
+                "\n🔑 Secrets: {} decrypted, {} stored in keyring (skipped), {} failed",
+                secrets_summary.decrypted, secrets_summary.in_keyring, secrets_summary.failed
+            );
+            report.secrets = Some(secrets_summary);
+        }
+
+        profiles
+    }
+}
+
+/// Methods for RemminaProfile
+impl RemminaProfile {
+    /// Build a .remmina document from the profile
+    ///
+    /// # Returns
+    /// * `RemminaKeyFile` - A key file with a [remmina] section holding the profile settings
+    /// # Behavior
+    /// * Writes the keys read by `from_keyfile`, auth methods as their Remmina index (see `get_auth_method_as_int`)
+    /// * The SSH tunnel is written as ssh_tunnel_enabled=1 with ssh_tunnel_server "host:port"
+    /// * Secrets are never written
+    pub fn to_keyfile(&self) -> RemminaKeyFile {
+        let flag = |value: Option<bool>| value.map(|v| if v { "1" } else { "0" }.to_string());
+        let auth_index = |auth: &Option<String>| auth.as_deref()
+            .map(SshAuthMethod::from_str)
+            .filter(|method| !matches!(method, SshAuthMethod::Unknown(_)))
+            .map(|method| get_auth_method_as_int(&method).to_string());
+
+        let mut keyfile = RemminaKeyFile::default();
+        let section = keyfile.section_mut(REMMINA_SECTION);
+        let mut values = vec![
+            ("name", self.name.clone()),
+            ("group", self.group.clone()),
+            ("server", self.server.clone()),
+            ("port", self.port.clone()),
+            ("protocol", self.protocol.clone()),
+            ("username", self.user.clone()),
+            ("ssh_auth", auth_index(&self.auth)),
+            ("ssh_privatekey", self.private_key.clone()),
+            ("ssh_proxycommand", self.proxy_command.clone()),
+            ("ssh_compression", flag(self.compression)),
+            ("ssh_stricthostkeycheck", flag(self.strict_host_key_check)),
+            ("ssh_charset", self.charset.clone()),
+            ("ssh_kex_algorithms", self.kex_algorithms.clone()),
+            ("ssh_ciphers", self.ciphers.clone()),
+            ("ssh_hostkeytypes", self.host_key_types.clone()),
+            ("exec", self.exec.clone()),
+            ("ssh_forward_x11", flag(self.forward_x11)),
+            (SOURCE_ID_KEY, self.source_id.clone()),
+        ];
+        if let Some(tunnel) = &self.tunnel {
+            values.extend([
+                ("ssh_tunnel_enabled", Some("1".to_string())),
+                ("ssh_tunnel_server", Some(format!("{}:{}", tunnel.server, tunnel.port))),
+                ("ssh_tunnel_username", tunnel.user.clone()),
+                ("ssh_tunnel_auth", auth_index(&tunnel.auth)),
+                ("ssh_tunnel_privatekey", tunnel.private_key.clone()),
+            ]);
+        }
+        for (key, value) in values {
+            if let Some(value) = value {
+                section.set(key, &value);
             }
+        }
+        keyfile
+    }
 
-            // Prototype: Handle VNC auth (future implementation)
-            if let (Some(proto), Some(rest)) = (protocol.as_ref(), vnc_auth_value.as_ref()) {
-                if proto.as_str() == "VNC" {
-                    // TODO: Implement VNC auth method parsing
-                    println!("(Prototype) Found VNC auth method '{}' in file {}", rest, path.display());
-                    // Example: auth_method = Some(VncAuthMethod::from_str(rest));
-                }
+    /// File name Remmina gives to the profile, following its default "%G_%P_%N_%h" pattern
+    ///
+    /// # Returns
+    /// * `String` - "group_protocol_name_server.remmina", lowercased, with the characters Remmina
+    ///   does not keep in file names (`\ % | / $ ? < > : * . "` and spaces) replaced by '-'
+    pub fn file_name(&self) -> String {
+        let parts = [&self.group, &self.protocol, &self.name, &self.server];
+        let stem: String = parts.iter()
+            .map(|part| part.as_deref().unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("_")
+            .to_lowercase()
+            .chars()
+            .map(|c| if "\\%|/$?<>:*. \"".contains(c) { '-' } else { c })
+            .collect();
+        format!("{stem}.remmina")
+    }
+
+    /// Build a profile from the [remmina] section of a parsed .remmina file
+    ///
+    /// # Arguments
+    /// * `keyfile` - The parsed .remmina document
+    /// * `path` - Path of the file, kept in the profile as its source
+    /// # Returns
+    /// * `Result<RemminaProfile>` - InvalidRemminaFile if the file has no protocol in its [remmina] section,
+    ///   UnsupportedProtocol if the protocol is not in ALLOWED_PROTOCOLS_EXPORT
+    pub fn from_keyfile(keyfile: &RemminaKeyFile, path: &Path) -> Result<Self> {
+        let get = |key: &str| keyfile.get_string(REMMINA_SECTION, key);
+
+        let protocol = get("protocol")
+            .map(|p| p.trim().to_uppercase())
+            .ok_or_else(|| Error::InvalidRemminaFile {
+                path: path.to_path_buf(),
+                line: None,
+                message: format!("no protocol in [{REMMINA_SECTION}] section"),
+            })?;
+        if !ALLOWED_PROTOCOLS_EXPORT.iter().any(|&p| p == protocol) {
+            return Err(Error::UnsupportedProtocol { path: path.to_path_buf(), protocol });
+        }
+
+        // Handle ssh_auth if protocol is SSH, the value is usually the numeric index of the method
+        let mut auth_method = None;
+        if protocol == "SSH"
+            && let Some(rest) = get("ssh_auth") {
+            let method = rest.parse::<u8>()
+                .map(get_auth_method_from_int)
+                .unwrap_or_else(|_| SshAuthMethod::from_str(&rest));
+            if let SshAuthMethod::Unknown(ref s) = method {
+                eprintln!("Warning: Unknown SSH auth method '{}' in file {}", s, path.display());
             }
+            auth_method = Some(method);
+        }
 
-            if protocol.is_some() {
-                let profile = RemminaProfile {
-                    name,
-                    server,
-                    port,
-                    group,
-                    protocol,
-                    user,
-                    auth: auth_method.as_ref().map(|m| m.as_str().to_string()),
-                    path: path.clone(),
-                };
-
-                println!(" ⬅️  Exporting Profile:");
-                println!("    • Name:     {}", profile.name.as_deref().unwrap_or("<none>"));
-                println!("    • Server:   {}", profile.server.as_deref().unwrap_or("<none>"));
-                println!("    • Port:     {}", profile.port.as_deref().unwrap_or("<none>"));
-                println!("    • User:     {}", profile.user.as_deref().unwrap_or("<none>"));
-                println!("    • Group:    {}", profile.group.as_deref().unwrap_or("<none>"));
-                println!("    • Protocol: {}", profile.protocol.as_deref().unwrap_or("<none>"));
-                println!(
-                    "    • Auth Method: {}",
-                    match (auth_method.as_ref(), profile.protocol.as_deref()) {
-                        (Some(m), Some("SSH")) => format!("{:?} [{}]", m, get_auth_method_as_int(m)),
-                        (Some(m), _) => format!("{m:?}"),
-                        (None, _) => "<none>".to_string(),
-                    }
-                );
-                println!("    • Path:     {}", profile.path.display());
+        // Prototype: Handle RDP auth (future implementation)
+        if protocol == "RDP"
+            && let Some(rest) = get("rdp_auth") {
+            // TODO: Implement RDP auth method parsing
+            eprintln!("(Prototype) Found RDP auth method '{}' in file {}", rest, path.display());
+            // Example: auth_method = Some(RdpAuthMethod::from_str(rest));
+        }
+
+        // Prototype: Handle VNC auth (future implementation)
+        if protocol == "VNC"
+            && let Some(rest) = get("vnc_auth") {
+            // TODO: Implement VNC auth method parsing
+            eprintln!("(Prototype) Found VNC auth method '{}' in file {}", rest, path.display());
+            // Example: auth_method = Some(VncAuthMethod::from_str(rest));
+        }
+
+        let get_bool = |key: &str| keyfile.get_bool(REMMINA_SECTION, key);
 
-                profiles.push(profile);
+        // SSH tunnel: an empty ssh_tunnel_server means "same server" in Remmina, which is not a bastion
+        let tunnel = if get_bool("ssh_tunnel_enabled") == Some(true) {
+            match get("ssh_tunnel_server") {
+                Some(server) => {
+                    let (server, port) = JumpHost::parse_server(&server);
+                    let tunnel_auth = get("ssh_tunnel_auth").map(|rest| {
+                        rest.parse::<u8>()
+                            .map(get_auth_method_from_int)
+                            .unwrap_or_else(|_| SshAuthMethod::from_str(&rest))
+                            .as_str()
+                            .to_string()
+                    });
+                    Some(SshTunnel {
+                        server,
+                        port,
+                        user: get("ssh_tunnel_username"),
+                        auth: tunnel_auth,
+                        private_key: get("ssh_tunnel_privatekey"),
+                        password: None,
+                    })
+                }
+                None => {
+                    eprintln!("Warning: SSH tunnel enabled without ssh_tunnel_server in file {}, ignored", path.display());
+                    None
+                }
             }
+        } else {
+            None
+        };
+
+        Ok(RemminaProfile {
+            name: get("name"),
+            server: get("server"),
+            port: get("port"),
+            group: get("group"),
+            protocol: Some(protocol),
+            // Remmina stores the SSH user as "username", older releases used "ssh_username"
+            user: get("username").or_else(|| get("ssh_username")).or_else(|| get("user")),
+            auth: auth_method.as_ref().map(|m| m.as_str().to_string()),
+            private_key: get("ssh_privatekey"),
+            proxy_command: get("ssh_proxycommand"),
+            compression: get_bool("ssh_compression"),
+            strict_host_key_check: get_bool("ssh_stricthostkeycheck"),
+            charset: get("ssh_charset"),
+            kex_algorithms: get("ssh_kex_algorithms"),
+            ciphers: get("ssh_ciphers"),
+            host_key_types: get("ssh_hostkeytypes"),
+            exec: get("exec"),
+            forward_x11: get_bool("ssh_forward_x11"),
+            tunnel,
+            password: None,
+            ssh_passphrase: None,
+            path: path.to_path_buf(),
+            source_id: get(SOURCE_ID_KEY)
+                .or_else(|| path.file_name().map(|name| name.to_string_lossy().into_owned())),
+        })
+    }
+    /// The profile as a connection, the Remmina side of `Importer::import`
+    ///
+    /// # Behavior
+    /// * ssh_auth: sshidentityfile and publickey -> public key, sshagent -> agent,
+    ///   kerberosinteractive -> keyboard-interactive, kerberosgssapi -> GSSAPI, unknown methods are left unset;
+    ///   a SSH profile without ssh_auth uses password, Remmina's default
+    /// * The comma separated algorithm lists are split, a port that is not a number is left unset
+    pub fn to_connection(self) -> Connection {
+        let port = self.port.as_deref().and_then(|port| {
+            let parsed = port.trim().parse::<u16>().ok();
+            if parsed.is_none() {
+                eprintln!("Warning: Invalid port '{port}' in file {}, using the default port", self.path.display());
+            }
+            parsed
+        });
+        let list = |value: Option<String>| value.as_deref().map(split_list).unwrap_or_default();
+        let protocol = ConnectionProtocols::from_str(self.protocol.as_deref().unwrap_or_default());
+        let auth = match self.auth.as_deref() {
+            Some(auth) => connection_auth(auth),
+            None if protocol == ConnectionProtocols::Ssh => Some(AuthMethod::Password),
+            None => None,
+        };
+        Connection {
+            name: self.name,
+            host: self.server,
+            port,
+            group: self.group,
+            protocol,
+            user: self.user,
+            auth,
+            private_key: self.private_key,
+            proxy_command: self.proxy_command,
+            jump_host: self.tunnel.map(|tunnel| JumpHost {
+                host: tunnel.server,
+                port: tunnel.port,
+                user: tunnel.user,
+                auth: tunnel.auth.as_deref().and_then(connection_auth),
+                private_key: tunnel.private_key,
+                password: tunnel.password,
+            }),
+            compression: self.compression,
+            strict_host_key_check: self.strict_host_key_check,
+            charset: self.charset,
+            kex_algorithms: list(self.kex_algorithms),
+            ciphers: list(self.ciphers),
+            host_key_types: list(self.host_key_types),
+            remote_command: self.exec,
+            forward_x11: self.forward_x11,
+            password: self.password,
+            key_passphrase: self.ssh_passphrase,
+            path: self.path,
+            source_id: self.source_id,
+            ..Default::default()
         }
+    }
 
-        profiles
+    /// The profile of a connection, the Remmina side of `Exporter::export`
+    ///
+    /// # Behavior
+    /// * Public key authentication with a private key is written as sshidentityfile, without as publickey
+    /// * The jump host becomes the SSH tunnel, color, icon and weight have no Remmina equivalent
+    pub fn from_connection(connection: &Connection) -> Self {
+        let list = |values: &[String]| Some(values.join(",")).filter(|l| !l.is_empty());
+        let auth = |auth: Option<AuthMethod>, private_key: &Option<String>| {
+            auth.map(|auth| remmina_auth(auth, private_key.is_some()).as_str().to_string())
+        };
+        RemminaProfile {
+            name: connection.name.clone(),
+            server: connection.host.clone(),
+            port: connection.port.map(|port| port.to_string()),
+            group: connection.group.clone(),
+            protocol: Some(connection.protocol.as_str().to_uppercase()),
+            user: connection.user.clone(),
+            auth: auth(connection.auth, &connection.private_key),
+            private_key: connection.private_key.clone(),
+            proxy_command: connection.proxy_command.clone(),
+            compression: connection.compression,
+            strict_host_key_check: connection.strict_host_key_check,
+            charset: connection.charset.clone(),
+            kex_algorithms: list(&connection.kex_algorithms),
+            ciphers: list(&connection.ciphers),
+            host_key_types: list(&connection.host_key_types),
+            exec: connection.remote_command.clone(),
+            forward_x11: connection.forward_x11,
+            tunnel: connection.jump_host.as_ref().map(|jump| SshTunnel {
+                server: jump.host.clone(),
+                port: jump.port,
+                user: jump.user.clone(),
+                auth: auth(jump.auth, &jump.private_key),
+                private_key: jump.private_key.clone(),
+                password: jump.password.clone(),
+            }),
+            password: connection.password.clone(),
+            ssh_passphrase: connection.key_passphrase.clone(),
+            path: connection.path.clone(),
+            source_id: connection.source_id.clone(),
+        }
+    }
+}
+
+/// Authentication method of a Remmina ssh_auth name (`SshAuthMethod::as_str`), None for unknown methods
+fn connection_auth(auth: &str) -> Option<AuthMethod> {
+    match SshAuthMethod::from_str(auth) {
+        SshAuthMethod::Password => Some(AuthMethod::Password),
+        SshAuthMethod::SSHIdentityFile | SshAuthMethod::PublicKey => Some(AuthMethod::PublicKey),
+        SshAuthMethod::SSHAgent => Some(AuthMethod::Agent),
+        SshAuthMethod::KerberosInteractive => Some(AuthMethod::KeyboardInteractive),
+        SshAuthMethod::KerberosGSSAPI => Some(AuthMethod::Gssapi),
+        SshAuthMethod::Unknown(_) => None,
+    }
+}
+
+/// Remmina ssh_auth of an authentication method, a public key is an identity file when a private key is set
+fn remmina_auth(auth: AuthMethod, has_private_key: bool) -> SshAuthMethod {
+    match auth {
+        AuthMethod::Password => SshAuthMethod::Password,
+        AuthMethod::PublicKey if has_private_key => SshAuthMethod::SSHIdentityFile,
+        AuthMethod::PublicKey => SshAuthMethod::PublicKey,
+        AuthMethod::Agent => SshAuthMethod::SSHAgent,
+        AuthMethod::KeyboardInteractive => SshAuthMethod::KerberosInteractive,
+        AuthMethod::Gssapi => SshAuthMethod::KerberosGSSAPI,
     }
 }
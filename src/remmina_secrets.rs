@@ -0,0 +1,209 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use cbc::cipher::{BlockDecryptMut, KeyIvInit, block_padding::NoPadding};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::remmina_keyfile::{RemminaKeyFile, REMMINA_SECTION};
+use crate::remmina_types::RemminaProfile;
+use crate::tabby_parser::Profile;
+
+/// Section of remmina.pref holding the encryption secret
+const PREF_SECTION: &str = "remmina_pref";
+
+/// Value stored instead of the password when Remmina saves it in the system keyring
+const KEYRING_MARKER: &str = ".";
+
+type TdesCbcDecryptor = cbc::Decryptor<des::TdesEde3>;
+
+/// A decrypted secret, only kept in memory
+///
+/// Notes:
+///     Debug never shows the value, use `expose()` to hand it to a secret sink
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        Secret(value)
+    }
+
+    /// Returns the plaintext value
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(<redacted>)")
+    }
+}
+
+/// Kind of secret attached to an imported profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretKind {
+    /// SSH login password (Remmina "password" / "ssh_tunnel_password")
+    Password,
+    /// Private key passphrase (Remmina "ssh_passphrase")
+    KeyPassphrase,
+}
+
+impl SecretKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SecretKind::Password => "password",
+            SecretKind::KeyPassphrase => "key passphrase",
+        }
+    }
+}
+
+/// Destination of the secrets of imported profiles
+pub trait SecretSink {
+    /// Store `secret` for the imported Tabby profile `profile`
+    fn store(&mut self, profile: &Profile, kind: SecretKind, secret: &Secret);
+}
+
+/// Secret sink that drops the secrets and only records which ones were handed over
+#[derive(Debug, Default)]
+pub struct SecretReport {
+    pub stored: Vec<(String, SecretKind)>,
+}
+
+impl SecretSink for SecretReport {
+    fn store(&mut self, profile: &Profile, kind: SecretKind, _secret: &Secret) {
+        self.stored.push((profile.id.clone().unwrap_or_default(), kind));
+    }
+}
+
+/// Counters of a secrets decryption run
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct DecryptSummary {
+    pub decrypted: usize,
+    pub in_keyring: usize,
+    pub failed: usize,
+}
+
+/// Remmina preferences holding the key used to encrypt stored passwords
+///
+/// Notes:
+///     When no keyring is used, Remmina encrypts passwords with 3DES (CBC, zero padded) and stores them base64 encoded.
+///     The key is the first 24 bytes and the IV the last 8 bytes of the base64 "secret" in the [remmina_pref] section
+#[derive(Clone)]
+pub struct RemminaPref {
+    secret: Vec<u8>,
+}
+
+/// Methods for RemminaPref
+impl RemminaPref {
+    /// Find remmina.pref next to the profiles, or in the default Remmina config directory
+    ///
+    /// # Arguments
+    /// * `remmina_dir` - The Remmina profiles directory
+    /// # Returns
+    /// * `Option<PathBuf>` - Path of the first remmina.pref found
+    pub fn find(remmina_dir: &str) -> Option<PathBuf> {
+        let mut candidates = vec![Path::new(remmina_dir).join("remmina.pref")];
+        if let Ok(home) = std::env::var("HOME") {
+            candidates.push(Path::new(&home).join(".config/remmina/remmina.pref"));
+        }
+        candidates.into_iter().find(|path| path.is_file())
+    }
+
+    /// Load the encryption secret from a remmina.pref file
+    ///
+    /// # Arguments
+    /// * `path` - Path of remmina.pref
+    /// # Returns
+    /// * `Result<Self>` - The preferences, or an error if the file cannot be parsed or has no valid secret
+    pub fn load(path: &Path) -> Result<Self> {
+        let keyfile = RemminaKeyFile::load(path)?;
+        let encoded = keyfile.get_string(PREF_SECTION, "secret")
+            .ok_or_else(|| Error::InvalidRemminaFile {
+                path: path.to_path_buf(),
+                line: None,
+                message: format!("no secret in [{PREF_SECTION}]"),
+            })?;
+        Self::from_secret(&encoded)
+            .map_err(|e| Error::Secret(format!("Invalid secret in {}: {e}", path.display())))
+    }
+
+    /// Build the preferences from the base64 "secret" value
+    pub fn from_secret(encoded: &str) -> Result<Self> {
+        let secret = BASE64.decode(encoded.trim())
+            .map_err(|e| Error::Secret(format!("not base64 ({e})")))?;
+        if secret.len() < 32 {
+            return Err(Error::Secret(format!("expected 32 bytes, found {}", secret.len())));
+        }
+        Ok(RemminaPref { secret })
+    }
+
+    /// Decrypt a value stored by Remmina
+    ///
+    /// # Arguments
+    /// * `value` - The base64 encoded ciphertext
+    /// # Returns
+    /// * `Result<Secret>` - The plaintext, or a Secret error that never contains secret material
+    pub fn decrypt(&self, value: &str) -> Result<Secret> {
+        let fail = |message: &str| Error::Secret(message.to_string());
+        let mut buffer = BASE64.decode(value.trim())
+            .map_err(|_| fail("value is not base64"))?;
+        if buffer.is_empty() || buffer.len() % 8 != 0 {
+            return Err(fail("ciphertext length is not a multiple of the 3DES block size"));
+        }
+
+        let decryptor = TdesCbcDecryptor::new_from_slices(&self.secret[..24], &self.secret[24..32])
+            .map_err(|_| fail("invalid key length"))?;
+        let plaintext = decryptor.decrypt_padded_mut::<NoPadding>(&mut buffer)
+            .map_err(|_| fail("decryption failed"))?;
+
+        let end = plaintext.iter().position(|&b| b == 0).unwrap_or(plaintext.len());
+        String::from_utf8(plaintext[..end].to_vec())
+            .map(Secret::new)
+            .map_err(|_| fail("decrypted value is not valid UTF-8, wrong secret?"))
+    }
+
+    /// Decrypt the password, ssh_passphrase and ssh_tunnel_password of a .remmina file into its profile
+    ///
+    /// # Arguments
+    /// * `keyfile` - The parsed .remmina document the profile was built from
+    /// * `profile` - The profile receiving the secrets
+    /// * `summary` - Counters updated with the outcome of every stored value
+    /// # Behavior
+    /// * Values saved in the system keyring (".") cannot be recovered and are skipped
+    /// * Failures are reported with the key name and file path, never with the value
+    pub fn decrypt_profile(&self, keyfile: &RemminaKeyFile, profile: &mut RemminaProfile, summary: &mut DecryptSummary) {
+        let mut decrypt_key = |key: &str| -> Option<Secret> {
+            let value = keyfile.get_string(REMMINA_SECTION, key)?;
+            if value == KEYRING_MARKER {
+                summary.in_keyring += 1;
+                eprintln!("Note: {key} of {} is stored in the system keyring, skipped", profile.path.display());
+                return None;
+            }
+            match self.decrypt(&value) {
+                Ok(secret) => {
+                    summary.decrypted += 1;
+                    Some(secret)
+                }
+                Err(e) => {
+                    summary.failed += 1;
+                    eprintln!("Warning: cannot decrypt {key} of {}: {e}", profile.path.display());
+                    None
+                }
+            }
+        };
+
+        let password = decrypt_key("password");
+        let passphrase = decrypt_key("ssh_passphrase");
+        let tunnel_password = decrypt_key("ssh_tunnel_password");
+
+        profile.password = password;
+        profile.ssh_passphrase = passphrase;
+        if let Some(tunnel) = profile.tunnel.as_mut() {
+            tunnel.password = tunnel_password;
+        }
+    }
+}
@@ -1,12 +1,20 @@
 // use std::{path::PathBuf, u8};
 use std::path::PathBuf;
 
+use serde::Serialize;
+
+use crate::remmina_secrets::Secret;
+
+/// Key of the [remmina] section holding the source id of a profile, written on export so renamed files keep syncing
+pub const SOURCE_ID_KEY: &str = "tabby_source_id";
+
 /// Struct to hold a list of .remmina files
 pub struct RemminaFiles {
     pub files: Vec<PathBuf>,
 }
 
-#[derive(Debug, Clone)]
+/// The [remmina] section of a .remmina file, see `RemminaProfile::to_connection` for the neutral model
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct RemminaProfile {
     pub name: Option<String>,
     pub server: Option<String>,
@@ -16,7 +24,43 @@ pub struct RemminaProfile {
     pub user: Option<String>,
     pub auth: Option<String>,
 
+    // SSH options
+    pub private_key: Option<String>,
+    pub proxy_command: Option<String>,
+    pub compression: Option<bool>,
+    pub strict_host_key_check: Option<bool>,
+    pub charset: Option<String>,
+    /// Comma separated lists, as stored by Remmina (libssh format)
+    pub kex_algorithms: Option<String>,
+    pub ciphers: Option<String>,
+    pub host_key_types: Option<String>,
+    /// Command executed right after login
+    pub exec: Option<String>,
+    pub forward_x11: Option<bool>,
+    /// SSH tunnel (bastion) used to reach the server, only set when ssh_tunnel_enabled=1
+    pub tunnel: Option<SshTunnel>,
+
+    // Secrets, only set when decrypted with --with-secrets, never serialized
+    #[serde(skip)]
+    pub password: Option<Secret>,
+    #[serde(skip)]
+    pub ssh_passphrase: Option<Secret>,
+
     pub path: std::path::PathBuf,
+    /// Stable id of the profile used to sync it with Tabby: the `tabby_source_id` key if set, the file name otherwise
+    pub source_id: Option<String>,
+}
+
+/// SSH tunnel settings of a Remmina profile (ssh_tunnel_* keys)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SshTunnel {
+    pub server: String,
+    pub port: u16,
+    pub user: Option<String>,
+    pub auth: Option<String>,
+    pub private_key: Option<String>,
+    #[serde(skip)]
+    pub password: Option<Secret>,
 }
 
 
@@ -33,6 +77,7 @@ pub enum SshAuthMethod {
 }
 
 impl SshAuthMethod {
+    #[allow(clippy::should_implement_trait)] // infallible, unknown values are kept in Unknown
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "password" => SshAuthMethod::Password,
@@ -73,8 +118,10 @@ impl SshAuthMethod {
 /// * `u8` - The corresponding u8 value for the authentication method
 /// # Examples
 /// ```
+/// use remmina_to_tabby::remmina_types::{SshAuthMethod, get_auth_method_as_int};
+///
 /// let method = SshAuthMethod::Password;
-/// let method_u8 = get_auth_method(&method);
+/// let method_u8 = get_auth_method_as_int(&method);
 /// assert_eq!(method_u8, 0);
 /// ```
 pub fn get_auth_method_as_int(method: &SshAuthMethod) -> u8 {
@@ -103,8 +150,10 @@ pub fn get_auth_method_as_int(method: &SshAuthMethod) -> u8 {
 /// * `SshAuthMethod` - The corresponding SshAuthMethod enum variant
 /// # Examples
 /// ```
+/// use remmina_to_tabby::remmina_types::{SshAuthMethod, get_auth_method_from_int};
+///
 /// let method_u8 = 0;
-/// let method = get_auth_method_from_u8(method_u8);
+/// let method = get_auth_method_from_int(method_u8);
 /// assert_eq!(method, SshAuthMethod::Password);
 /// ```
 pub fn get_auth_method_from_int(method: u8) -> SshAuthMethod {
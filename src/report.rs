@@ -0,0 +1,374 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::connection::Connection;
+use crate::error::{Error, Result};
+use crate::remmina_secrets::DecryptSummary;
+use crate::remmina_types::RemminaProfile;
+use crate::tabby_parser::ConflictStrategy;
+
+/// Format of the report written to stdout at the end of a command
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable summary
+    #[default]
+    Text,
+    Json,
+    Yaml,
+}
+
+/// A .remmina file that was not turned into a profile
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    /// Error kind (see `Error::kind`), "filtered" when the file was left out by --protocol,
+    /// or "exists" when an export did not overwrite it
+    pub kind: String,
+    pub reason: String,
+}
+
+impl SkippedFile {
+    pub fn from_error(path: &Path, error: &Error) -> Self {
+        SkippedFile { path: path.to_path_buf(), kind: error.kind().to_string(), reason: error.to_string() }
+    }
+}
+
+/// Result of the protocol check of a .remmina file
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckedFile {
+    pub path: PathBuf,
+    pub protocol: Option<String>,
+    /// "available", "not implemented", "not recognized", "missing" or "unreadable"
+    pub status: String,
+}
+
+/// A group created in the Tabby config
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedGroup {
+    pub id: String,
+    pub name: String,
+}
+
+/// A profile created in the Tabby config
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedProfile {
+    pub id: String,
+    pub name: String,
+    pub r#type: String,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+    /// Group id
+    pub group: Option<String>,
+    /// Jump host profile id
+    pub jump_host: Option<String>,
+}
+
+/// A previously imported profile checked by a sync
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncedProfile {
+    pub id: String,
+    pub name: String,
+    /// Names of the updated fields, empty if the profile was up to date
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changed: Vec<String>,
+}
+
+/// How a conflict with an existing profile was resolved
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictDecision {
+    /// Name of the Remmina profile
+    pub name: String,
+    /// Name and id of the existing Tabby profile
+    pub existing: String,
+    pub existing_id: String,
+    /// "name" or "host, port and user"
+    pub matched_by: String,
+    pub decision: ConflictStrategy,
+    /// New name of the imported profile, for the rename strategy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_to: Option<String>,
+}
+
+/// A profile field changed by a rule of the `--rules` file
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleChange {
+    pub rule: String,
+    /// Name of the profile before the rules
+    pub profile: String,
+    pub field: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// A previously imported profile removed because its Remmina source no longer exists
+#[derive(Debug, Clone, Serialize)]
+pub struct RemovedProfile {
+    pub id: String,
+    pub name: String,
+    /// Source id of the vanished Remmina profile
+    pub source: String,
+}
+
+/// A Tabby profile default set by an import (`profileDefaults`)
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileDefault {
+    /// Path of the setting, e.g. "ssh.options.user"
+    pub key: String,
+    /// The value, as YAML
+    pub value: String,
+}
+
+/// A Remmina profile that was not imported
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedProfile {
+    pub name: String,
+    pub path: PathBuf,
+    /// Error kind, see `Error::kind`
+    pub kind: String,
+    pub reason: String,
+}
+
+impl SkippedProfile {
+    pub fn from_error(profile: &Connection, error: &Error) -> Self {
+        SkippedProfile {
+            name: profile.name.clone().unwrap_or_default(),
+            path: profile.path.clone(),
+            kind: error.kind().to_string(),
+            reason: error.to_string(),
+        }
+    }
+}
+
+/// Changes made to the Tabby config by an import
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Plan {
+    /// Connections read from the source, secrets are never serialized
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<Connection>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_files: Vec<SkippedFile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<DecryptSummary>,
+    pub created_groups: Vec<PlannedGroup>,
+    pub created_profiles: Vec<PlannedProfile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub updated_profiles: Vec<SyncedProfile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unchanged_profiles: Vec<SyncedProfile>,
+    pub skipped_profiles: Vec<SkippedProfile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_profiles: Vec<RemovedProfile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_groups: Vec<PlannedGroup>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<ConflictDecision>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rule_changes: Vec<RuleChange>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub profile_defaults: Vec<ProfileDefault>,
+}
+
+/// Methods for Plan
+impl Plan {
+    /// Number of groups and profiles created, updated or removed, and of profile defaults set
+    pub fn changes(&self) -> usize {
+        self.created_groups.len() + self.created_profiles.len() + self.updated_profiles.len()
+            + self.removed_profiles.len() + self.removed_groups.len() + self.profile_defaults.len()
+    }
+}
+
+/// A .remmina file written (or that would be written) by an export
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedFile {
+    pub name: String,
+    pub path: PathBuf,
+    pub written: bool,
+}
+
+/// Structured result of a command
+///
+/// Notes:
+///     Progress logs are written to stderr while the command runs,
+///     the report is the only thing written to stdout so scripts can parse it with `--output json|yaml`
+#[derive(Debug, Default, Serialize)]
+pub struct RunReport {
+    pub command: String,
+    /// False when nothing was written to disk
+    pub execute: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub checked_files: Vec<CheckedFile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<RemminaProfile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_files: Vec<SkippedFile>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<DecryptSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<Plan>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exported_files: Vec<ExportedFile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+    /// Path of the Tabby config that was saved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saved_to: Option<PathBuf>,
+}
+
+/// Methods for RunReport
+impl RunReport {
+    pub fn new(command: &str, execute: bool) -> Self {
+        RunReport { command: command.to_string(), execute, ..Default::default() }
+    }
+
+    /// Write the report to stdout
+    ///
+    /// # Arguments
+    /// * `format` - Text for a human readable summary, JSON or YAML for a single document
+    /// # Returns
+    /// * `Result<()>` - An error if the report cannot be serialized
+    pub fn print(&self, format: OutputFormat) -> Result<()> {
+        match format {
+            OutputFormat::Text => self.print_text(),
+            other => print_structured(self, other)?,
+        }
+        Ok(())
+    }
+
+    fn print_text(&self) {
+        for file in &self.checked_files {
+            println!("{}: {} [{}]", file.path.display(), file.protocol.as_deref().unwrap_or("-"), file.status);
+        }
+
+        for profile in &self.profiles {
+            println!(
+                "{} ({}@{}:{}) group={}",
+                profile.name.as_deref().unwrap_or("<none>"),
+                profile.user.as_deref().unwrap_or(""),
+                profile.server.as_deref().unwrap_or(""),
+                profile.port.as_deref().unwrap_or("-"),
+                profile.group.as_deref().unwrap_or("<none>")
+            );
+        }
+
+        let plan_skipped_files = self.plan.iter().flat_map(|plan| &plan.skipped_files);
+        let skipped_files: Vec<&SkippedFile> = self.skipped_files.iter().chain(plan_skipped_files).collect();
+        if !skipped_files.is_empty() {
+            println!("Skipped files:");
+            for file in skipped_files {
+                println!("  - {}: {}", file.path.display(), file.reason);
+            }
+        }
+
+        if let Some(plan) = &self.plan {
+            if plan.changes() == 0 {
+                println!("No changes.");
+            } else {
+                println!("{}:", if self.execute { "Changes" } else { "Pending changes" });
+            }
+            for change in &plan.rule_changes {
+                println!(
+                    "  * profile '{}' {}: '{}' -> '{}' (rule '{}')",
+                    change.profile,
+                    change.field,
+                    change.from.as_deref().unwrap_or_default(),
+                    change.to.as_deref().unwrap_or_default(),
+                    change.rule
+                );
+            }
+            for default in &plan.profile_defaults {
+                println!("  + default {}: {}", default.key, default.value);
+            }
+            for group in &plan.created_groups {
+                println!("  + group   '{}'", group.name);
+            }
+            for profile in &plan.created_profiles {
+                println!(
+                    "  + profile '{}' ({}@{}:{})",
+                    profile.name,
+                    profile.user.as_deref().unwrap_or(""),
+                    profile.host.as_deref().unwrap_or(""),
+                    profile.port.unwrap_or(22)
+                );
+            }
+            for profile in &plan.updated_profiles {
+                println!("  ~ profile '{}' ({})", profile.name, profile.changed.join(", "));
+            }
+            for profile in &plan.removed_profiles {
+                println!("  - profile '{}' (source {} not found)", profile.name, profile.source);
+            }
+            for group in &plan.removed_groups {
+                println!("  - group   '{}'", group.name);
+            }
+            for profile in &plan.skipped_profiles {
+                println!("  = profile '{}' skipped: {}", profile.name, profile.reason);
+            }
+            for conflict in &plan.conflicts {
+                print!("  ! profile '{}' conflicts with '{}' (same {}): {}", conflict.name, conflict.existing, conflict.matched_by, conflict.decision.as_str());
+                match &conflict.renamed_to {
+                    Some(name) => println!(" as '{name}'"),
+                    None => println!(),
+                }
+            }
+            println!(
+                "{} created, {} updated, {} unchanged, {} removed, {} skipped",
+                plan.created_profiles.len(),
+                plan.updated_profiles.len(),
+                plan.unchanged_profiles.len(),
+                plan.removed_profiles.len(),
+                plan.skipped_profiles.len()
+            );
+        }
+
+        for file in &self.exported_files {
+            let action = if file.written { "exported" } else { "would export" };
+            println!("  {action} '{}' to {}", file.name, file.path.display());
+        }
+
+        for problem in &self.problems {
+            println!(" ⚠️  {problem}");
+        }
+
+        if let Some(path) = &self.saved_to {
+            println!("Tabby config saved to {}", path.display());
+        }
+    }
+}
+
+/// Fatal error, written to stdout instead of the report with `--output json|yaml`
+#[derive(Debug, Serialize)]
+struct ErrorReport {
+    error: ErrorDetails,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorDetails {
+    kind: &'static str,
+    message: String,
+    exit_code: u8,
+}
+
+/// Write a fatal error to stdout, only for structured output formats (text errors are already on stderr)
+pub fn print_error(error: &Error, format: OutputFormat) -> Result<()> {
+    if format == OutputFormat::Text {
+        return Ok(());
+    }
+    let report = ErrorReport {
+        error: ErrorDetails { kind: error.kind(), message: error.to_string(), exit_code: error.exit_code() },
+    };
+    print_structured(&report, format)
+}
+
+fn print_structured(value: &impl Serialize, format: OutputFormat) -> Result<()> {
+    let output = match format {
+        OutputFormat::Json => serde_json::to_string_pretty(value)
+            .map(|json| json + "\n")
+            .map_err(io::Error::other),
+        _ => serde_yaml_ng::to_string(value).map_err(io::Error::other),
+    };
+    print!("{}", output.map_err(|e| Error::io("<stdout>", e))?);
+    Ok(())
+}
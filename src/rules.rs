@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use glob::{MatchOptions, Pattern as GlobPattern};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::connection::{AuthMethod, Connection, check_template};
+use crate::error::{Error, Result};
+use crate::report::{RuleChange, SkippedProfile};
+
+/// Rules transforming the connections before their import, loaded from a YAML or TOML file
+///
+/// # Example
+/// ```yaml
+/// rules:
+///   - name: new domain
+///     match: { host: "*.corp.old" }
+///     replace: { host: { pattern: '\.corp\.old$', with: ".corp.new" } }
+///   - match: { group: "Prod*" }
+///     set: { user: deploy }
+///     color: "#d9534f"
+///   - match: { name: 'regex:^tmp-' }
+///     drop: true
+/// ```
+///
+/// Notes:
+///     Every rule matching a profile is applied, in file order, on the profile as left by the previous rules.
+///     A dropped profile is not imported and no later rule sees it
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleSet {
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    /// Shown in the report, defaults to "#<position>"
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Every condition must match, a rule without conditions matches every profile
+    #[serde(default, rename = "match")]
+    pub conditions: Conditions,
+    /// Do not import the profile
+    #[serde(default)]
+    pub drop: bool,
+    /// Field values to set
+    #[serde(default)]
+    pub set: BTreeMap<Field, String>,
+    /// Regex replacements in field values (`$1` refers to a capture group)
+    #[serde(default)]
+    pub replace: BTreeMap<Field, Replacement>,
+    /// New profile name, a template such as "{name} ({server})", see `TEMPLATE_PLACEHOLDERS`
+    #[serde(default)]
+    pub rename: Option<String>,
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Tabby profile color, e.g. "#d9534f"
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Tabby profile icon, e.g. "fas fa-database"
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+/// Conditions of a rule, glob patterns ("*.corp.old", case-insensitive) or regexes prefixed with "regex:"
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Conditions {
+    pub name: Option<Pattern>,
+    pub host: Option<Pattern>,
+    pub group: Option<Pattern>,
+    pub protocol: Option<Pattern>,
+}
+
+/// Profile fields rules can change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Name,
+    Host,
+    Port,
+    User,
+    Group,
+    Auth,
+    PrivateKey,
+    ProxyCommand,
+    Exec,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Replacement {
+    #[serde(with = "regex_string")]
+    pub pattern: Regex,
+    pub with: String,
+}
+
+/// A glob or regex condition
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Pattern {
+    Glob(GlobPattern),
+    Regex(Regex),
+}
+
+impl TryFrom<String> for Pattern {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        match value.strip_prefix("regex:") {
+            Some(regex) => Regex::new(regex).map(Pattern::Regex).map_err(|e| e.to_string()),
+            None => GlobPattern::new(&value).map(Pattern::Glob).map_err(|e| format!("invalid glob '{value}': {e}")),
+        }
+    }
+}
+
+impl Pattern {
+    pub fn is_match(&self, value: &str) -> bool {
+        match self {
+            Pattern::Glob(glob) => glob.matches_with(value, MatchOptions { case_sensitive: false, ..Default::default() }),
+            Pattern::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+mod regex_string {
+    use regex::Regex;
+    use serde::{Deserialize, Deserializer, de::Error};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern).map_err(D::Error::custom)
+    }
+}
+
+impl Field {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Field::Name => "name",
+            Field::Host => "host",
+            Field::Port => "port",
+            Field::User => "user",
+            Field::Group => "group",
+            Field::Auth => "auth",
+            Field::PrivateKey => "private_key",
+            Field::ProxyCommand => "proxy_command",
+            Field::Exec => "exec",
+        }
+    }
+
+    /// Value of the field as text, the auth method by its `AuthMethod::as_str` name
+    fn get(&self, profile: &Connection) -> Option<String> {
+        match self {
+            Field::Name => profile.name.clone(),
+            Field::Host => profile.host.clone(),
+            Field::Port => profile.port.map(|port| port.to_string()),
+            Field::User => profile.user.clone(),
+            Field::Group => profile.group.clone(),
+            Field::Auth => profile.auth.map(|auth| auth.as_str().to_string()),
+            Field::PrivateKey => profile.private_key.clone(),
+            Field::ProxyCommand => profile.proxy_command.clone(),
+            Field::Exec => profile.remote_command.clone(),
+        }
+    }
+
+    /// Set the field from text
+    ///
+    /// # Returns
+    /// * `Result<(), String>` - An error message if the value is no port number or no auth method
+    ///   (see `AuthMethod::from_name`), the field is left unchanged then
+    fn set(&self, profile: &mut Connection, value: &str) -> std::result::Result<(), String> {
+        let text = Some(value.to_string());
+        match self {
+            Field::Name => profile.name = text,
+            Field::Host => profile.host = text,
+            Field::Port => profile.port = Some(value.trim().parse().map_err(|_| format!("invalid port '{value}'"))?),
+            Field::User => profile.user = text,
+            Field::Group => profile.group = text,
+            Field::Auth => profile.auth = Some(AuthMethod::from_name(value).ok_or_else(|| format!("unknown auth method '{value}'"))?),
+            Field::PrivateKey => profile.private_key = text,
+            Field::ProxyCommand => profile.proxy_command = text,
+            Field::Exec => profile.remote_command = text,
+        }
+        Ok(())
+    }
+}
+
+/// Methods for RuleSet
+impl RuleSet {
+    /// Load rules from a file, TOML if its extension is .toml, YAML otherwise
+    ///
+    /// # Returns
+    /// * `Result<RuleSet>` - Io if the file cannot be read, InvalidRules if it cannot be parsed
+    ///   (unknown key or placeholder, invalid glob or regex)
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        let invalid = |message: String| Error::InvalidRules { path: path.to_path_buf(), message };
+        let rules: RuleSet = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("toml")) {
+            toml::from_str(&content).map_err(|e| invalid(e.to_string()))?
+        } else {
+            serde_yaml_ng::from_str(&content).map_err(|e| invalid(e.to_string()))?
+        };
+        for template in rules.rules.iter().filter_map(|rule| rule.rename.as_deref()) {
+            check_template(template).map_err(invalid)?;
+        }
+        for (field, value) in rules.rules.iter().flat_map(|rule| &rule.set) {
+            field.set(&mut Connection::default(), value).map_err(|e| invalid(format!("{}: {e}", field.as_str())))?;
+        }
+        Ok(rules)
+    }
+
+    /// Apply the rules to the profiles
+    ///
+    /// # Arguments
+    /// * `profiles` - The connections about to be imported
+    /// * `changes` - Receives every field changed by a rule
+    /// * `dropped` - Receives the dropped profiles (kind "dropped")
+    /// # Returns
+    /// * `Vec<Connection>` - The connections left, transformed
+    pub fn apply(&self, profiles: Vec<Connection>, changes: &mut Vec<RuleChange>, dropped: &mut Vec<SkippedProfile>) -> Vec<Connection> {
+        let mut kept = Vec::new();
+        'profiles: for mut profile in profiles {
+            for (position, rule) in self.rules.iter().enumerate() {
+                if !rule.matches(&profile) {
+                    continue;
+                }
+                let rule_name = rule.name.clone().unwrap_or_else(|| format!("#{}", position + 1));
+                if rule.drop {
+                    eprintln!(" └── Profile '{}' dropped by rule '{rule_name}'", profile.name.as_deref().unwrap_or_default());
+                    dropped.push(SkippedProfile {
+                        name: profile.name.clone().unwrap_or_default(),
+                        path: profile.path.clone(),
+                        kind: "dropped".to_string(),
+                        reason: format!("dropped by rule '{rule_name}'"),
+                    });
+                    continue 'profiles;
+                }
+                rule.apply(&mut profile, &rule_name, changes);
+            }
+            kept.push(profile);
+        }
+        kept
+    }
+}
+
+/// Methods for Rule
+impl Rule {
+    fn matches(&self, profile: &Connection) -> bool {
+        let protocol = profile.protocol.as_str().to_uppercase();
+        let conditions = [
+            (&self.conditions.name, profile.name.as_deref()),
+            (&self.conditions.host, profile.host.as_deref()),
+            (&self.conditions.group, profile.group.as_deref()),
+            (&self.conditions.protocol, Some(protocol.as_str())),
+        ];
+        conditions.iter().all(|(pattern, value)| match pattern {
+            Some(pattern) => pattern.is_match(value.unwrap_or_default()),
+            None => true,
+        })
+    }
+
+    fn apply(&self, profile: &mut Connection, rule_name: &str, changes: &mut Vec<RuleChange>) {
+        let profile_name = profile.name.clone().unwrap_or_default();
+        let mut record = |field: &str, from: Option<String>, to: Option<String>| {
+            if from != to {
+                changes.push(RuleChange { rule: rule_name.to_string(), profile: profile_name.clone(), field: field.to_string(), from, to });
+            }
+        };
+
+        // Values are checked by `RuleSet::load`, only a replacement can give an invalid port or auth method
+        for (field, value) in &self.set {
+            let from = field.get(profile);
+            if field.set(profile, value).is_ok() {
+                record(field.as_str(), from, field.get(profile));
+            }
+        }
+        for (field, replacement) in &self.replace {
+            if let Some(current) = field.get(profile) {
+                let replaced = replacement.pattern.replace_all(&current, replacement.with.as_str()).into_owned();
+                match field.set(profile, &replaced) {
+                    Ok(()) => record(field.as_str(), Some(current), field.get(profile)),
+                    Err(e) => eprintln!(" └── Warning: rule '{rule_name}' ignored for {}: {e}", field.as_str()),
+                }
+            }
+        }
+        if let Some(template) = &self.rename {
+            let name = profile.render_name(template);
+            let from = profile.name.replace(name.clone());
+            record("name", from, Some(name));
+        }
+        if let Some(group) = &self.group {
+            let from = profile.group.replace(group.clone());
+            record("group", from, Some(group.clone()));
+        }
+        if let Some(color) = &self.color {
+            let from = profile.color.replace(color.clone());
+            record("color", from, Some(color.clone()));
+        }
+        if let Some(icon) = &self.icon {
+            let from = profile.icon.replace(icon.clone());
+            record("icon", from, Some(icon.clone()));
+        }
+    }
+}
@@ -14,5 +14,6 @@ TAB_DIR="$2"
 
 
 # cargo run -- --remmina-dir "$REM_DIR" --tabby-dir "$TAB_DIR" --protocol ssh --remmina-check --execute --yes
-cargo run -- --remmina-dir "$REM_DIR" --tabby-dir "$TAB_DIR" --protocol ssh,rdp --remmina-check --execute
-#cargo run -- --remmina-dir "$REM_DIR" --tabby-dir "$TAB_DIR" --protocol ssh --remmina-check
\ No newline at end of file
+cargo run -- check --remmina-dir "$REM_DIR"
+cargo run -- import --remmina-dir "$REM_DIR" --tabby-dir "$TAB_DIR" --protocol ssh,rdp --execute
+#cargo run -- diff --remmina-dir "$REM_DIR" --tabby-dir "$TAB_DIR" --protocol ssh
\ No newline at end of file
@@ -0,0 +1,304 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+
+use crate::connection::Connection;
+use crate::error::{Error, Result};
+use crate::protocols_types::ConnectionProtocols;
+use crate::report::{ExportedFile, RunReport};
+
+/// First line of the region of an ssh_config file managed by the exporter
+pub const BEGIN_MARKER: &str = "# BEGIN remmina-to-tabby managed hosts";
+/// Last line of the managed region
+pub const END_MARKER: &str = "# END remmina-to-tabby managed hosts";
+
+/// How the profile groups appear in the exported ssh_config
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum GroupLayout {
+    /// One file, the hosts of a group follow a "# Group: name" comment
+    #[default]
+    Comments,
+    /// One file per group in "<config>.d/", included from the main file
+    Include,
+}
+
+/// Options of the ssh_config export
+#[derive(Debug, Clone, Default)]
+pub struct SshConfigExport {
+    pub layout: GroupLayout,
+    /// Only replace the region between `BEGIN_MARKER` and `END_MARKER` of the existing file,
+    /// the rest of the file is kept (the region is appended if the file has none)
+    pub managed: bool,
+}
+
+/// A file produced by the export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshConfigFile {
+    pub path: PathBuf,
+    /// Group of the hosts of the file, None for the main file
+    pub group: Option<String>,
+    pub content: String,
+    /// Number of Host blocks in the file
+    pub hosts: usize,
+}
+
+/// Methods for SshConfigExport
+impl SshConfigExport {
+    /// Builds the ssh_config files of the SSH profiles
+    ///
+    /// # Arguments
+    /// * `profiles` - Connections, read from .remmina files or from Tabby (`TabbyConfig::connections`)
+    /// * `config_path` - Path of the main ssh_config file, e.g. ~/.ssh/config
+    /// * `existing` - Current content of the main file, only used with `managed`
+    /// # Returns
+    /// * `Result<Vec<SshConfigFile>>` - The main file first, then one file per group with `GroupLayout::Include`,
+    ///   or a Conflict error if the existing file has a begin marker without end marker (or the reverse)
+    /// # Behavior
+    /// * Profiles of other protocols are left out
+    /// * Host aliases are the profile names with whitespace and pattern characters replaced by '-',
+    ///   made unique with a counter ("web-2")
+    /// * HostName, Port, User, IdentityFile, ProxyJump (jump host) and ProxyCommand are written when set
+    pub fn files(&self, profiles: &[Connection], config_path: &Path, existing: Option<&str>) -> Result<Vec<SshConfigFile>> {
+        let mut by_group: BTreeMap<Option<String>, Vec<&Connection>> = BTreeMap::new();
+        for profile in profiles {
+            if profile.protocol != ConnectionProtocols::Ssh {
+                eprintln!(" └── Profile '{}' is not an SSH profile, skipped.", profile.name.as_deref().unwrap_or_default());
+                continue;
+            }
+            by_group.entry(profile.group.clone().filter(|g| !g.trim().is_empty())).or_default().push(profile);
+        }
+
+        let mut aliases = BTreeSet::new();
+        let mut blocks: BTreeMap<Option<String>, Vec<String>> = BTreeMap::new();
+        for (group, profiles) in &by_group {
+            for profile in profiles {
+                let alias = unique_alias(profile, &mut aliases);
+                blocks.entry(group.clone()).or_default().push(host_block(profile, &alias));
+            }
+        }
+
+        let mut files = Vec::new();
+        let mut main = String::new();
+        let mut main_hosts = 0;
+        match self.layout {
+            GroupLayout::Comments => {
+                for (group, blocks) in &blocks {
+                    if let Some(group) = group {
+                        main.push_str(&format!("# Group: {group}\n\n"));
+                    }
+                    main.push_str(&blocks.join("\n"));
+                    main.push('\n');
+                    main_hosts += blocks.len();
+                }
+            }
+            GroupLayout::Include => {
+                let include_dir = include_dir(config_path);
+                let mut includes = String::new();
+                let mut slugs = BTreeSet::new();
+                for (group, blocks) in &blocks {
+                    let Some(group) = group else { continue };
+                    let base = slug(group);
+                    let mut file_stem = base.clone();
+                    let mut counter = 2;
+                    while !slugs.insert(file_stem.clone()) {
+                        file_stem = format!("{base}-{counter}");
+                        counter += 1;
+                    }
+                    let path = include_dir.join(format!("{file_stem}.conf"));
+                    includes.push_str(&format!("Include {}\n", quote(&path.display().to_string())));
+                    files.push(SshConfigFile {
+                        path,
+                        group: Some(group.clone()),
+                        content: format!("# Group: {group}\n\n{}\n", blocks.join("\n")),
+                        hosts: blocks.len(),
+                    });
+                }
+                // Include lines inside a Host block only apply to that host, they must come first
+                if !includes.is_empty() {
+                    main.push_str(&includes);
+                    main.push('\n');
+                }
+                if let Some(blocks) = blocks.get(&None) {
+                    main.push_str(&blocks.join("\n"));
+                    main.push('\n');
+                    main_hosts += blocks.len();
+                }
+            }
+        }
+
+        let content = match (self.managed, existing) {
+            (true, Some(existing)) => replace_managed_region(existing, &main, self.layout).map_err(|reason| Error::Conflict {
+                name: config_path.display().to_string(),
+                reason,
+            })?,
+            (true, None) => managed_region(&main, false, self.layout),
+            (false, _) => main,
+        };
+        files.insert(0, SshConfigFile { path: config_path.to_path_buf(), group: None, content, hosts: main_hosts });
+        Ok(files)
+    }
+
+    /// Writes the files built by `files`
+    ///
+    /// # Arguments
+    /// * `files` - The files to write, the main file first
+    /// * `execute` - If false, only print what would be written (dry-run)
+    /// * `report` - Receives the exported files
+    /// # Returns
+    /// * `Result<()>` - The first I/O error
+    /// # Behavior
+    /// * Missing directories are created
+    /// * Without `managed` the main file is replaced, after a copy to "<file>.bak"
+    pub fn write(&self, files: &[SshConfigFile], execute: bool, report: &mut RunReport) -> Result<()> {
+        for file in files {
+            let name = match &file.group {
+                Some(group) => format!("{group} ({} hosts)", file.hosts),
+                None => format!("ssh_config ({} hosts)", file.hosts),
+            };
+            if execute {
+                if let Some(dir) = file.path.parent() {
+                    fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+                }
+                // The whole file is replaced without --managed, keep a copy of it
+                if file.group.is_none() && !self.managed && file.path.exists() {
+                    let mut backup = file.path.clone().into_os_string();
+                    backup.push(".bak");
+                    fs::copy(&file.path, &backup).map_err(|e| Error::io(&file.path, e))?;
+                    eprintln!("\nBackup of {} created: {}\n", file.path.display(), PathBuf::from(&backup).display());
+                }
+                fs::write(&file.path, &file.content).map_err(|e| Error::io(&file.path, e))?;
+                eprintln!(" ⬅️  Wrote {name} to {} ✅", file.path.display());
+            } else {
+                eprintln!("Dry-run: would write {name} to {}:\n{}", file.path.display(), file.content);
+            }
+            report.exported_files.push(ExportedFile { name, path: file.path.clone(), written: execute });
+        }
+        Ok(())
+    }
+}
+
+/// Host block of a profile
+///
+/// # Returns
+/// * `String` - "Host alias" followed by its indented options, a comment with the profile name if it differs from the alias
+pub fn host_block(profile: &Connection, alias: &str) -> String {
+    let mut block = String::new();
+    let name = profile.name.as_deref().unwrap_or_default();
+    if !name.is_empty() && name != alias {
+        block.push_str(&format!("# {name}\n"));
+    }
+    block.push_str(&format!("Host {alias}\n"));
+
+    let mut option = |key: &str, value: Option<String>| {
+        if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+            block.push_str(&format!("    {key} {value}\n"));
+        }
+    };
+    option("HostName", profile.host.clone());
+    option("Port", profile.port.map(|port| port.to_string()));
+    option("User", profile.user.clone().map(|u| quote(&u)));
+    option("IdentityFile", profile.private_key.clone().map(|k| quote(&k)));
+    option("ProxyJump", profile.jump_host.as_ref().map(|jump| {
+        let host = if jump.host.contains(':') { format!("[{}]", jump.host) } else { jump.host.clone() };
+        let user = jump.user.as_deref().map(|u| format!("{u}@")).unwrap_or_default();
+        match jump.port {
+            22 => format!("{user}{host}"),
+            port => format!("{user}{host}:{port}"),
+        }
+    }));
+    // ProxyCommand takes the rest of the line, it is not quoted
+    option("ProxyCommand", profile.proxy_command.clone());
+    block
+}
+
+/// Replaces the managed region of an existing ssh_config, or appends one
+///
+/// # Returns
+/// * `Result<String, String>` - The updated content, or the reason why the markers cannot be used
+pub fn replace_managed_region(existing: &str, hosts: &str, layout: GroupLayout) -> std::result::Result<String, String> {
+    let lines: Vec<&str> = existing.lines().collect();
+    let begin = lines.iter().position(|l| l.trim() == BEGIN_MARKER);
+    let end = lines.iter().position(|l| l.trim() == END_MARKER);
+    match (begin, end) {
+        (Some(begin), Some(end)) if begin < end => {
+            let before = lines[..begin].join("\n");
+            let after = lines[end + 1..].join("\n");
+            let mut content = String::new();
+            if !before.is_empty() {
+                content.push_str(&before);
+                content.push('\n');
+            }
+            content.push_str(&managed_region(hosts, !before.trim().is_empty(), layout));
+            if !after.is_empty() {
+                content.push_str(&after);
+                content.push('\n');
+            }
+            Ok(content)
+        }
+        (None, None) => {
+            let mut content = existing.to_string();
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            if !content.trim().is_empty() {
+                content.push('\n');
+            }
+            content.push_str(&managed_region(hosts, !existing.trim().is_empty(), layout));
+            Ok(content)
+        }
+        (Some(_), None) => Err(format!("'{BEGIN_MARKER}' without '{END_MARKER}'")),
+        _ => Err(format!("'{END_MARKER}' without '{BEGIN_MARKER}' before it")),
+    }
+}
+
+/// The hosts between the markers
+///
+/// Notes:
+///     After other Host blocks, "Match all" makes the Include lines of the region unconditional again
+fn managed_region(hosts: &str, after_content: bool, layout: GroupLayout) -> String {
+    let reset = if after_content && layout == GroupLayout::Include { "Match all\n" } else { "" };
+    format!("{BEGIN_MARKER}\n{reset}{hosts}{END_MARKER}\n")
+}
+
+/// Directory of the per-group files: "<config>.d" next to the main file
+fn include_dir(config_path: &Path) -> PathBuf {
+    let mut name = config_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".d");
+    config_path.with_file_name(name)
+}
+
+/// Host alias of a profile that no other exported profile uses
+fn unique_alias(profile: &Connection, taken: &mut BTreeSet<String>) -> String {
+    let name = profile.name.clone().filter(|n| !n.trim().is_empty()).or_else(|| profile.host.clone()).unwrap_or_else(|| "host".to_string());
+    let base: String = name.trim()
+        .chars()
+        .map(|c| if c.is_whitespace() || "*?!,#\"".contains(c) { '-' } else { c })
+        .collect();
+    let mut alias = base.clone();
+    let mut counter = 2;
+    while !taken.insert(alias.clone()) {
+        alias = format!("{base}-{counter}");
+        counter += 1;
+    }
+    alias
+}
+
+/// File name of a group, lowercased with anything but letters, digits, '-' and '_' replaced by '-'
+fn slug(group: &str) -> String {
+    group.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect()
+}
+
+/// Quotes values containing whitespace, as ssh_config splits arguments on it
+fn quote(value: &str) -> String {
+    if value.contains(char::is_whitespace) {
+        format!("\"{value}\"")
+    } else {
+        value.to_string()
+    }
+}
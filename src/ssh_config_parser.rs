@@ -0,0 +1,570 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::connection::{AuthMethod, Connection, JumpHost, split_list};
+use crate::error::{Error, Result};
+use crate::protocols_types::ConnectionProtocols;
+
+/// Scheme of the source ids of the profiles read from ssh_config ("ssh_config:alias"), see `source_scheme`
+pub const SSH_CONFIG_SCHEME: &str = "ssh_config";
+
+/// Group of the jump hosts created for ProxyJump chains, the one used by `TabbyConfig::import_profiles`
+const JUMP_HOSTS_GROUP: &str = "Jump Hosts";
+
+/// Includes nested deeper than this are ignored, as ssh does
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Options of ssh taking a value, see ssh(1)
+const SSH_VALUE_FLAGS: &str = "BbcDEeFIiJLlmOopQRSWw";
+
+/// Hosts of an OpenSSH client configuration, read as SSH connections
+#[derive(Debug, Default)]
+pub struct SshConfig {
+    /// One profile per concrete host alias (no wildcard, no negation), jump hosts before the hosts using them
+    pub profiles: Vec<Connection>,
+    /// Options of the blocks matching every host (lines before the first Host, "Host *", "Match all"),
+    /// to be used as Tabby SSH profile defaults, see `TabbyConfig::set_ssh_defaults`
+    pub defaults: Connection,
+    /// Every file read, the main file first
+    pub files: Vec<PathBuf>,
+}
+
+/// A Host or Match block, with the options that follow it
+#[derive(Debug)]
+struct Block {
+    condition: Condition,
+    /// Keyword (lowercase) and arguments, in file order
+    options: Vec<(String, Vec<String>)>,
+    /// Set by a "# Group: name" comment, as written by the ssh_config exporter
+    group: Option<String>,
+    path: PathBuf,
+}
+
+#[derive(Debug)]
+enum Condition {
+    /// Lines before the first Host or Match
+    Global,
+    Host(Vec<String>),
+    Match(Vec<Criterion>),
+    /// A Match block depending on the run time (exec, localuser, ...), never applied
+    Unresolvable,
+}
+
+#[derive(Debug)]
+enum Criterion {
+    All,
+    /// host (HostName once resolved) or originalhost (the alias) against a comma separated pattern list
+    Host { patterns: Vec<String>, original: bool, negated: bool },
+}
+
+/// Methods for SshConfig
+impl SshConfig {
+    /// Read an ssh_config file and the files it includes
+    ///
+    /// # Arguments
+    /// * `path` - The main file, e.g. ~/.ssh/config; relative Include paths are resolved from its directory
+    /// # Returns
+    /// * `Result<SshConfig>` - The host profiles and defaults, Io if the main file cannot be read
+    /// # Behavior
+    /// * Options are resolved like ssh does: for each alias, the first value of every keyword among the
+    ///   matching blocks, in file order
+    /// * Host patterns support `*`, `?` and `!` negation; Match supports all, host, originalhost and `!`,
+    ///   other criteria (exec, user, localuser, ...) cannot be resolved without connecting and their block is ignored
+    /// * A ProxyJump chain "a,b" gives the host the jump host b, and b the jump host a
+    ///   (b is created in the "Jump Hosts" group if it is not a host of the file)
+    /// * Included files that cannot be read are reported on stderr and skipped
+    ///
+    /// Notes:
+    ///     Options of the blocks matching every host only go to `defaults`, so a leading "Host *" does not win
+    ///     over the options of the hosts as it would with ssh: in Tabby, profile options win over defaults
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        let base_dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        let mut config = SshConfig::default();
+        let mut blocks = vec![Block { condition: Condition::Global, options: Vec::new(), group: None, path: path.to_path_buf() }];
+        config.read(&content, path, &base_dir, 0, &mut blocks, 0);
+        config.resolve(&blocks);
+        Ok(config)
+    }
+
+    /// Parse the lines of a file into blocks, following Include
+    ///
+    /// Notes:
+    ///     `current` is the block the lines before the first Host or Match belong to; the lines after an Include
+    ///     still belong to the block it is in, not to the last block of the included file
+    fn read(&mut self, content: &str, path: &Path, base_dir: &Path, depth: usize, blocks: &mut Vec<Block>, mut current: usize) {
+        self.files.push(path.to_path_buf());
+        let mut group = None;
+        for line in content.lines() {
+            let line = line.trim();
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some(name) = comment.trim().strip_prefix("Group:") {
+                    group = Some(name.trim().to_string()).filter(|g| !g.is_empty());
+                }
+                continue;
+            }
+            let Some((keyword, args)) = split_line(line) else { continue };
+            match keyword.as_str() {
+                "host" => {
+                    blocks.push(Block { condition: Condition::Host(args), options: Vec::new(), group: group.clone(), path: path.to_path_buf() });
+                    current = blocks.len() - 1;
+                }
+                "match" => {
+                    let condition = parse_match(&args).unwrap_or_else(|| {
+                        eprintln!(" └── Match {} in {} cannot be resolved statically, ignored.", args.join(" "), path.display());
+                        Condition::Unresolvable
+                    });
+                    blocks.push(Block { condition, options: Vec::new(), group: group.clone(), path: path.to_path_buf() });
+                    current = blocks.len() - 1;
+                }
+                "include" if depth >= MAX_INCLUDE_DEPTH => {
+                    eprintln!(" └── Include nested too deep in {}, ignored.", path.display());
+                }
+                "include" => {
+                    for included in args.iter().flat_map(|pattern| include_paths(pattern, base_dir)) {
+                        match fs::read_to_string(&included) {
+                            Ok(content) => self.read(&content, &included, base_dir, depth + 1, blocks, current),
+                            Err(e) => eprintln!(" └── Cannot read included file {}: {e}", included.display()),
+                        }
+                    }
+                }
+                _ => blocks[current].options.push((keyword, args)),
+            }
+        }
+    }
+
+    /// Build the profiles of the concrete aliases and the defaults
+    fn resolve(&mut self, blocks: &[Block]) {
+        let mut aliases: Vec<(String, &Block)> = Vec::new();
+        for block in blocks {
+            if let Condition::Host(patterns) = &block.condition {
+                for pattern in patterns {
+                    let concrete = !pattern.contains(['*', '?', '!']);
+                    if concrete && !aliases.iter().any(|(alias, _)| alias == pattern) {
+                        aliases.push((pattern.clone(), block));
+                    }
+                }
+            }
+        }
+
+        let mut defaults = Options::default();
+        for block in blocks.iter().filter(|b| matches_all(&b.condition)) {
+            defaults.add(&block.options);
+        }
+        self.defaults = defaults.profile("*", None);
+
+        let mut profiles: BTreeMap<String, Connection> = BTreeMap::new();
+        let mut jumps: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (alias, first_block) in &aliases {
+            let mut options = Options::default();
+            for block in blocks.iter().filter(|b| !matches_all(&b.condition)) {
+                let host_name = options.first("hostname").map(|h| h.replace("%h", alias));
+                if block_matches(&block.condition, alias, host_name.as_deref().unwrap_or(alias)) {
+                    options.add(&block.options);
+                }
+            }
+            let mut profile = options.profile(alias, first_block.group.clone());
+            profile.path = first_block.path.clone();
+            if let Some(chain) = options.first("proxyjump").filter(|j| !j.eq_ignore_ascii_case("none")) {
+                jumps.insert(alias.clone(), chain.split(',').map(|hop| hop.trim().to_string()).filter(|h| !h.is_empty()).collect());
+            }
+            profiles.insert(alias.clone(), profile);
+        }
+
+        // Jump hosts of the chains, the hops are aliases of the file or user@host:port specs
+        let hop_jump_host = |hop: &str, profiles: &BTreeMap<String, Connection>| -> JumpHost {
+            let (user, host, port) = parse_hop(hop);
+            match profiles.get(&host) {
+                Some(profile) => JumpHost {
+                    host: profile.host.clone().unwrap_or(host),
+                    port: port.or(profile.port).unwrap_or(22),
+                    user: user.or_else(|| profile.user.clone()),
+                    auth: profile.auth,
+                    private_key: profile.private_key.clone(),
+                    password: None,
+                },
+                None => JumpHost { host, port: port.unwrap_or(22), user, auth: None, private_key: None, password: None },
+            }
+        };
+        let mut created_hops = Vec::new();
+        for (alias, chain) in &jumps {
+            let Some(last) = chain.last() else { continue };
+            let jump_host = hop_jump_host(last, &profiles);
+            for pair in chain.windows(2) {
+                let previous = hop_jump_host(&pair[0], &profiles);
+                let (_, hop_host, _) = parse_hop(&pair[1]);
+                if let Some(profile) = profiles.get_mut(&hop_host) {
+                    if profile.jump_host.as_ref().is_some_and(|j| j != &previous) {
+                        eprintln!(" └── Jump host '{hop_host}' reached through {} in the chain of '{alias}', not its own ProxyJump.", previous.host);
+                    }
+                    profile.jump_host = Some(previous);
+                } else {
+                    let hop = hop_jump_host(&pair[1], &profiles);
+                    let name = format!("{} (jump host)", hop.host);
+                    if !created_hops.iter().any(|p: &Connection| p.name.as_deref() == Some(name.as_str())) {
+                        created_hops.push(Connection {
+                            name: Some(name),
+                            host: Some(hop.host.clone()),
+                            port: Some(hop.port),
+                            group: Some(JUMP_HOSTS_GROUP.to_string()),
+                            protocol: ConnectionProtocols::Ssh,
+                            user: hop.user.clone(),
+                            jump_host: Some(previous),
+                            source_id: Some(format!("{SSH_CONFIG_SCHEME}:{}", pair[1])),
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+            if let Some(profile) = profiles.get_mut(alias) {
+                profile.jump_host = Some(jump_host);
+            }
+        }
+
+        // Hosts in file order, each after the jump hosts it goes through
+        let mut ordered: Vec<Connection> = aliases.iter().filter_map(|(alias, _)| profiles.remove(alias)).collect();
+        ordered.extend(created_hops);
+        self.profiles = order_by_jump_host(ordered);
+    }
+}
+
+/// First value of every keyword, as ssh keeps it
+#[derive(Debug, Default)]
+struct Options {
+    values: BTreeMap<String, Vec<String>>,
+}
+
+impl Options {
+    fn add(&mut self, options: &[(String, Vec<String>)]) {
+        for (keyword, args) in options {
+            self.values.entry(keyword.clone()).or_insert_with(|| args.clone());
+        }
+    }
+
+    fn first(&self, keyword: &str) -> Option<String> {
+        self.values.get(keyword).map(|args| args.join(" "))
+    }
+
+    fn flag(&self, keyword: &str) -> Option<bool> {
+        self.first(keyword).map(|v| matches!(v.to_lowercase().as_str(), "yes" | "true" | "always"))
+    }
+
+    /// The options as a SSH connection named after the alias
+    fn profile(&self, alias: &str, group: Option<String>) -> Connection {
+        let host_name = self.first("hostname").map(|h| h.replace("%h", alias));
+        let user = self.first("user");
+        let expand = |value: String| {
+            let value = match value.strip_prefix("~/") {
+                Some(rest) => format!("{}/{rest}", std::env::var("HOME").unwrap_or_default()),
+                None => value,
+            };
+            value.replace("%d", &std::env::var("HOME").unwrap_or_default())
+                .replace("%h", host_name.as_deref().unwrap_or(alias))
+                .replace("%r", user.as_deref().unwrap_or_default())
+        };
+        let private_key = self.values.get("identityfile").and_then(|args| args.first().cloned()).map(expand);
+        // Without PreferredAuthentications ssh tries every method, the client picks (None)
+        let auth = match self.first("preferredauthentications").as_deref().and_then(|p| p.split(',').next()) {
+            Some("publickey") => Some(AuthMethod::PublicKey),
+            Some("password") => Some(AuthMethod::Password),
+            Some("keyboard-interactive") => Some(AuthMethod::KeyboardInteractive),
+            Some("gssapi-with-mic") => Some(AuthMethod::Gssapi),
+            _ => None,
+        };
+        let port = self.first("port").and_then(|port| match port.parse() {
+            Ok(port) => Some(port),
+            Err(_) => {
+                eprintln!(" └── Warning: Invalid port '{port}' for host '{alias}', using the default port");
+                None
+            }
+        });
+        Connection {
+            name: Some(alias.to_string()),
+            host: host_name.or_else(|| Some(alias.to_string())).filter(|_| alias != "*"),
+            port,
+            group,
+            protocol: ConnectionProtocols::Ssh,
+            user,
+            auth,
+            private_key,
+            proxy_command: self.first("proxycommand").filter(|c| !c.eq_ignore_ascii_case("none")),
+            compression: self.flag("compression"),
+            strict_host_key_check: self.flag("stricthostkeychecking"),
+            kex_algorithms: self.first("kexalgorithms").map(|list| split_list(&list)).unwrap_or_default(),
+            ciphers: self.first("ciphers").map(|list| split_list(&list)).unwrap_or_default(),
+            host_key_types: self.first("hostkeyalgorithms").map(|list| split_list(&list)).unwrap_or_default(),
+            remote_command: self.first("remotecommand"),
+            forward_x11: self.flag("forwardx11"),
+            source_id: Some(format!("{SSH_CONFIG_SCHEME}:{alias}")),
+            ..Default::default()
+        }
+    }
+}
+
+/// Keyword (lowercase) and arguments of a line, None for blank lines
+///
+/// Notes:
+///     "Keyword value" and "Keyword=value" are both accepted, double quoted arguments may contain spaces
+fn split_line(line: &str) -> Option<(String, Vec<String>)> {
+    let end = line.find(|c: char| c.is_whitespace() || c == '=')?;
+    let keyword = line[..end].to_lowercase();
+    let rest = line[end..].trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim_start();
+    (!keyword.is_empty()).then(|| (keyword, split_arguments(rest)))
+}
+
+/// Arguments of a line separated by whitespace, double quoted arguments may contain spaces
+///
+/// # Arguments
+/// * `line` - The arguments of an ssh_config keyword, or a whole command line
+/// # Returns
+/// * `Vec<String>` - The arguments without their quotes
+pub fn split_arguments(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+/// An ssh command line ("ssh -p 2222 -J bastion deploy@db.example.com uptime") as a SSH connection
+///
+/// # Arguments
+/// * `args` - The arguments following the ssh program
+/// # Returns
+/// * `Result<Connection, String>` - The connection named after the destination host,
+///   the error message if there is no destination or an option misses its value
+/// # Behavior
+/// * -p, -l, -i, -J, -C, -X/-Y/-x and "-o Keyword=value" are read as their ssh_config keywords,
+///   the first value wins as in ssh, so they take precedence over the user and port of the destination
+/// * The destination is "[user@]host" or "ssh://[user@]host[:port]", the arguments after it are the remote command
+/// * A ProxyJump chain gives the host the last hop as jump host
+pub fn command_line_profile(args: &[String]) -> std::result::Result<Connection, String> {
+    let mut options: Vec<(String, Vec<String>)> = Vec::new();
+    let mut destination = None;
+    let mut command = Vec::new();
+    let mut options_done = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if destination.is_some() {
+            command.push(arg.clone());
+            continue;
+        }
+        let flags = match arg.strip_prefix('-') {
+            Some("-") if !options_done => {
+                options_done = true;
+                continue;
+            }
+            Some(flags) if !flags.is_empty() && !options_done => flags,
+            _ => {
+                destination = Some(arg.as_str());
+                continue;
+            }
+        };
+        for (index, flag) in flags.char_indices() {
+            if !SSH_VALUE_FLAGS.contains(flag) {
+                let option = match flag {
+                    'C' => ("compression", "yes"),
+                    'X' | 'Y' => ("forwardx11", "yes"),
+                    'x' => ("forwardx11", "no"),
+                    _ => continue,
+                };
+                options.push((option.0.to_string(), vec![option.1.to_string()]));
+                continue;
+            }
+            // The value follows the flag ("-p2222") or is the next argument
+            let value = match &flags[index + flag.len_utf8()..] {
+                "" => args.next().ok_or_else(|| format!("option -{flag} needs a value"))?.clone(),
+                attached => attached.to_string(),
+            };
+            let keyword = match flag {
+                'p' => "port",
+                'l' => "user",
+                'i' => "identityfile",
+                'J' => "proxyjump",
+                'o' => {
+                    options.extend(split_line(&value));
+                    break;
+                }
+                _ => break,
+            };
+            options.push((keyword.to_string(), vec![value]));
+            break;
+        }
+    }
+
+    let destination = destination.ok_or("no destination host")?;
+    let (user, host, port) = parse_hop(destination);
+    options.extend(user.map(|user| ("user".to_string(), vec![user])));
+    options.extend(port.map(|port| ("port".to_string(), vec![port.to_string()])));
+    if !command.is_empty() {
+        options.push(("remotecommand".to_string(), vec![command.join(" ")]));
+    }
+    let mut values = Options::default();
+    values.add(&options);
+
+    let mut profile = values.profile(&host, None);
+    if let Some(chain) = values.first("proxyjump").filter(|j| !j.eq_ignore_ascii_case("none")) {
+        let hops: Vec<&str> = chain.split(',').map(str::trim).filter(|h| !h.is_empty()).collect();
+        if hops.len() > 1 {
+            eprintln!(" └── '{destination}' goes through {} jump hosts, only the last one is kept.", hops.len());
+        }
+        profile.jump_host = hops.last().map(|hop| {
+            let (user, host, port) = parse_hop(hop);
+            JumpHost { host, port: port.unwrap_or(22), user, auth: None, private_key: None, password: None }
+        });
+    }
+    Ok(profile)
+}
+
+/// The criteria of a Match line, None if one of them cannot be resolved statically
+fn parse_match(args: &[String]) -> Option<Condition> {
+    let mut criteria = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (negated, name) = match arg.strip_prefix('!') {
+            Some(name) => (true, name.to_lowercase()),
+            None => (false, arg.to_lowercase()),
+        };
+        match name.as_str() {
+            "all" => criteria.push(Criterion::All),
+            "host" | "originalhost" => {
+                let patterns = args.next()?.split(',').map(str::to_string).collect();
+                criteria.push(Criterion::Host { patterns, original: name == "originalhost", negated });
+            }
+            _ => return None,
+        }
+    }
+    Some(Condition::Match(criteria))
+}
+
+/// Whether a block applies to every host: global lines, "Host *" and "Match all"
+fn matches_all(condition: &Condition) -> bool {
+    match condition {
+        Condition::Global => true,
+        Condition::Host(patterns) => patterns.iter().all(|p| p == "*") && !patterns.is_empty(),
+        Condition::Match(criteria) => criteria.iter().all(|c| matches!(c, Criterion::All)),
+        Condition::Unresolvable => false,
+    }
+}
+
+fn block_matches(condition: &Condition, alias: &str, host_name: &str) -> bool {
+    match condition {
+        Condition::Global => true,
+        Condition::Host(patterns) => pattern_list_matches(patterns, alias),
+        Condition::Match(criteria) => criteria.iter().all(|criterion| match criterion {
+            Criterion::All => true,
+            Criterion::Host { patterns, original, negated } => {
+                let value = if *original { alias } else { host_name };
+                pattern_list_matches(patterns, value) != *negated
+            }
+        }),
+        Condition::Unresolvable => false,
+    }
+}
+
+/// ssh pattern list: one pattern must match and no negated pattern may match
+fn pattern_list_matches(patterns: &[String], value: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        match pattern.strip_prefix('!') {
+            Some(negated) if wildcard_match(negated, value) => return false,
+            Some(_) => {}
+            None => matched |= wildcard_match(pattern, value),
+        }
+    }
+    matched
+}
+
+/// Case-insensitive match of `*` and `?` wildcards
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let value: Vec<char> = value.to_lowercase().chars().collect();
+    let (mut p, mut v) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, v));
+            p += 1;
+        } else if let Some((star_p, star_v)) = star {
+            p = star_p + 1;
+            v = star_v + 1;
+            star = Some((star_p, star_v + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Files of an Include argument: ~ expanded, relative to the ssh directory, glob patterns sorted
+fn include_paths(pattern: &str, base_dir: &Path) -> Vec<PathBuf> {
+    let pattern = match pattern.strip_prefix("~/") {
+        Some(rest) => format!("{}/{rest}", std::env::var("HOME").unwrap_or_default()),
+        None => pattern.to_string(),
+    };
+    let pattern = if Path::new(&pattern).is_absolute() { pattern } else { base_dir.join(pattern).display().to_string() };
+    let mut paths: Vec<PathBuf> = glob::glob(&pattern).map(|paths| paths.flatten().collect()).unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+/// User, host and port of a ProxyJump hop ("[user@]host[:port]", "[v6]:port")
+fn parse_hop(hop: &str) -> (Option<String>, String, Option<u16>) {
+    let hop = hop.strip_prefix("ssh://").unwrap_or(hop);
+    let (user, host) = match hop.rsplit_once('@') {
+        Some((user, host)) => (Some(user.to_string()), host),
+        None => (None, hop),
+    };
+    let explicit_port = if host.starts_with('[') { host.contains("]:") } else { host.matches(':').count() == 1 };
+    let (host, port) = JumpHost::parse_server(host);
+    (user, host, explicit_port.then_some(port))
+}
+
+/// Puts every profile after the profile of its jump host (same host, port and user), keeping the order otherwise
+fn order_by_jump_host(profiles: Vec<Connection>) -> Vec<Connection> {
+    let key = |host: &str, port: u16, user: &Option<String>| (host.to_lowercase(), port, user.clone());
+    let profile_key = |p: &Connection| key(p.host.as_deref().unwrap_or_default(), p.port.unwrap_or(22), &p.user);
+    let keys: HashSet<_> = profiles.iter().map(profile_key).collect();
+
+    let mut ordered = Vec::new();
+    let mut placed = HashSet::new();
+    let mut remaining = profiles;
+    while !remaining.is_empty() {
+        let before = remaining.len();
+        let (ready, waiting): (Vec<_>, Vec<_>) = remaining.into_iter().partition(|p| match &p.jump_host {
+            None => true,
+            Some(j) => {
+                let jump_host = key(&j.host, j.port, &j.user);
+                placed.contains(&jump_host) || !keys.contains(&jump_host)
+            }
+        });
+        for profile in ready {
+            placed.insert(profile_key(&profile));
+            ordered.push(profile);
+        }
+        remaining = waiting;
+        // Jump hosts going through each other: keep the file order
+        if remaining.len() == before {
+            ordered.append(&mut remaining);
+        }
+    }
+    ordered
+}
@@ -1,78 +1,258 @@
 // use std::path::{Path, PathBuf};
 use std::path::Path;
 use std::fs;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::LazyLock;
 
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use serde_yaml_ng::Value;
 
-use crate::remmina_types::RemminaProfile;
+use crate::connection::{AuthMethod, Connection, JumpHost, source_scheme};
+use crate::error::{Error, Result};
+use crate::remmina_secrets::{SecretKind, SecretSink};
+use crate::report::{ConflictDecision, Plan, PlannedGroup, PlannedProfile, ProfileDefault, RemovedProfile, SkippedProfile, SyncedProfile};
+use crate::tabby_vault::StoredVault;
 use crate::protocols_types::{ConnectionProtocols, get_default_port_for_protocol};
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 /// Represents the options for a profile, with various optional fields.
+/// 
+/// Notes:
+///     Every field is optional and skipped when absent, so that options of existing profiles
+///     (which may not set a port, or may belong to non SSH profile types) are written back as they were read
 pub struct ProfileOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub algorithms: Option<serde_yaml_ng::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub input: Option<serde_yaml_ng::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub auth: Option<String>,
-    pub port: u16,
-    // Add other fields as needed
+    #[serde(rename = "privateKeys", default, skip_serializing_if = "Option::is_none")]
+    pub private_keys: Option<Vec<String>>,
+    #[serde(rename = "proxyCommand", default, skip_serializing_if = "Option::is_none")]
+    pub proxy_command: Option<String>,
+    /// Login scripts, a list of {expect, send, isRegex, optional} entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scripts: Option<serde_yaml_ng::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x11: Option<bool>,
+    /// Id of the profile used as jump host
+    #[serde(rename = "jumpHost", default, skip_serializing_if = "Option::is_none")]
+    pub jump_host: Option<String>,
+    /// Kept as a raw value as serial profiles store a device path here
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<serde_yaml_ng::Value>,
+
+    /// Options of existing profiles without a field above (forwarded ports, keep-alive, agent forwarding,
+    /// options of other profile types, ...) kept as-is
+    #[serde(flatten)]
+    extra: BTreeMap<String, Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 /// Represents a profile with various attributes and options.
+/// 
+/// Notes:
+///     type, options, icon, weight and color are only filled with defaults for newly imported profiles,
+///     existing profiles keep them unset if they were unset in config.yaml, see `profile_type` and `options`
 pub struct Profile {
-    #[serde(default = "Profile::default_type")]
-    pub r#type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
     pub name: String,
-    #[serde(default = "Profile::default_icon")]
-    pub icon: String,
-    pub options: ProfileOptions, //Option<serde_yaml_ng::Value>,
-    #[serde(default = "Profile::default_weight")]
-    pub weight: i32,
-    #[serde(default = "Profile::default_color")]
-    pub color: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<ProfileOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
-    // Add other fields as needed
+    /// Source id of the connection this profile was imported from, see `Connection::source_id`
+    /// (a .remmina file name, or "scheme:id" for the other formats, see `source_scheme`)
+    #[serde(rename = "remminaSource", default, skip_serializing_if = "Option::is_none")]
+    pub remmina_source: Option<String>,
+
+    /// Unknown profile fields kept as-is
+    #[serde(flatten)]
+    extra: BTreeMap<String, Value>,
 }
 #[derive(Debug, Serialize, Deserialize)]
 /// Represents a group with an ID and name.
 pub struct Group {
     pub id: String,
     pub name: String,
+
+    /// Unknown group fields (e.g. defaults) kept as-is
+    #[serde(flatten)]
+    extra: BTreeMap<String, Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 /// Represents the entire configuration structure.
 pub struct TabbyConfig {
     pub version: u32,
+    #[serde(default)]
     pub profiles: Vec<Profile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub groups: Option<Vec<Group>>,
+    /// Encrypted vault, see `TabbyVault` to read or update it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<StoredVault>,
     // Add other fields as needed
     
     /// This field captures any extra fields from the YAML that are not explicitly defined
     /// in the TabbyConfig struct. It allows deserialization of unknown or future fields
     /// without breaking, storing them in a BTreeMap with their names and values.
     #[serde(flatten)]
-    extra: std::collections::BTreeMap<String, Value>,
+    extra: BTreeMap<String, Value>,
+
+}
+
+/// How a connection colliding with an existing Tabby profile is imported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Keep the existing profile, do not import
+    #[default]
+    Skip,
+    /// Replace the existing profile, keeping its id
+    Overwrite,
+    /// Fill only the unset fields of the existing profile
+    Merge,
+    /// Import under a new name (name + rename suffix)
+    Rename,
+    /// Ask for each conflict
+    Ask,
+}
+
+impl ConflictStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictStrategy::Skip => "skip",
+            ConflictStrategy::Overwrite => "overwrite",
+            ConflictStrategy::Merge => "merge",
+            ConflictStrategy::Rename => "rename",
+            ConflictStrategy::Ask => "ask",
+        }
+    }
+}
+
+/// Asks how to resolve the conflict between a connection and an existing profile,
+/// returning `Error::Cancelled` stops the import
+pub type ConflictPrompt = fn(&Connection, &Profile) -> Result<ConflictStrategy>;
+
+/// Options of `TabbyConfig::import_profiles`
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Update the profiles imported earlier from the same connection instead of skipping them
+    pub sync: bool,
+    /// What to do with a profile colliding with an existing one
+    pub on_conflict: ConflictStrategy,
+    /// Also detect conflicts by host, port and user, not only by name
+    pub match_identity: bool,
+    /// Appended to the name of renamed profiles, `{group}` is replaced by the group name
+    pub rename_suffix: String,
+    /// Used by `ConflictStrategy::Ask`, conflicts are skipped when None
+    pub ask: Option<ConflictPrompt>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        ImportOptions {
+            sync: false,
+            on_conflict: ConflictStrategy::Skip,
+            match_identity: false,
+            rename_suffix: "(remmina)".to_string(),
+            ask: None,
+        }
+    }
+}
+
+impl ProfileOptions {
+    /// Returns the port as a number, if it is set and numeric
+    pub fn port_number(&self) -> Option<u16> {
+        self.port.as_ref()
+            .and_then(|p| p.as_u64())
+            .and_then(|p| u16::try_from(p).ok())
+    }
+
+    /// Sets the unset options from other options, set options are kept
+    fn fill_from(&mut self, other: ProfileOptions) {
+        self.host = self.host.take().or(other.host);
+        self.user = self.user.take().or(other.user);
+        self.algorithms = self.algorithms.take().or(other.algorithms);
+        self.input = self.input.take().or(other.input);
+        self.auth = self.auth.take().or(other.auth);
+        self.private_keys = self.private_keys.take().or(other.private_keys);
+        self.proxy_command = self.proxy_command.take().or(other.proxy_command);
+        self.scripts = self.scripts.take().or(other.scripts);
+        self.x11 = self.x11.take().or(other.x11);
+        self.jump_host = self.jump_host.take().or(other.jump_host);
+        self.port = self.port.take().or(other.port);
+        for (key, value) in other.extra {
+            self.extra.entry(key).or_insert(value);
+        }
+    }
+}
+
+impl Profile {
+    /// Type of the profile ("ssh", "rdp", ...), "ssh" if config.yaml has none
+    pub fn profile_type(&self) -> &str {
+        self.r#type.as_deref().unwrap_or("ssh")
+    }
+
+    /// Options of the profile, empty ones if config.yaml has none
+    pub fn options(&self) -> &ProfileOptions {
+        static NO_OPTIONS: LazyLock<ProfileOptions> = LazyLock::new(ProfileOptions::default);
+        self.options.as_ref().unwrap_or(&NO_OPTIONS)
+    }
 
+    /// Options of the profile to change them, added to the profile if config.yaml has none
+    pub fn options_mut(&mut self) -> &mut ProfileOptions {
+        self.options.get_or_insert_with(ProfileOptions::default)
+    }
+
+    /// Sets the unset fields and options from another profile, id and Remmina source excluded
+    fn fill_from(&mut self, other: Profile) {
+        self.icon = self.icon.take().or(other.icon);
+        self.weight = self.weight.take().or(other.weight);
+        self.color = self.color.take().or(other.color);
+        self.group = self.group.take().or(other.group);
+        match (&mut self.options, other.options) {
+            (Some(options), Some(other)) => options.fill_from(other),
+            (options @ None, other) => *options = other,
+            _ => {}
+        }
+        for (key, value) in other.extra {
+            self.extra.entry(key).or_insert(value);
+        }
+    }
 }
 
 /// Provide default values for Profile fields
 impl Default for Profile {
     fn default() -> Self {
         Profile {
-            r#type: Profile::default_type(),
+            r#type: Some(Profile::default_type()),
             name: String::new(),
-            icon: Profile::default_icon(),
-            options: ProfileOptions::default(),
-            weight: Profile::default_weight(),
-            color: Profile::default_color(),
+            icon: Some(Profile::default_icon()),
+            options: Some(ProfileOptions::default()),
+            weight: Some(Profile::default_weight()),
+            color: Some(Profile::default_color()),
             group: None,
             id: None,
+            remmina_source: None,
+            extra: BTreeMap::new(),
         }
     }
 }
@@ -100,26 +280,24 @@ impl TabbyConfig {
     /// # Arguments
     /// * `dir` - The directory where config.yaml is located.
     /// # Returns
-    /// * `Result<Self, String>` - Ok(TabbyConfig) if successful, Err(String) with error message if failed.
+    /// * `Result<Self>` - Ok(TabbyConfig) if successful, Err(Error) if failed.
     /// # Errors
-    /// * If the config.yaml file does not exist in the specified directory.
-    /// * If there is an error reading the file.
-    /// * If there is an error parsing the YAML content.
+    /// * Io if config.yaml does not exist in the specified directory or cannot be read.
+    /// * YamlParse, with line and column, if the YAML content cannot be parsed.
     /// # Example
-    /// ```
+    /// ```no_run
+    /// # use remmina_to_tabby::TabbyConfig;
     /// let config = TabbyConfig::load_from_dir("/path/to/tabby/dir")?;
+    /// # Ok::<(), remmina_to_tabby::Error>(())
     /// ```
     /// # Notes
     /// This function uses the `serde_yaml_ng` crate for YAML parsing.
-    pub fn load_from_dir(dir: &str) -> Result<Self, String> {
+    pub fn load_from_dir(dir: &str) -> Result<Self> {
         let config_path = Path::new(dir).join("config.yaml");
-        if !config_path.exists() {
-            return Err(format!("Error: config.yaml not found in directory '{dir}'"));
-        }
         let content = fs::read_to_string(&config_path)
-            .map_err(|e| format!("Error reading config.yaml: {e}"))?;
+            .map_err(|e| Error::io(&config_path, e))?;
         let config: TabbyConfig = serde_yaml_ng::from_str(&content)
-            .map_err(|e| format!("Error parsing config.yaml: {e}"))?;
+            .map_err(|e| Error::yaml(&config_path, &e))?;
         Ok(config)
     }
 
@@ -188,21 +366,20 @@ impl TabbyConfig {
     ///         - https://github.com/Eugeny/tabby/issues/6522
     ///         - https://github.com/Eugeny/tabby/issues/6408
     ///         - https://github.com/Eugeny/tabby/issues/411
-    ///   
     ///     So if a group with "/" is found, it will be created as is
     ///     and it will be up to the user to manage it in Tabby
-    ///     Future improvement could be to create a flat group structure in Tabby by replacing "/" with " - " or similar
-    ///     but for now we keep it simple and create groups as is
+    ///     Group paths are mapped to a single level before the import if requested, see `GroupMode`
     pub fn add_group(&mut self, name: &str) -> String {
         // Check if group with the same name already exists
         if let Some(groups) = &mut self.groups {
             if let Some(existing_group) = groups.iter().find(|g| g.name == name) {
-                println!(" └── Group '{name}' already exists.");
+                eprintln!(" └── Group '{name}' already exists.");
                 existing_group.id.clone()
             } else {
                 let new_group = Group {
                     id: TabbyConfig::generate_group_id(),
                     name: name.to_string(),
+                    extra: BTreeMap::new(),
                 };
                 let group_id = new_group.id.clone();
                 groups.push(new_group);
@@ -212,6 +389,7 @@ impl TabbyConfig {
             let new_group = Group {
                 id: TabbyConfig::generate_group_id(),
                 name: name.to_string(),
+                extra: BTreeMap::new(),
             };
             let group_id = new_group.id.clone();
             self.groups = Some(vec![new_group]);
@@ -219,88 +397,662 @@ impl TabbyConfig {
         }
     }
     
-    /// Imports multiple profiles into the TabbyConfig.
+    /// Imports connections as profiles of the TabbyConfig.
     /// 
     /// # Arguments
-    /// * `profiles` - A vector of Profile instances to be added.
+    /// * `profiles` - The connections to import, see `build_profile` for the mapping.
+    /// * `options` - Import behaviour, see `ImportOptions`
+    /// * `secret_sink` - Receives the decrypted secrets of the imported profiles, keyed by their new ids
+    /// # Returns
+    /// * `Result<Plan>` - The groups and profiles created (jump hosts included), updated, unchanged and skipped,
+    ///   with the conflict decisions, or Cancelled if the user quit at a conflict prompt
+    /// # Behavior
+    /// * Created profiles record the source id of their connection in `remminaSource`
+    /// * With `options.sync`, a profile with the same source id gets its host, port, user, auth and group updated,
+    ///   without it the profile is skipped
+    /// * Other profiles with the same name (or host, port and user with `options.match_identity`)
+    ///   are resolved with `options.on_conflict`
     ///
-    pub fn import_profiles(&mut self, profiles: Vec<RemminaProfile >) -> usize {
-        let mut imported_count = 0;
+    /// Notes:
+    ///     Connections without jump host are imported first, so a connection going through a bastion can reuse
+    ///     the imported profile of the bastion as jump host
+    pub fn import_profiles(&mut self, mut profiles: Vec<Connection>, options: &ImportOptions, secret_sink: &mut dyn SecretSink) -> Result<Plan> {
+        profiles.sort_by_key(|p| p.jump_host.is_some());
+
+        let mut plan = Plan::default();
+        let known_profiles: Vec<Option<String>> = self.profiles.iter().map(|p| p.id.clone()).collect();
+        let known_groups: Vec<String> = self.groups.iter().flatten().map(|g| g.id.clone()).collect();
+
         for profile in profiles {
-            // println!("➡️ Importing profile: {:?}", profile);
-            println!(" ➡️  Importing Profile: '{}' (protocol={})", profile.name.clone().unwrap_or_default(), profile.protocol.clone().unwrap_or_default());
+            // eprintln!("➡️ Importing profile: {:?}", profile);
+            eprintln!(" ➡️  Importing Profile: '{}' (protocol={})", profile.name.clone().unwrap_or_default(), profile.protocol.as_str().to_uppercase());
 
-            if self.get_profile(&profile.name.clone().unwrap_or_default()).is_some() {
-                println!(" └── Profile '{}' already exists. Skipping import.", profile.name.clone().unwrap_or_default());
+            let tracked = profile.source_id.as_ref()
+                .and_then(|source| self.profiles.iter().position(|p| p.remmina_source.as_ref() == Some(source)));
+            if let Some(index) = tracked {
+                if options.sync {
+                    let synced = self.sync_profile(index, &profile);
+                    if synced.changed.is_empty() {
+                        eprintln!(" └── Profile '{}' is up to date.", synced.name);
+                        plan.unchanged_profiles.push(synced);
+                    } else {
+                        eprintln!(" └── Profile '{}' updated: {}", synced.name, synced.changed.join(", "));
+                        plan.updated_profiles.push(synced);
+                    }
+                } else {
+                    eprintln!(" └── Profile '{}' was already imported. Skipping import (use sync to update it).", profile.name.clone().unwrap_or_default());
+                    let conflict = Error::Conflict {
+                        name: profile.name.clone().unwrap_or_default(),
+                        reason: "already imported from the same connection, sync to update it".to_string(),
+                    };
+                    plan.skipped_profiles.push(SkippedProfile::from_error(&profile, &conflict));
+                }
                 continue;
-            } else {
-                let profile_id = self.generate_profile_uuid(
-                    &profile.protocol.clone().unwrap_or_default().to_lowercase(),
-                    "custom",
-                    &profile.name.clone().unwrap_or_default(),
-                );
-                println!(" └── Generated profile UUID: {profile_id}");
+            }
 
-                let group_id = self.add_group(profile.group.as_deref().unwrap_or("Default Group"));
-                // println!(" └── Using group id: {:?} - name: {:?}", group_id, profile.group);
-                println!(" └── Using group id: {:?} - name: {:?}", group_id, profile.group.as_deref().unwrap_or("Default Group"));
+            let mut name = profile.name.clone().unwrap_or_default();
+            if let Some((index, matched_by)) = self.find_conflict(&profile, options.match_identity) {
+                let existing = &self.profiles[index];
+                eprintln!(" └── Profile '{name}' conflicts with existing profile '{}' (same {matched_by}).", existing.name);
+                let strategy = match (options.on_conflict, options.ask) {
+                    (ConflictStrategy::Ask, Some(ask)) => ask(&profile, existing)?,
+                    (ConflictStrategy::Ask, None) => ConflictStrategy::Skip,
+                    (strategy, _) => strategy,
+                };
+                let mut decision = ConflictDecision {
+                    name: name.clone(),
+                    existing: existing.name.clone(),
+                    existing_id: existing.id.clone().unwrap_or_default(),
+                    matched_by: matched_by.to_string(),
+                    decision: strategy,
+                    renamed_to: None,
+                };
 
-                // println!("Profile port: {:?}", profile.port);
+                match strategy {
+                    ConflictStrategy::Overwrite | ConflictStrategy::Merge => {
+                        let synced = self.resolve_conflict(index, &profile, strategy, secret_sink);
+                        eprintln!(" └── Profile '{}' {}: {}", synced.name, strategy.as_str(), if synced.changed.is_empty() { "no change".to_string() } else { synced.changed.join(", ") });
+                        if synced.changed.is_empty() {
+                            plan.unchanged_profiles.push(synced);
+                        } else {
+                            plan.updated_profiles.push(synced);
+                        }
+                        plan.conflicts.push(decision);
+                        continue;
+                    }
+                    ConflictStrategy::Rename => {
+                        name = self.unique_name(&name, &profile, &options.rename_suffix);
+                        eprintln!(" └── Importing as '{name}'.");
+                        decision.renamed_to = Some(name.clone());
+                        plan.conflicts.push(decision);
+                    }
+                    ConflictStrategy::Skip | ConflictStrategy::Ask => {
+                        eprintln!(" └── Skipping import.");
+                        let reason = match matched_by {
+                            "name" => "a profile with the same name already exists".to_string(),
+                            _ => format!("profile '{}' has the same host, port and user", decision.existing),
+                        };
+                        let conflict = Error::Conflict { name: name.clone(), reason };
+                        plan.skipped_profiles.push(SkippedProfile::from_error(&profile, &conflict));
+                        plan.conflicts.push(decision);
+                        continue;
+                    }
+                }
+            }
 
+            let profile_id = self.generate_profile_uuid(profile.protocol.as_str(), "custom", &name);
+            eprintln!(" └── Generated profile UUID: {profile_id}");
 
-                // Set default port based on protocol if port is None
-                let proto = ConnectionProtocols::from_str(profile.protocol.as_deref().unwrap_or(""));
+            let mut new_profile = self.build_profile(&profile, &name);
+            new_profile.id = Some(profile_id);
+            new_profile.group = Some(self.add_profile_group(&profile));
+            if let Some(jump_host) = &profile.jump_host {
+                new_profile.options_mut().jump_host = Some(self.find_or_add_jump_host(jump_host, secret_sink));
+            }
+            store_secrets(&profile, &new_profile, secret_sink);
+            self.add_profile(new_profile);
+        }
 
-                let port = profile.port
-                    .as_ref()
-                    .and_then(|p| p.parse::<u16>().ok())
-                    .unwrap_or_else(|| get_default_port_for_protocol(&proto));
+        plan.created_groups = self.groups.iter().flatten()
+            .filter(|g| !known_groups.contains(&g.id))
+            .map(|g| PlannedGroup { id: g.id.clone(), name: g.name.clone() })
+            .collect();
+        plan.created_profiles = self.profiles.iter()
+            .filter(|p| !known_profiles.contains(&p.id))
+            .map(|p| PlannedProfile {
+                id: p.id.clone().unwrap_or_default(),
+                name: p.name.clone(),
+                r#type: p.profile_type().to_string(),
+                host: p.options().host.clone(),
+                port: p.options().port_number(),
+                user: p.options().user.clone(),
+                group: p.group.clone(),
+                jump_host: p.options().jump_host.clone(),
+            })
+            .collect();
+        Ok(plan)
+    }
 
-                if profile.port.is_some() {
-                    println!(" └── Remmina profile port: {:?}", profile.port);
-                } else {
-                    println!(" └── Remmina profile port not set, using default for protocol [{:?}]: {}", proto.as_str(), get_default_port_for_protocol(&proto));
+    /// Removes the profiles imported from Remmina profiles that no longer exist.
+    ///
+    /// # Arguments
+    /// * `sources` - Source ids of the existing Remmina profiles, see `RemminaFiles::source_ids`
+    /// * `scheme` - Scheme of the source ids of the imported format, None for .remmina files (see `source_scheme`)
+    /// * `plan` - Receives the removed profiles and groups
+    /// # Behavior
+    /// * Only profiles with a `remminaSource` of the same scheme are candidates, profiles created in Tabby
+    ///   or imported from another format are kept
+    /// * A candidate still used as jump host by a kept profile is not removed
+    /// * Groups left without profiles by the removal are removed too, groups that were already empty are kept
+    pub fn prune_profiles(&mut self, sources: &HashSet<String>, scheme: Option<&str>, plan: &mut Plan) {
+        let stale: HashSet<String> = self.profiles.iter()
+            .filter(|p| p.remmina_source.as_ref().is_some_and(|source| source_scheme(source) == scheme && !sources.contains(source)))
+            .filter_map(|p| p.id.clone())
+            .collect();
+        let used_as_jump_host: HashSet<&String> = self.profiles.iter()
+            .filter(|p| !p.id.as_ref().is_some_and(|id| stale.contains(id)))
+            .filter_map(|p| p.options().jump_host.as_ref())
+            .collect();
+        let removed: HashSet<String> = stale.iter()
+            .filter(|id| {
+                let in_use = used_as_jump_host.contains(id);
+                if in_use {
+                    eprintln!(" └── Profile '{id}' lost its Remmina source but is used as jump host, kept.");
                 }
+                !in_use
+            })
+            .cloned()
+            .collect();
+        if removed.is_empty() {
+            return;
+        }
 
-                // Handle auth method for SSH protocol
-                let auth = profile.auth.as_deref().unwrap_or("password");
-                if proto == ConnectionProtocols::Ssh {
-                    match auth {
-                        "password" | "sshidentityfile" | "sshagent" | "publickey" | "kerberosgssapi" | "kerberosinteractive" => {
-                            println!(" └── Remmina profile SSH auth method: {auth:?}");
-                        }
-                        other => {
-                            println!(" └── Warning: Unrecognized SSH auth method '{other}', defaulting to 'password'");
-                        }
-                    }
-                } else if profile.auth.is_some() {
-                    println!(" └── Note: Auth method '{auth}' specified but protocol is not SSH, auth method will be ignored.");
-                } else {
-                    println!(" └── No auth method specified and protocol is not SSH, defaulting to 'password' (will be ignored).");
+        let groups_before: HashSet<String> = self.profiles.iter().filter_map(|p| p.group.clone()).collect();
+        let (gone, kept): (Vec<Profile>, Vec<Profile>) = std::mem::take(&mut self.profiles).into_iter()
+            .partition(|p| p.id.as_ref().is_some_and(|id| removed.contains(id)));
+        self.profiles = kept;
+        for profile in gone {
+            eprintln!(" ➖ Removing Profile: '{}' (source {} not found)", profile.name, profile.remmina_source.as_deref().unwrap_or_default());
+            plan.removed_profiles.push(RemovedProfile {
+                id: profile.id.unwrap_or_default(),
+                name: profile.name,
+                source: profile.remmina_source.unwrap_or_default(),
+            });
+        }
+
+        let groups_after: HashSet<String> = self.profiles.iter().filter_map(|p| p.group.clone()).collect();
+        if let Some(groups) = self.groups.as_mut() {
+            groups.retain(|group| {
+                let emptied = groups_before.contains(&group.id) && !groups_after.contains(&group.id);
+                if emptied {
+                    eprintln!(" ➖ Removing empty Group: '{}'", group.name);
+                    plan.removed_groups.push(PlannedGroup { id: group.id.clone(), name: group.name.clone() });
                 }
+                !emptied
+            });
+        }
+    }
 
-                // Create new ProfileOptions and Profile
-                let new_profile_options = ProfileOptions {
-                    host: profile.server.clone(),
-                    user: profile.user.clone(),
-                    algorithms: Some(serde_yaml_ng::Value::Mapping(Default::default())),
-                    input: Some(serde_yaml_ng::Value::Mapping(Default::default())),
-                    auth: profile.auth.clone(),
-                    port,
-                };
-                let new_profile = Profile {
-                    r#type: profile.protocol.clone().unwrap_or_default().to_lowercase(),
-                    name: profile.name.clone().unwrap_or_default(),
-                    options: new_profile_options,
-                    group: Some(group_id),
-                    id: Some(profile_id),
+    /// Sets the SSH profile defaults (`profileDefaults.ssh.options`) from a connection of default options.
+    ///
+    /// # Arguments
+    /// * `defaults` - Options shared by every host, e.g. the "Host *" block of an ssh_config (`SshConfig::defaults`)
+    /// # Returns
+    /// * `Vec<ProfileDefault>` - The defaults set
+    /// # Behavior
+    /// * user, port, private key, proxy command, algorithms and X11 forwarding are mapped like for a profile
+    /// * Only unset defaults are set, the ones already in config.yaml are kept
+    pub fn set_ssh_defaults(&mut self, defaults: &Connection) -> Vec<ProfileDefault> {
+        let mut options = ProfileOptions {
+            user: defaults.user.clone(),
+            port: defaults.port.map(Value::from),
+            ..Default::default()
+        };
+        TabbyConfig::apply_ssh_options(&mut options, &Connection { remote_command: None, ..defaults.clone() });
+        let Ok(Value::Mapping(values)) = serde_yaml_ng::to_value(&options) else { return Vec::new() };
+
+        let mut set = Vec::new();
+        let profile_defaults = self.extra.entry("profileDefaults".to_string()).or_insert_with(|| Value::Mapping(Default::default()));
+        let Some(ssh_options) = mapping_entry(profile_defaults, "ssh").map(|ssh| mapping_entry(ssh, "options")) else { return set };
+        let Some(Value::Mapping(ssh_options)) = ssh_options else { return set };
+        for (key, value) in values {
+            let empty = value.as_mapping().is_some_and(|m| m.is_empty());
+            if empty || ssh_options.contains_key(&key) {
+                continue;
+            }
+            let key_name = key.as_str().unwrap_or_default().to_string();
+            let shown = serde_yaml_ng::to_string(&value).unwrap_or_default().trim().replace('\n', " ");
+            eprintln!(" └── SSH profile default {key_name}: {shown}");
+            set.push(ProfileDefault { key: format!("ssh.options.{key_name}"), value: shown });
+            ssh_options.insert(key, value);
+        }
+        set
+    }
+
+    /// Finds an existing profile the connection collides with.
+    ///
+    /// # Arguments
+    /// * `profile` - The connection being imported.
+    /// * `match_identity` - Also match profiles of the same type with the same host, port and user.
+    /// # Returns
+    /// * `Option<(usize, &str)>` - Index of the existing profile and what matched ("name" or "host, port and user").
+    fn find_conflict(&self, profile: &Connection, match_identity: bool) -> Option<(usize, &'static str)> {
+        let name = profile.name.clone().unwrap_or_default();
+        if let Some(index) = self.profiles.iter().position(|p| p.name == name) {
+            return Some((index, "name"));
+        }
+        if !match_identity {
+            return None;
+        }
+
+        let port = profile.port.unwrap_or_else(|| get_default_port_for_protocol(&profile.protocol));
+        let r#type = profile.protocol.as_str();
+        self.profiles.iter()
+            .position(|p| {
+                let existing_port = p.options().port_number()
+                    .unwrap_or_else(|| get_default_port_for_protocol(&ConnectionProtocols::from_str(p.profile_type())));
+                p.profile_type() == r#type
+                    && p.options().host.as_deref().map(str::to_lowercase) == profile.host.as_deref().map(str::to_lowercase)
+                    && existing_port == port
+                    && p.options().user == profile.user
+            })
+            .map(|index| (index, "host, port and user"))
+    }
+
+    /// Overwrites or merges an existing profile with a conflicting connection.
+    ///
+    /// # Arguments
+    /// * `index` - Index of the existing Tabby profile in `profiles`.
+    /// * `profile` - The conflicting connection.
+    /// * `strategy` - Overwrite replaces the profile (keeping its id), Merge only fills its unset fields.
+    /// * `secret_sink` - Receives the decrypted secrets of the profile.
+    /// # Returns
+    /// * `SyncedProfile` - The profile id and name, with the names of the changed fields.
+    ///
+    /// Notes:
+    ///     A merged profile is not marked with `remminaSource`, so it is never synced or pruned later
+    fn resolve_conflict(&mut self, index: usize, profile: &Connection, strategy: ConflictStrategy, secret_sink: &mut dyn SecretSink) -> SyncedProfile {
+        let before = serde_yaml_ng::to_value(&self.profiles[index]).unwrap_or_default();
+        let mut new_profile = self.build_profile(profile, &self.profiles[index].name.clone());
+
+        if strategy == ConflictStrategy::Overwrite || self.profiles[index].group.is_none() {
+            new_profile.group = Some(self.add_profile_group(profile));
+        }
+        if let Some(jump_host) = &profile.jump_host
+            && (strategy == ConflictStrategy::Overwrite || self.profiles[index].options().jump_host.is_none())
+        {
+            new_profile.options_mut().jump_host = Some(self.find_or_add_jump_host(jump_host, secret_sink));
+        }
+
+        let existing = &mut self.profiles[index];
+        if strategy == ConflictStrategy::Overwrite {
+            new_profile.id = existing.id.take();
+            *existing = new_profile;
+        } else {
+            existing.fill_from(new_profile);
+        }
+        store_secrets(profile, existing, secret_sink);
+
+        let after = serde_yaml_ng::to_value(&*existing).unwrap_or_default();
+        SyncedProfile {
+            id: existing.id.clone().unwrap_or_default(),
+            name: existing.name.clone(),
+            changed: changed_fields(&before, &after),
+        }
+    }
+
+    /// Returns a name for a renamed profile that no other profile uses.
+    ///
+    /// # Arguments
+    /// * `name` - The conflicting name.
+    /// * `profile` - The connection, for its group.
+    /// * `suffix` - Appended to the name, `{group}` is replaced by the group name.
+    /// # Returns
+    /// * `String` - "name suffix", followed by a counter if that name is taken too.
+    fn unique_name(&self, name: &str, profile: &Connection, suffix: &str) -> String {
+        let suffix = suffix.replace("{group}", profile.group.as_deref().unwrap_or("Default Group"));
+        let base = format!("{name} {suffix}");
+        let mut candidate = base.clone();
+        let mut counter = 2;
+        while self.get_profile(&candidate).is_some() {
+            candidate = format!("{base} {counter}");
+            counter += 1;
+        }
+        candidate
+    }
+
+    /// Builds the Tabby profile of a connection, without id, group and jump host.
+    ///
+    /// # Arguments
+    /// * `profile` - The connection to convert.
+    /// * `name` - Name of the Tabby profile.
+    /// # Returns
+    /// * `Profile` - The profile of the protocol type with its connection and SSH options and the source id.
+    fn build_profile(&self, profile: &Connection, name: &str) -> Profile {
+        let port = resolve_port(profile);
+        let tabby_auth = resolve_auth(profile);
+
+        // Create new ProfileOptions and Profile
+        let mut new_profile_options = ProfileOptions {
+            host: profile.host.clone(),
+            user: profile.user.clone(),
+            algorithms: Some(serde_yaml_ng::Value::Mapping(Default::default())),
+            input: Some(serde_yaml_ng::Value::Mapping(Default::default())),
+            auth: tabby_auth,
+            port: Some(Value::from(port)),
+            ..Default::default()
+        };
+        if profile.protocol == ConnectionProtocols::Ssh {
+            TabbyConfig::apply_ssh_options(&mut new_profile_options, profile);
+        }
+
+        let defaults = Profile::default();
+        Profile {
+            r#type: Some(profile.protocol.as_str().to_string()),
+            name: name.to_string(),
+            icon: profile.icon.clone().or(defaults.icon),
+            options: Some(new_profile_options),
+            weight: profile.weight.or(defaults.weight),
+            color: profile.color.clone().or(defaults.color),
+            remmina_source: profile.source_id.clone(),
+            ..defaults
+        }
+    }
+
+    /// Returns the id of the group of a connection, creating the group if needed.
+    fn add_profile_group(&mut self, profile: &Connection) -> String {
+        let group_id = self.add_group(profile.group.as_deref().unwrap_or("Default Group"));
+        eprintln!(" └── Using group id: {:?} - name: {:?}", group_id, profile.group.as_deref().unwrap_or("Default Group"));
+        group_id
+    }
+
+    /// Updates a previously imported profile from its connection.
+    ///
+    /// # Arguments
+    /// * `index` - Index of the Tabby profile in `profiles`.
+    /// * `profile` - The connection it was imported from.
+    /// # Returns
+    /// * `SyncedProfile` - The profile id and name, with the names of the changed fields (empty if up to date).
+    /// # Behavior
+    /// * Only host, port, user, auth and group are synced, other settings edited in Tabby are kept
+    fn sync_profile(&mut self, index: usize, profile: &Connection) -> SyncedProfile {
+        let port = resolve_port(profile);
+        let auth = resolve_auth(profile);
+        let group_id = self.add_group(profile.group.as_deref().unwrap_or("Default Group"));
+
+        let existing = &mut self.profiles[index];
+        let options = existing.options_mut();
+        let mut changed = Vec::new();
+        if options.host != profile.host {
+            options.host = profile.host.clone();
+            changed.push("host".to_string());
+        }
+        if options.port_number() != Some(port) {
+            options.port = Some(Value::from(port));
+            changed.push("port".to_string());
+        }
+        if options.user != profile.user {
+            options.user = profile.user.clone();
+            changed.push("user".to_string());
+        }
+        if options.auth != auth {
+            options.auth = auth;
+            changed.push("auth".to_string());
+        }
+        if existing.group.as_ref() != Some(&group_id) {
+            existing.group = Some(group_id);
+            changed.push("group".to_string());
+        }
+
+        SyncedProfile {
+            id: existing.id.clone().unwrap_or_default(),
+            name: existing.name.clone(),
+            changed,
+        }
+    }
+
+    /// Returns the id of a SSH profile matching the jump host, port and user, creating it if needed.
+    ///
+    /// # Arguments
+    /// * `jump_host` - The jump host of a connection.
+    /// # Returns
+    /// * `String` - The id to use as `options.jumpHost`.
+    /// # Behavior
+    /// * Any existing SSH profile (imported or created in Tabby) with the same host/port/user is reused
+    /// * Otherwise a profile named "host (jump host)" is created in the "Jump Hosts" group,
+    ///   so several connections sharing a bastion share the same jump host
+    /// * The jump host password, if any, is handed to `secret_sink` for a newly created jump host
+    fn find_or_add_jump_host(&mut self, jump_host: &JumpHost, secret_sink: &mut dyn SecretSink) -> String {
+        let existing = self.profiles.iter().find(|p| {
+            p.profile_type() == "ssh"
+                && p.options().host.as_deref() == Some(jump_host.host.as_str())
+                && p.options().port_number().unwrap_or(22) == jump_host.port
+                && p.options().user == jump_host.user
+        });
+        if let Some(id) = existing.and_then(|p| p.id.clone()) {
+            eprintln!(" └── Using existing jump host: {id}");
+            return id;
+        }
+
+        let name = format!("{} (jump host)", jump_host.host);
+        let profile_id = self.generate_profile_uuid("ssh", "custom", &name);
+        let group_id = self.add_group("Jump Hosts");
+
+        let mut options = ProfileOptions {
+            host: Some(jump_host.host.clone()),
+            user: jump_host.user.clone(),
+            port: Some(Value::from(jump_host.port)),
+            algorithms: Some(Value::Mapping(Default::default())),
+            input: Some(Value::Mapping(Default::default())),
+            auth: jump_host.auth.and_then(tabby_auth).map(str::to_string),
+            ..Default::default()
+        };
+        if let Some(key) = &jump_host.private_key {
+            options.private_keys = Some(vec![private_key_uri(key)]);
+        }
+
+        eprintln!(" └── Created jump host '{name}': {profile_id}");
+        let profile = Profile {
+            name,
+            options: Some(options),
+            group: Some(group_id),
+            id: Some(profile_id.clone()),
+            ..Default::default()
+        };
+        if let Some(password) = &jump_host.password {
+            secret_sink.store(&profile, SecretKind::Password, password);
+        }
+        self.add_profile(profile);
+        profile_id
+    }
+
+    /// Maps the SSH settings of a connection onto Tabby SSH profile options.
+    ///
+    /// # Arguments
+    /// * `options` - The Tabby options being built for the imported profile.
+    /// * `profile` - The connection being imported.
+    /// # Behavior
+    /// * private_key -> privateKeys (as file:// URI)
+    /// * proxy_command -> proxyCommand
+    /// * kex_algorithms / ciphers / host_key_types -> algorithms.kex / cipher / serverHostKey
+    /// * charset -> input.encoding
+    /// * remote_command -> scripts (a script without "expect" is sent right after login)
+    /// * forward_x11 -> x11
+    /// * compression and strict_host_key_check have no per profile equivalent in Tabby and are only reported
+    fn apply_ssh_options(options: &mut ProfileOptions, profile: &Connection) {
+        if let Some(key) = &profile.private_key {
+            eprintln!(" └── Private key: {key}");
+            options.private_keys = Some(vec![private_key_uri(key)]);
+        }
+
+        if let Some(command) = &profile.proxy_command {
+            eprintln!(" └── Proxy command: {command}");
+            options.proxy_command = Some(command.clone());
+        }
+
+        let mut algorithms = serde_yaml_ng::Mapping::new();
+        let lists = [
+            ("kex", &profile.kex_algorithms),
+            ("cipher", &profile.ciphers),
+            ("serverHostKey", &profile.host_key_types),
+        ];
+        for (key, list) in lists {
+            if !list.is_empty() {
+                eprintln!(" └── Algorithms {key}: {}", list.join(","));
+                let values: Vec<Value> = list.iter().map(|a| Value::from(a.as_str())).collect();
+                algorithms.insert(Value::from(key), Value::Sequence(values));
+            }
+        }
+        options.algorithms = Some(Value::Mapping(algorithms));
+
+        if let Some(charset) = &profile.charset {
+            eprintln!(" └── Charset: {charset}");
+            let mut input = serde_yaml_ng::Mapping::new();
+            input.insert(Value::from("encoding"), Value::from(charset.as_str()));
+            options.input = Some(Value::Mapping(input));
+        }
+
+        if let Some(exec) = &profile.remote_command {
+            eprintln!(" └── Login script: {exec}");
+            let mut script = serde_yaml_ng::Mapping::new();
+            script.insert(Value::from("expect"), Value::from(""));
+            script.insert(Value::from("send"), Value::from(exec.as_str()));
+            script.insert(Value::from("isRegex"), Value::from(false));
+            script.insert(Value::from("optional"), Value::from(false));
+            options.scripts = Some(Value::Sequence(vec![Value::Mapping(script)]));
+        }
+
+        if profile.forward_x11 == Some(true) {
+            options.x11 = Some(true);
+        }
+
+        if profile.compression == Some(true) {
+            eprintln!(" └── Note: SSH compression is not configurable per profile in Tabby, ignored.");
+        }
+        if profile.strict_host_key_check == Some(true) {
+            eprintln!(" └── Note: Strict host key checking is a global Tabby setting (ssh.verifyHostKeys), ignored.");
+        }
+    }
+
+    /// Converts the SSH profiles back into connections.
+    ///
+    /// # Returns
+    /// * `Vec<Connection>` - One connection per Tabby SSH profile, other profile types are skipped
+    /// # Behavior
+    /// * The group id is resolved back to the group name
+    /// * auth -> password, public key, agent, keyboard interactive; automatic selection (unset) leaves auth unset
+    /// * privateKeys (first one, without file://) -> private_key
+    /// * jumpHost -> jump host with the host, port, user, auth and private key of the jump host profile
+    /// * proxyCommand, algorithms, input.encoding, x11 and a login script without "expect" are mapped back,
+    ///   the reverse of `apply_ssh_options`
+    pub fn connections(&self) -> Vec<Connection> {
+        let groups = self.groups.as_deref().unwrap_or_default();
+        self.profiles.iter()
+            .filter(|p| p.profile_type() == "ssh")
+            .map(|p| {
+                let options = p.options();
+                let jump_host = options.jump_host.as_ref()
+                    .and_then(|id| self.profiles.iter().find(|j| j.id.as_ref() == Some(id)))
+                    .map(|jump| JumpHost {
+                        host: jump.options().host.clone().unwrap_or_default(),
+                        port: jump.options().port_number().unwrap_or(22),
+                        user: jump.options().user.clone(),
+                        auth: connection_auth(jump.options()),
+                        private_key: private_key_path(jump.options()),
+                        password: None,
+                    });
+                let algorithms = |key: &str| options.algorithms.as_ref()
+                    .and_then(|a| a.get(key))
+                    .and_then(Value::as_sequence)
+                    .map(|list| list.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                    .unwrap_or_default();
+                let exec = options.scripts.as_ref()
+                    .and_then(Value::as_sequence)
+                    .and_then(|scripts| scripts.iter().find(|s| s["expect"].as_str().unwrap_or_default().is_empty()))
+                    .and_then(|script| script["send"].as_str())
+                    .filter(|send| !send.is_empty())
+                    .map(str::to_string);
+
+                Connection {
+                    name: Some(p.name.clone()),
+                    host: options.host.clone(),
+                    port: options.port_number(),
+                    group: p.group.as_ref()
+                        .and_then(|id| groups.iter().find(|g| &g.id == id))
+                        .map(|g| g.name.clone()),
+                    protocol: ConnectionProtocols::Ssh,
+                    user: options.user.clone(),
+                    auth: connection_auth(options),
+                    private_key: private_key_path(options),
+                    proxy_command: options.proxy_command.clone().filter(|c| !c.is_empty()),
+                    charset: options.input.as_ref()
+                        .and_then(|input| input.get("encoding"))
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                    kex_algorithms: algorithms("kex"),
+                    ciphers: algorithms("cipher"),
+                    host_key_types: algorithms("serverHostKey"),
+                    remote_command: exec,
+                    forward_x11: options.x11.filter(|&x11| x11),
+                    jump_host,
+                    source_id: p.remmina_source.clone(),
                     ..Default::default()
-                };
-                self.add_profile(new_profile);
+                }
+            })
+            .collect()
+    }
+
+    /// Checks the configuration for inconsistencies Tabby would not report.
+    ///
+    /// # Returns
+    /// * `Vec<String>` - One message per problem, empty if the configuration is valid
+    /// # Behavior
+    /// * Profiles and groups must have unique ids, groups unique names
+    /// * Profile groups and jump hosts must reference existing groups and profiles
+    /// * SSH profiles must have a host and, if set, a numeric port
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let groups = self.groups.as_deref().unwrap_or_default();
+
+        let mut seen = std::collections::HashSet::new();
+        for id in self.profiles.iter().filter_map(|p| p.id.as_deref()) {
+            if !seen.insert(id) {
+                problems.push(format!("Duplicate profile id '{id}'"));
+            }
+        }
+        let mut seen = std::collections::HashSet::new();
+        for group in groups {
+            if !seen.insert(group.id.as_str()) {
+                problems.push(format!("Duplicate group id '{}'", group.id));
+            }
+        }
+        let mut seen = std::collections::HashSet::new();
+        for group in groups {
+            if !seen.insert(group.name.as_str()) {
+                problems.push(format!("Duplicate group name '{}'", group.name));
+            }
+        }
+
+        for profile in &self.profiles {
+            let name = &profile.name;
+            if let Some(group) = &profile.group
+                && !groups.iter().any(|g| &g.id == group) {
+                problems.push(format!("Profile '{name}' references missing group '{group}'"));
+            }
+            if let Some(jump_host) = &profile.options().jump_host
+                && !self.profiles.iter().any(|p| p.id.as_ref() == Some(jump_host)) {
+                problems.push(format!("Profile '{name}' references missing jump host '{jump_host}'"));
+            }
+            if profile.profile_type() == "ssh" {
+                if profile.options().host.as_deref().unwrap_or_default().is_empty() {
+                    problems.push(format!("SSH profile '{name}' has no host"));
+                }
+                if profile.options().port.is_some() && profile.options().port_number().is_none() {
+                    problems.push(format!("SSH profile '{name}' has an invalid port"));
+                }
             }
-            imported_count += 1;
         }
-        imported_count
+        problems
     }
 
     /// Saves the TabbyConfig as YAML to the given path.
@@ -308,14 +1060,125 @@ impl TabbyConfig {
     /// # Arguments
     /// * `path` - The file path where the YAML should be saved.
     /// # Returns
-    /// * `Result<(), String>` - Ok if successful, Err with error message if failed.
-    pub fn save_to_path(&self, path: &str) -> Result<(), String> {
+    /// * `Result<()>` - Ok if successful, Err if the config cannot be serialized or written.
+    pub fn save_to_path(&self, path: &str) -> Result<()> {
         let yaml = serde_yaml_ng::to_string(self)
-            .map_err(|e| format!("Error serializing config to YAML: {e}"))?;
+            .map_err(|e| Error::yaml(path, &e))?;
         std::fs::write(path, yaml)
-            .map_err(|e| format!("Error writing YAML to file: {e}"))?;
+            .map_err(|e| Error::io(path, e))?;
         Ok(())
     }
 
 
-}
\ No newline at end of file
+}
+
+/// Mapping value of a key, created empty if missing; None if the value is not a mapping
+fn mapping_entry<'a>(value: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+    let entry = value.as_mapping_mut()?
+        .entry(Value::from(key))
+        .or_insert_with(|| Value::Mapping(Default::default()));
+    entry.is_mapping().then_some(entry)
+}
+
+/// Hand secrets over to the sink, they never end up in the profile itself
+fn store_secrets(profile: &Connection, target: &Profile, secret_sink: &mut dyn SecretSink) {
+    if let Some(password) = &profile.password {
+        secret_sink.store(target, SecretKind::Password, password);
+    }
+    if let Some(passphrase) = &profile.key_passphrase {
+        secret_sink.store(target, SecretKind::KeyPassphrase, passphrase);
+    }
+}
+
+/// Names of the profile fields (options included) that differ between two serialized profiles
+fn changed_fields(before: &Value, after: &Value) -> Vec<String> {
+    fn keys(value: &Value) -> Vec<&Value> {
+        value.as_mapping().map(|m| m.keys().collect()).unwrap_or_default()
+    }
+    let mut changed = Vec::new();
+    let mut compare = |before: &Value, after: &Value, skip: &[&str]| {
+        let mut names: Vec<&Value> = keys(before);
+        names.extend(keys(after).into_iter().filter(|k| before.get(*k).is_none()));
+        for key in names {
+            let Some(name) = key.as_str() else { continue };
+            if !skip.contains(&name) && before.get(key) != after.get(key) {
+                changed.push(name.to_string());
+            }
+        }
+    };
+    compare(before, after, &["options", "id"]);
+    compare(&before["options"], &after["options"], &[]);
+    changed
+}
+
+/// Returns the port of a connection, or the default port of its protocol
+fn resolve_port(profile: &Connection) -> u16 {
+    match profile.port {
+        Some(port) => {
+            eprintln!(" └── Connection port: {port}");
+            port
+        }
+        None => {
+            let port = get_default_port_for_protocol(&profile.protocol);
+            eprintln!(" └── Connection port not set, using default for protocol [{:?}]: {port}", profile.protocol.as_str());
+            port
+        }
+    }
+}
+
+/// Maps the connection auth method onto the Tabby SSH auth, None lets Tabby try every method
+///
+/// Notes:
+///     A SSH connection without auth method is written without one (auto), never as password
+fn resolve_auth(profile: &Connection) -> Option<String> {
+    if profile.protocol == ConnectionProtocols::Ssh {
+        let auth = profile.auth.and_then(tabby_auth);
+        eprintln!(" └── Connection SSH auth method: {:?} -> Tabby: {:?}",
+                  profile.auth.as_ref().map_or("auto", AuthMethod::as_str), auth.unwrap_or("auto"));
+        auth.map(str::to_string)
+    } else {
+        if let Some(auth) = profile.auth {
+            eprintln!(" └── Note: Auth method '{}' specified but protocol is not SSH, auth method will be ignored.", auth.as_str());
+        } else {
+            eprintln!(" └── No auth method specified and protocol is not SSH, defaulting to 'password' (will be ignored).");
+        }
+        None
+    }
+}
+
+/// Tabby SSH auth of an auth method, None (automatic selection) for Kerberos which Tabby does not support
+fn tabby_auth(method: AuthMethod) -> Option<&'static str> {
+    match method {
+        AuthMethod::Password => Some("password"),
+        AuthMethod::PublicKey => Some("publicKey"),
+        AuthMethod::Agent => Some("agent"),
+        AuthMethod::KeyboardInteractive => Some("keyboardInteractive"),
+        AuthMethod::Gssapi => None,
+    }
+}
+
+/// Auth method of a Tabby SSH profile, the reverse of `tabby_auth`; None for automatic selection
+fn connection_auth(options: &ProfileOptions) -> Option<AuthMethod> {
+    match options.auth.as_deref()? {
+        "password" => Some(AuthMethod::Password),
+        "publicKey" => Some(AuthMethod::PublicKey),
+        "agent" => Some(AuthMethod::Agent),
+        "keyboardInteractive" => Some(AuthMethod::KeyboardInteractive),
+        _ => None,
+    }
+}
+
+/// First private key of a Tabby profile as a path, without the file:// scheme
+fn private_key_path(options: &ProfileOptions) -> Option<String> {
+    let key = options.private_keys.as_ref()?.first()?;
+    Some(key.strip_prefix("file://").unwrap_or(key).to_string())
+}
+
+/// Tabby references private key files as file:// URIs
+fn private_key_uri(path: &str) -> String {
+    if path.starts_with("file://") {
+        path.to_string()
+    } else {
+        format!("file://{path}")
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use aes::Aes256;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit, block_padding::Pkcs7};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use serde_yaml_ng::Value;
+use sha2::{Digest, Sha512};
+
+use crate::error::{Error, Result};
+use crate::remmina_secrets::{Secret, SecretKind, SecretSink};
+use crate::tabby_parser::Profile;
+
+/// Vault parameters, they must match Tabby's VaultService
+const PBKDF_ITERATIONS: u32 = 100_000;
+const PBKDF_SALT_LENGTH: usize = 8;
+const CRYPT_KEY_LENGTH: usize = 32;
+const CRYPT_IV_LENGTH: usize = 16;
+const VAULT_VERSION: u32 = 1;
+
+/// Secret types used by Tabby's SSH plugin
+pub const VAULT_SECRET_TYPE_PASSWORD: &str = "ssh:password";
+pub const VAULT_SECRET_TYPE_PASSPHRASE: &str = "ssh:key-passphrase";
+
+type Aes256CbcEncryptor = cbc::Encryptor<Aes256>;
+type Aes256CbcDecryptor = cbc::Decryptor<Aes256>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The `vault` section of config.yaml, as stored by Tabby
+pub struct StoredVault {
+    pub version: u32,
+    /// Base64 AES-256-CBC ciphertext of the JSON vault content
+    pub contents: String,
+    /// Hex PBKDF2 salt
+    #[serde(rename = "keySalt")]
+    pub key_salt: String,
+    /// Hex AES IV
+    pub iv: String,
+
+    #[serde(flatten)]
+    extra: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A secret inside the vault, Tabby looks it up by type and key
+pub struct VaultSecret {
+    pub r#type: String,
+    pub key: JsonValue,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Decrypted vault content
+struct VaultContent {
+    #[serde(default)]
+    config: JsonValue,
+    #[serde(default)]
+    secrets: Vec<VaultSecret>,
+    #[serde(default)]
+    settings: JsonValue,
+
+    #[serde(flatten)]
+    extra: serde_json::Map<String, JsonValue>,
+}
+
+/// An unlocked Tabby vault
+///
+/// Notes:
+///     Tabby derives the key with PBKDF2-HMAC-SHA512 (100000 iterations, 8 bytes salt)
+///     and encrypts the JSON content with AES-256-CBC, see tabby-core VaultService.
+///     The vault is re-encrypted with a fresh salt and IV by `seal`.
+///     Secrets are keyed the way Tabby's SSH plugin looks them up:
+///         - ssh:password by {user, host, port} of the profile
+///         - ssh:key-passphrase by {hash} = SHA-512 hex digest of the private key file content
+pub struct TabbyVault {
+    content: VaultContent,
+    passphrase: String,
+    /// Number of secrets added or replaced since unlock
+    pub added: usize,
+}
+
+/// Methods for TabbyVault
+impl TabbyVault {
+    /// Create a new empty vault protected by `passphrase`
+    pub fn create(passphrase: &str) -> Self {
+        TabbyVault {
+            content: VaultContent {
+                config: JsonValue::Null,
+                secrets: Vec::new(),
+                settings: json!({}),
+                extra: serde_json::Map::new(),
+            },
+            passphrase: passphrase.to_string(),
+            added: 0,
+        }
+    }
+
+    /// Decrypt an existing vault
+    ///
+    /// # Arguments
+    /// * `stored` - The vault section of config.yaml
+    /// * `passphrase` - The vault passphrase
+    /// # Returns
+    /// * `Result<Self>` - The unlocked vault, or a Secret error if the passphrase is wrong or the vault is corrupted
+    pub fn unlock(stored: &StoredVault, passphrase: &str) -> Result<Self> {
+        if stored.version != VAULT_VERSION {
+            return Err(Error::Secret(format!("Unsupported Tabby vault version {}", stored.version)));
+        }
+        let salt = decode_hex(&stored.key_salt).ok_or_else(|| Error::Secret("Invalid vault keySalt".to_string()))?;
+        let iv = decode_hex(&stored.iv).ok_or_else(|| Error::Secret("Invalid vault iv".to_string()))?;
+        let mut buffer = BASE64.decode(stored.contents.trim())
+            .map_err(|e| Error::Secret(format!("Invalid vault contents: {e}")))?;
+
+        let key = derive_key(passphrase, &salt);
+        let wrong_passphrase = || Error::Secret("Cannot unlock Tabby vault: wrong passphrase?".to_string());
+        let decryptor = Aes256CbcDecryptor::new_from_slices(&key, &iv)
+            .map_err(|_| Error::Secret("Invalid vault iv length".to_string()))?;
+        let plaintext = decryptor.decrypt_padded_mut::<Pkcs7>(&mut buffer)
+            .map_err(|_| wrong_passphrase())?;
+        let content: VaultContent = serde_json::from_slice(plaintext)
+            .map_err(|_| wrong_passphrase())?;
+
+        Ok(TabbyVault { content, passphrase: passphrase.to_string(), added: 0 })
+    }
+
+    /// Encrypt the vault content with a fresh salt and IV
+    ///
+    /// # Returns
+    /// * `Result<StoredVault>` - The vault section to store in config.yaml
+    pub fn seal(&self) -> Result<StoredVault> {
+        let mut salt = [0u8; PBKDF_SALT_LENGTH];
+        let mut iv = [0u8; CRYPT_IV_LENGTH];
+        getrandom::fill(&mut salt).map_err(|e| Error::Secret(format!("Cannot generate vault salt: {e}")))?;
+        getrandom::fill(&mut iv).map_err(|e| Error::Secret(format!("Cannot generate vault iv: {e}")))?;
+
+        let plaintext = serde_json::to_vec(&self.content)
+            .map_err(|e| Error::Secret(format!("Error serializing vault: {e}")))?;
+        let key = derive_key(&self.passphrase, &salt);
+        // PKCS#7 always adds between 1 and 16 bytes of padding
+        let mut buffer = plaintext.clone();
+        buffer.resize(plaintext.len() + CRYPT_IV_LENGTH, 0);
+        let ciphertext = Aes256CbcEncryptor::new_from_slices(&key, &iv)
+            .map_err(|_| Error::Secret("Invalid vault key length".to_string()))?
+            .encrypt_padded_mut::<Pkcs7>(&mut buffer, plaintext.len())
+            .map_err(|_| Error::Secret("Error encrypting vault".to_string()))?;
+
+        Ok(StoredVault {
+            version: VAULT_VERSION,
+            contents: BASE64.encode(ciphertext),
+            key_salt: encode_hex(&salt),
+            iv: encode_hex(&iv),
+            extra: BTreeMap::new(),
+        })
+    }
+
+    /// Add a secret, replacing an existing one with the same type and key
+    pub fn add_secret(&mut self, r#type: &str, key: JsonValue, value: &str) {
+        self.content.secrets.retain(|s| !(s.r#type == r#type && s.key == key));
+        self.content.secrets.push(VaultSecret {
+            r#type: r#type.to_string(),
+            key,
+            value: value.to_string(),
+        });
+        self.added += 1;
+    }
+}
+
+/// Store imported secrets in the vault, keyed as Tabby's SSH plugin expects
+impl SecretSink for TabbyVault {
+    fn store(&mut self, profile: &Profile, kind: SecretKind, secret: &Secret) {
+        let name = &profile.name;
+        match kind {
+            SecretKind::Password => {
+                let key = json!({
+                    "user": profile.options().user,
+                    "host": profile.options().host,
+                    "port": profile.options().port_number().unwrap_or(22),
+                });
+                self.add_secret(VAULT_SECRET_TYPE_PASSWORD, key, secret.expose());
+                eprintln!(" └── 🔒 Password of '{name}' stored in Tabby vault");
+            }
+            SecretKind::KeyPassphrase => {
+                let key_file = profile.options().private_keys.as_ref()
+                    .and_then(|keys| keys.first())
+                    .map(|uri| uri.strip_prefix("file://").unwrap_or(uri).to_string());
+                match key_file.as_deref().map(fs::read) {
+                    Some(Ok(content)) => {
+                        let hash = encode_hex(&Sha512::digest(&content));
+                        self.add_secret(VAULT_SECRET_TYPE_PASSPHRASE, json!({ "hash": hash }), secret.expose());
+                        eprintln!(" └── 🔒 Key passphrase of '{name}' stored in Tabby vault");
+                    }
+                    Some(Err(e)) => {
+                        eprintln!("Warning: cannot read private key of '{name}' ({e}), key passphrase not stored");
+                    }
+                    None => {
+                        eprintln!("Warning: '{name}' has a key passphrase but no private key, passphrase not stored");
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; CRYPT_KEY_LENGTH] {
+    let mut key = [0u8; CRYPT_KEY_LENGTH];
+    pbkdf2::pbkdf2_hmac::<Sha512>(passphrase.as_bytes(), salt, PBKDF_ITERATIONS, &mut key);
+    key
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex = hex.trim();
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::connection::Connection;
+use crate::error::{Error, Result};
+use crate::protocols_types::ConnectionProtocols;
+use crate::putty_parser::decode_text;
+
+/// Scheme of the source ids of the Termius hosts ("termius:<id>"), see `source_scheme`
+pub const TERMIUS_SCHEME: &str = "termius";
+
+/// A Termius JSON export: groups and hosts
+#[derive(Debug, Deserialize)]
+struct Export {
+    #[serde(default)]
+    groups: Vec<Group>,
+    hosts: Vec<Host>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Group {
+    id: Value,
+    #[serde(default)]
+    label: String,
+    parent_group: Option<Value>,
+    ssh_config: Option<Settings>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Host {
+    id: Option<Value>,
+    label: Option<String>,
+    address: Option<String>,
+    group: Option<Value>,
+    ssh_config: Option<Settings>,
+    telnet_config: Option<Settings>,
+}
+
+/// SSH or Telnet settings of a host or group, the SSH settings of a group apply to its hosts
+#[derive(Debug, Deserialize)]
+struct Settings {
+    port: Option<u16>,
+    identity: Option<Identity>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Identity {
+    username: Option<String>,
+    ssh_key: Option<SshKey>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SshKey {
+    label: Option<String>,
+}
+
+/// Hosts of a Termius export, read as connections
+#[derive(Debug, Default)]
+pub struct TermiusHosts {
+    /// One profile per host, in file order
+    pub profiles: Vec<Connection>,
+    /// Source ids of every host, including the ones whose protocol is filtered out later
+    pub sources: HashSet<String>,
+}
+
+/// Methods for TermiusHosts
+impl TermiusHosts {
+    /// Read a Termius JSON export ({"groups": [...], "hosts": [...]})
+    ///
+    /// # Arguments
+    /// * `path` - The JSON file
+    /// # Returns
+    /// * `Result<TermiusHosts>` - The hosts, Io if the file cannot be read,
+    ///   InvalidSessionFile if it is not valid JSON or has no "hosts" list
+    /// # Behavior
+    /// * The `group` of a host and the `parent_group` of the groups give the group path ("Prod/DB")
+    /// * Port and username come from the `ssh_config` of the host, or else of its groups, nearest first
+    /// * Hosts with only a `telnet_config` are Telnet hosts, the others SSH hosts
+    ///
+    /// Notes:
+    ///     SSH keys are stored in Termius, not as files, they are not imported
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path).map_err(|e| Error::io(path, e))?;
+        let export: Export = serde_json::from_str(&decode_text(&bytes)).map_err(|e| Error::InvalidSessionFile {
+            path: path.to_path_buf(),
+            line: Some(e.line()),
+            message: format!("not a Termius export: {e}"),
+        })?;
+        let groups: HashMap<String, &Group> = export.groups.iter().map(|g| (id_key(&g.id), g)).collect();
+
+        let mut result = TermiusHosts::default();
+        for host in &export.hosts {
+            // The groups of the host, nearest first; a loop in the parents ends the chain
+            let mut chain: Vec<&Group> = Vec::new();
+            let mut next = host.group.as_ref().map(id_key);
+            while let Some(group) = next.and_then(|id| groups.get(&id).copied()) {
+                if chain.iter().any(|g| std::ptr::eq(*g, group)) {
+                    break;
+                }
+                chain.push(group);
+                next = group.parent_group.as_ref().map(id_key);
+            }
+            let labels: Vec<&str> = chain.iter().rev().map(|g| g.label.trim()).filter(|l| !l.is_empty()).collect();
+            let group = (!labels.is_empty()).then(|| labels.join("/"));
+
+            let protocol = match (&host.ssh_config, &host.telnet_config) {
+                (None, Some(_)) => ConnectionProtocols::Telnet,
+                _ => ConnectionProtocols::Ssh,
+            };
+            let settings: Vec<&Settings> = match protocol {
+                ConnectionProtocols::Ssh => host.ssh_config.iter().chain(chain.iter().filter_map(|g| g.ssh_config.as_ref())).collect(),
+                _ => host.telnet_config.iter().collect(),
+            };
+            let identity = || settings.iter().filter_map(|s| s.identity.as_ref());
+
+            let address = host.address.as_deref().map(str::trim).filter(|a| !a.is_empty());
+            let name = host.label.as_deref().map(str::trim).filter(|l| !l.is_empty()).or(address).unwrap_or_default().to_string();
+            if let Some(key) = identity().find_map(|i| i.ssh_key.as_ref()) {
+                eprintln!(" └── Termius host '{name}' uses the key '{}' stored in Termius, not imported.", key.label.as_deref().unwrap_or_default());
+            }
+            let source = match &host.id {
+                Some(id) => format!("{TERMIUS_SCHEME}:{}", id_key(id)),
+                None => format!("{TERMIUS_SCHEME}:{}", group.as_ref().map_or(name.clone(), |g| format!("{g}/{name}"))),
+            };
+            result.sources.insert(source.clone());
+            result.profiles.push(Connection {
+                name: Some(name),
+                host: address.map(str::to_string),
+                port: settings.iter().find_map(|s| s.port),
+                group,
+                protocol,
+                user: identity().find_map(|i| i.username.clone()).filter(|u| !u.is_empty()),
+                source_id: Some(source),
+                path: path.to_path_buf(),
+                ..Default::default()
+            });
+        }
+        Ok(result)
+    }
+}
+
+/// An id of the export, numbers and strings alike
+fn id_key(id: &Value) -> String {
+    match id {
+        Value::String(id) => id.clone(),
+        other => other.to_string(),
+    }
+}
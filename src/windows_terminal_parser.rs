@@ -0,0 +1,166 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::connection::Connection;
+use crate::error::{Error, Result};
+use crate::putty_parser::decode_text;
+use crate::report::{RunReport, SkippedFile};
+use crate::ssh_config_parser::{command_line_profile, split_arguments};
+
+/// Scheme of the source ids of the Windows Terminal profiles ("windows-terminal:{guid}"), see `source_scheme`
+pub const WINDOWS_TERMINAL_SCHEME: &str = "windows-terminal";
+
+/// settings.json, only the profiles are read
+#[derive(Debug, Deserialize)]
+struct Settings {
+    profiles: ProfileList,
+}
+
+/// The profiles: {"defaults": ..., "list": [...]}, or the list alone in older settings
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ProfileList {
+    Object {
+        #[serde(default)]
+        list: Vec<TerminalProfile>,
+    },
+    List(Vec<TerminalProfile>),
+}
+
+/// A profile of the new tab menu
+#[derive(Debug, Deserialize)]
+struct TerminalProfile {
+    #[serde(default)]
+    name: String,
+    guid: Option<String>,
+    commandline: Option<String>,
+}
+
+/// The ssh profiles of a Windows Terminal settings.json, read as SSH connections
+#[derive(Debug, Default)]
+pub struct WindowsTerminalProfiles {
+    /// One profile per ssh command line, in file order
+    pub profiles: Vec<Connection>,
+    /// Source ids of every ssh profile
+    pub sources: HashSet<String>,
+}
+
+/// Methods for WindowsTerminalProfiles
+impl WindowsTerminalProfiles {
+    /// Read the profiles of a settings.json (%LOCALAPPDATA%\Packages\Microsoft.WindowsTerminal_8wekyb3d8bbwe\LocalState)
+    ///
+    /// # Arguments
+    /// * `path` - The settings.json file, comments and trailing commas are accepted
+    /// * `report` - Receives the profiles running another program than ssh, and the ssh command lines
+    ///   that cannot be parsed (no destination, option without its value)
+    /// # Returns
+    /// * `Result<WindowsTerminalProfiles>` - The profiles, Io if the file cannot be read,
+    ///   InvalidSessionFile if it is not valid JSON or has no "profiles"
+    /// # Behavior
+    /// * A profile whose `commandline` runs ssh (or ssh.exe) is a connection, see `command_line_profile`
+    /// * Profiles without `commandline` (generated shells: PowerShell, WSL, Azure Cloud Shell) are ignored
+    pub fn load(path: &Path, report: &mut RunReport) -> Result<Self> {
+        let bytes = fs::read(path).map_err(|e| Error::io(path, e))?;
+        let content = strip_json_comments(&decode_text(&bytes));
+        let invalid = |line: Option<usize>, message: String| Error::InvalidSessionFile { path: path.to_path_buf(), line, message };
+        let settings: Settings = serde_json::from_str(&content).map_err(|e| invalid(Some(e.line()), format!("invalid settings: {e}")))?;
+        let list = match settings.profiles {
+            ProfileList::Object { list } | ProfileList::List(list) => list,
+        };
+
+        let mut result = WindowsTerminalProfiles::default();
+        for terminal_profile in list {
+            let Some(commandline) = terminal_profile.commandline.as_deref() else { continue };
+            let name = terminal_profile.name.trim();
+            let mut args = split_arguments(commandline);
+            if args.is_empty() {
+                continue;
+            }
+            let program = args.remove(0);
+            if !is_ssh(&program) {
+                eprintln!(" └── Windows Terminal profile '{name}' runs {program}, skipped.");
+                let error = Error::UnsupportedProtocol { path: path.to_path_buf(), protocol: format!("command line {program} ('{name}')") };
+                report.skipped_files.push(SkippedFile::from_error(path, &error));
+                continue;
+            }
+            let mut profile = match command_line_profile(&args) {
+                Ok(profile) => profile,
+                Err(message) => {
+                    eprintln!(" └── Windows Terminal profile '{name}': {message}, skipped.");
+                    report.skipped_files.push(SkippedFile::from_error(path, &invalid(None, format!("profile '{name}': {message}"))));
+                    continue;
+                }
+            };
+            let id = terminal_profile.guid.as_deref().unwrap_or(name).trim_matches(['{', '}']);
+            let source = format!("{WINDOWS_TERMINAL_SCHEME}:{id}");
+            if !name.is_empty() {
+                profile.name = Some(name.to_string());
+            }
+            profile.source_id = Some(source.clone());
+            profile.path = path.to_path_buf();
+            result.sources.insert(source);
+            result.profiles.push(profile);
+        }
+        Ok(result)
+    }
+}
+
+/// Whether a program is ssh: "ssh", "ssh.exe" or a path to one of them
+fn is_ssh(program: &str) -> bool {
+    let file = program.rsplit(['\\', '/']).next().unwrap_or_default().to_lowercase();
+    file == "ssh" || file == "ssh.exe"
+}
+
+/// The JSON of a settings file without its comments and trailing commas
+///
+/// Notes:
+///     Comments become spaces and keep their line breaks, so that errors give the line of the file
+fn strip_json_comments(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            result.push(c);
+            match c {
+                '\\' => result.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                result.push(c);
+            }
+            ('/', Some('/')) => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    result.push(if c == '\n' { '\n' } else { ' ' });
+                    previous = c;
+                }
+            }
+            (c @ ('}' | ']'), _) => {
+                // A comma followed only by whitespace before the closing bracket
+                let trimmed = result.trim_end().len();
+                if result[..trimmed].ends_with(',') {
+                    result.replace_range(trimmed - 1..trimmed, " ");
+                }
+                result.push(c);
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use std::fs;
+
+use common::{copy_fixture, run_converter, work_dir};
+
+#[test]
+fn check_reports_files_that_cannot_be_imported() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+    let output = run_converter(["check", "--remmina-dir", &format!("{fixtures}/remmina")]);
+    assert_eq!(output.status.code(), Some(0));
+
+    // One of the keyfile fixtures has no [remmina] section
+    let output = run_converter(["check", "--remmina-dir", &format!("{fixtures}/remmina_keyfile")]);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn validate_exit_code_reflects_config_problems() {
+    let work_dir = work_dir("cli-validate");
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+
+    let output = run_converter(["validate".as_ref(), "--tabby-dir".as_ref(), tabby_dir.as_os_str()]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stdout));
+
+    let broken = "version: 7\nprofiles:\n  - type: ssh\n    name: orphan\n    id: ssh:orphan\n    group: missing-group\n    options:\n      host: orphan.example.com\ngroups: []\n";
+    fs::write(tabby_dir.join("config.yaml"), broken).unwrap();
+
+    let output = run_converter(["validate".as_ref(), "--tabby-dir".as_ref(), tabby_dir.as_os_str()]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("missing group 'missing-group'"));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn diff_lists_pending_changes_without_saving() {
+    let work_dir = work_dir("cli-diff");
+    let remmina_dir = copy_fixture("remmina", &work_dir);
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+    let original = fs::read_to_string(tabby_dir.join("config.yaml")).unwrap();
+
+    let diff = || run_converter([
+        "diff".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+    ]);
+
+    let output = diff();
+    assert_eq!(output.status.code(), Some(5));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("+ profile 'web-01'"));
+    assert_eq!(fs::read_to_string(tabby_dir.join("config.yaml")).unwrap(), original);
+
+    let output = run_converter([
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--execute".as_ref(),
+        "--yes".as_ref(),
+    ]);
+    assert!(output.status.success());
+
+    // Nothing left to import
+    assert_eq!(diff().status.code(), Some(0));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn export_writes_remmina_files_only_with_execute() {
+    let work_dir = work_dir("cli-export");
+    let tabby_dir = copy_fixture("tabby", &work_dir);
+    let remmina_dir = work_dir.join("remmina");
+    fs::create_dir_all(&remmina_dir).unwrap();
+
+    let export = |execute: bool| {
+        let mut args = vec![
+            "export".as_ref(),
+            "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+            "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        ];
+        if execute {
+            args.push("--execute".as_ref());
+        }
+        run_converter(args)
+    };
+
+    assert!(export(false).status.success());
+    assert_eq!(fs::read_dir(&remmina_dir).unwrap().count(), 0);
+
+    assert!(export(true).status.success());
+    let written = fs::read_dir(&remmina_dir).unwrap().count();
+    assert!(written > 0);
+
+    // The exported files can be read back
+    let output = run_converter(["check".as_ref(), "--remmina-dir".as_ref(), remmina_dir.as_os_str()]);
+    assert_eq!(output.status.code(), Some(0));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn missing_subcommand_is_a_usage_error() {
+    assert_eq!(run_converter(["--execute"]).status.code(), Some(2));
+}
@@ -0,0 +1,75 @@
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use serde_yaml_ng::Value;
+
+/// Create an empty temporary working directory unique to this test
+pub fn work_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("remmina-to-tabby-{test_name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Copy a fixture directory into the working directory
+pub fn copy_fixture(name: &str, dest: &Path) -> PathBuf {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+    let target = dest.join(name);
+    fs::create_dir_all(&target).unwrap();
+    for entry in fs::read_dir(&src).unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), target.join(entry.file_name())).unwrap();
+    }
+    target
+}
+
+/// Run the converter binary with the given arguments
+pub fn run_converter<I, S>(args: I) -> Output
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<std::ffi::OsStr>,
+{
+    Command::new(env!("CARGO_BIN_EXE_remmina-to-tabby"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+pub fn load_yaml(path: &Path) -> Value {
+    serde_yaml_ng::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// Find a profile by name in a Tabby config
+pub fn find_profile<'a>(config: &'a Value, name: &str) -> Option<&'a Value> {
+    config["profiles"].as_sequence()?.iter().find(|p| p["name"] == name)
+}
+
+/// Decrypt the vault section of a Tabby config, independently of the converter code
+///
+/// Notes:
+///     Follows Tabby's VaultService: PBKDF2-HMAC-SHA512 (100000 iterations) key, AES-256-CBC JSON content
+pub fn decrypt_vault(vault: &Value, passphrase: &str) -> serde_json::Value {
+    use base64::Engine;
+    use cbc::cipher::{BlockDecryptMut, KeyIvInit, block_padding::Pkcs7};
+
+    let hex = |value: &Value| -> Vec<u8> {
+        let s = value.as_str().unwrap();
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    };
+    let salt = hex(&vault["keySalt"]);
+    let iv = hex(&vault["iv"]);
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha512>(passphrase.as_bytes(), &salt, 100_000, &mut key);
+
+    let mut buffer = base64::engine::general_purpose::STANDARD
+        .decode(vault["contents"].as_str().unwrap())
+        .unwrap();
+    let plaintext = cbc::Decryptor::<aes::Aes256>::new_from_slices(&key, &iv)
+        .unwrap()
+        .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+        .unwrap();
+    serde_json::from_slice(plaintext).unwrap()
+}
@@ -0,0 +1,183 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+use remmina_to_tabby::tabby_parser::{ConflictStrategy, ImportOptions, Profile};
+use remmina_to_tabby::{Connection, ConvertOptions, Error, Plan, Result, TabbyConfig, convert};
+
+/// A Tabby config with a manually created 'web-01' profile (no user) and 'legacy' on the host of the Remmina one
+const EXISTING: &str = "version: 7
+profiles:
+  - type: ssh
+    name: web-01
+    id: ssh:custom:web-01:manual
+    options:
+      host: web-01.internal
+  - type: ssh
+    name: legacy
+    id: ssh:custom:legacy:manual
+    options:
+      host: web-01.example.com
+      port: 22
+      user: deploy
+groups: []
+";
+
+fn remmina_fixture() -> &'static str {
+    concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/remmina")
+}
+
+fn import(work_dir: &Path, import: ImportOptions) -> (Result<Plan>, TabbyConfig) {
+    fs::write(work_dir.join("config.yaml"), EXISTING).unwrap();
+    let mut config = TabbyConfig::load_from_dir(work_dir.to_str().unwrap()).unwrap();
+    let options = ConvertOptions { import, ..Default::default() };
+    let plan = convert(remmina_fixture(), &mut config, options);
+    (plan, config)
+}
+
+fn strategy(on_conflict: ConflictStrategy) -> ImportOptions {
+    ImportOptions { on_conflict, ..Default::default() }
+}
+
+#[test]
+fn skip_keeps_the_existing_profile_and_records_the_decision() {
+    let work_dir = work_dir("conflict-skip");
+    let (plan, config) = import(&work_dir, ImportOptions::default());
+    let plan = plan.unwrap();
+
+    assert!(plan.created_profiles.is_empty());
+    assert_eq!(plan.skipped_profiles[0].kind, "conflict");
+    assert_eq!(plan.conflicts[0].existing_id, "ssh:custom:web-01:manual");
+    assert_eq!(plan.conflicts[0].matched_by, "name");
+    assert_eq!(plan.conflicts[0].decision, ConflictStrategy::Skip);
+    assert_eq!(config.get_profile("web-01").unwrap().options().host.as_deref(), Some("web-01.internal"));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn overwrite_replaces_the_profile_and_keeps_its_id() {
+    let work_dir = work_dir("conflict-overwrite");
+    let (plan, config) = import(&work_dir, strategy(ConflictStrategy::Overwrite));
+    let plan = plan.unwrap();
+
+    let profile = config.get_profile("web-01").unwrap();
+    assert_eq!(profile.id.as_deref(), Some("ssh:custom:web-01:manual"));
+    assert_eq!(profile.options().host.as_deref(), Some("web-01.example.com"));
+    assert_eq!(profile.options().user.as_deref(), Some("deploy"));
+    assert_eq!(profile.remmina_source.as_deref(), Some("1700000000001.remmina"));
+    assert!(plan.updated_profiles[0].changed.iter().any(|field| field == "host"));
+    assert_eq!(plan.conflicts[0].decision, ConflictStrategy::Overwrite);
+    assert_eq!(config.profiles.len(), 2);
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn merge_only_fills_unset_fields() {
+    let work_dir = work_dir("conflict-merge");
+    let (plan, config) = import(&work_dir, strategy(ConflictStrategy::Merge));
+    let plan = plan.unwrap();
+
+    let profile = config.get_profile("web-01").unwrap();
+    assert_eq!(profile.options().host.as_deref(), Some("web-01.internal"));
+    assert_eq!(profile.options().user.as_deref(), Some("deploy"));
+    assert!(profile.group.is_some());
+    // Still a manual profile: never synced nor pruned
+    assert!(profile.remmina_source.is_none());
+    let changed = &plan.updated_profiles[0].changed;
+    assert!(changed.contains(&"user".to_string()) && !changed.contains(&"host".to_string()), "{changed:?}");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn rename_imports_under_a_free_name() {
+    let work_dir = work_dir("conflict-rename");
+    let (plan, config) = import(&work_dir, strategy(ConflictStrategy::Rename));
+    let plan = plan.unwrap();
+    assert_eq!(plan.created_profiles[0].name, "web-01 (remmina)");
+    assert_eq!(plan.conflicts[0].renamed_to.as_deref(), Some("web-01 (remmina)"));
+    assert!(config.get_profile("web-01").is_some());
+
+    let options = ImportOptions { rename_suffix: "({group})".to_string(), ..strategy(ConflictStrategy::Rename) };
+    let (plan, _) = import(&work_dir, options);
+    assert_eq!(plan.unwrap().created_profiles[0].name, "web-01 (Production)");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn identity_matching_detects_the_same_host_port_and_user() {
+    let work_dir = work_dir("conflict-identity");
+    fs::write(work_dir.join("config.yaml"), EXISTING.replace("name: web-01\n", "name: web-01-old\n")).unwrap();
+
+    let convert_with = |match_identity: bool| {
+        let mut config = TabbyConfig::load_from_dir(work_dir.to_str().unwrap()).unwrap();
+        let import = ImportOptions { match_identity, ..Default::default() };
+        convert(remmina_fixture(), &mut config, ConvertOptions { import, ..Default::default() }).unwrap()
+    };
+
+    assert_eq!(convert_with(false).created_profiles.len(), 1);
+
+    let plan = convert_with(true);
+    assert!(plan.created_profiles.is_empty());
+    assert_eq!(plan.conflicts[0].existing, "legacy");
+    assert_eq!(plan.conflicts[0].matched_by, "host, port and user");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn ask_uses_the_prompt_answer() {
+    fn rename(_: &Connection, existing: &Profile) -> Result<ConflictStrategy> {
+        assert_eq!(existing.name, "web-01");
+        Ok(ConflictStrategy::Rename)
+    }
+    fn quit(_: &Connection, _: &Profile) -> Result<ConflictStrategy> {
+        Err(Error::Cancelled)
+    }
+
+    let work_dir = work_dir("conflict-ask");
+    let options = ImportOptions { ask: Some(rename), ..strategy(ConflictStrategy::Ask) };
+    let (plan, _) = import(&work_dir, options);
+    let plan = plan.unwrap();
+    assert_eq!(plan.conflicts[0].decision, ConflictStrategy::Rename);
+    assert_eq!(plan.created_profiles[0].name, "web-01 (remmina)");
+
+    let options = ImportOptions { ask: Some(quit), ..strategy(ConflictStrategy::Ask) };
+    assert!(matches!(import(&work_dir, options).0, Err(Error::Cancelled)));
+
+    // Without a prompt, conflicts are skipped
+    let (plan, _) = import(&work_dir, strategy(ConflictStrategy::Ask));
+    assert_eq!(plan.unwrap().conflicts[0].decision, ConflictStrategy::Skip);
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn on_conflict_option_is_reported() {
+    let work_dir = work_dir("conflict-cli");
+    let remmina_dir = copy_fixture("remmina", &work_dir);
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+    fs::write(tabby_dir.join("config.yaml"), EXISTING).unwrap();
+
+    let output = run_converter([
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--on-conflict".as_ref(), "rename".as_ref(),
+        "--execute".as_ref(),
+        "--yes".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("! profile 'web-01' conflicts with 'web-01' (same name): rename as 'web-01 (remmina)'"), "{stdout}");
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    assert_eq!(find_profile(&config, "web-01 (remmina)").unwrap()["options"]["host"], "web-01.example.com");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
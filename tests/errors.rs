@@ -0,0 +1,65 @@
+mod common;
+
+use std::fs;
+
+use common::{run_converter, work_dir};
+
+#[test]
+fn invalid_tabby_config_reports_line_and_column() {
+    let work_dir = work_dir("errors-yaml");
+    fs::write(work_dir.join("config.yaml"), "version: 7\nprofiles:\n  - name: [unclosed\n").unwrap();
+
+    let output = run_converter(["validate".as_ref(), "--tabby-dir".as_ref(), work_dir.as_os_str()]);
+    assert_eq!(output.status.code(), Some(11));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("config.yaml:3:11: invalid YAML"), "{stderr}");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn missing_directory_is_an_io_error_in_json() {
+    let output = run_converter(["list", "--remmina-dir", "/nonexistent/remmina", "--output", "json"]);
+    assert_eq!(output.status.code(), Some(10));
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("stdout is not JSON");
+    assert_eq!(report["error"]["kind"], "io");
+    assert_eq!(report["error"]["exit_code"], 10);
+    assert!(report["error"]["message"].as_str().unwrap().contains("/nonexistent/remmina"));
+
+    // A file where a directory is expected is a usage error
+    let file = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+    let output = run_converter(["list", "--remmina-dir", file, "--output", "json"]);
+    assert_eq!(output.status.code(), Some(2));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("stdout is not JSON");
+    assert_eq!(report["error"]["kind"], "usage");
+}
+
+#[test]
+fn unreadable_remmina_files_are_reported_not_skipped() {
+    let work_dir = work_dir("errors-files");
+    fs::write(work_dir.join("broken.remmina"), "name=no section\n").unwrap();
+    fs::write(work_dir.join("rdp.remmina"), "[remmina]\nname=desktop\nprotocol=RDP\nserver=desktop\n").unwrap();
+    fs::write(work_dir.join("ok.remmina"), "[remmina]\nname=ok\nprotocol=SSH\nserver=ok.example.com\n").unwrap();
+
+    let output = run_converter([
+        "list".as_ref(),
+        "--remmina-dir".as_ref(), work_dir.as_os_str(),
+        "--protocol".as_ref(), "SSH,RDP".as_ref(),
+        "--output".as_ref(), "json".as_ref(),
+    ]);
+    assert!(output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["profiles"].as_array().unwrap().len(), 1);
+    let skipped = report["skipped_files"].as_array().unwrap();
+    let kind_of = |file: &str| {
+        skipped.iter()
+            .find(|f| f["path"].as_str().unwrap().ends_with(file))
+            .map(|f| f["kind"].as_str().unwrap().to_string())
+    };
+    assert_eq!(kind_of("broken.remmina").as_deref(), Some("invalid_remmina_file"));
+    assert_eq!(kind_of("rdp.remmina").as_deref(), Some("unsupported_protocol"));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
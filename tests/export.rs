@@ -0,0 +1,102 @@
+mod common;
+
+use std::fs;
+
+use common::{copy_fixture, run_converter, work_dir};
+use remmina_to_tabby::remmina_keyfile::{REMMINA_SECTION, RemminaKeyFile};
+use remmina_to_tabby::{AuthMethod, Connection, RemminaProfile, TabbyConfig};
+
+const TABBY_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tabby");
+
+fn exported(name: &str) -> Connection {
+    let config = TabbyConfig::load_from_dir(TABBY_DIR).unwrap();
+    config.connections().into_iter().find(|p| p.name.as_deref() == Some(name)).unwrap()
+}
+
+#[test]
+fn export_maps_group_auth_key_and_algorithms() {
+    let bastion = exported("bastion-eu");
+    assert_eq!(bastion.group.as_deref(), Some("Production"));
+    assert_eq!(bastion.port, Some(2222));
+    assert_eq!(bastion.user.as_deref(), Some("ops"));
+    assert_eq!(bastion.auth, Some(AuthMethod::PublicKey));
+    assert_eq!(bastion.private_key.as_deref(), Some("/home/ops/.ssh/id_ed25519"));
+    assert_eq!(bastion.kex_algorithms, ["curve25519-sha256", "diffie-hellman-group14-sha256"]);
+    // A public key with a key file is a Remmina SSH identity file
+    assert_eq!(RemminaProfile::from_connection(&bastion).auth.as_deref(), Some("sshidentityfile"));
+    assert_eq!(bastion.forward_x11, None);
+
+    let router = exported("legacy-router");
+    assert_eq!(router.group, None);
+    assert_eq!(router.auth, Some(AuthMethod::Password));
+    assert_eq!(router.proxy_command.as_deref(), Some("nc -X connect -x proxy.example.com:3128 %h %p"));
+}
+
+#[test]
+fn export_maps_jump_host_to_ssh_tunnel() {
+    let db = exported("db-primary");
+    let jump_host = db.jump_host.as_ref().unwrap();
+    assert_eq!((jump_host.host.as_str(), jump_host.port), ("bastion.eu.example.com", 2222));
+    assert_eq!(jump_host.user.as_deref(), Some("ops"));
+    assert_eq!(jump_host.private_key.as_deref(), Some("/home/ops/.ssh/id_ed25519"));
+    // Empty proxy command and scripts waiting for a prompt are not exported
+    assert_eq!(db.proxy_command, None);
+    assert_eq!(db.remote_command, None);
+
+    let keyfile = RemminaProfile::from_connection(&db).to_keyfile();
+    assert_eq!(keyfile.get_string(REMMINA_SECTION, "ssh_tunnel_enabled").as_deref(), Some("1"));
+    assert_eq!(keyfile.get_string(REMMINA_SECTION, "ssh_tunnel_server").as_deref(), Some("bastion.eu.example.com:2222"));
+    assert_eq!(keyfile.get_string(REMMINA_SECTION, "ssh_tunnel_auth").as_deref(), Some("1"));
+}
+
+#[test]
+fn exported_files_follow_remmina_naming_and_read_back() {
+    let work_dir = work_dir("export-files");
+    let tabby_dir = copy_fixture("tabby", &work_dir);
+    let remmina_dir = work_dir.join("remmina");
+    fs::create_dir_all(&remmina_dir).unwrap();
+
+    let output = run_converter([
+        "export".as_ref(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--execute".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let mut names: Vec<String> = fs::read_dir(&remmina_dir).unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, [
+        "_ssh_legacy-router_10-0-0-1.remmina",
+        "production_ssh_bastion-eu_bastion-eu-example-com.remmina",
+        "production_ssh_db-primary_db-primary-internal.remmina",
+    ]);
+
+    let path = remmina_dir.join("production_ssh_bastion-eu_bastion-eu-example-com.remmina");
+    let keyfile = RemminaKeyFile::load(&path).unwrap();
+    assert_eq!(keyfile.get_string(REMMINA_SECTION, "ssh_auth").as_deref(), Some("1"));
+    let profile = RemminaProfile::from_keyfile(&keyfile, &path).unwrap();
+    assert_eq!(profile.group.as_deref(), Some("Production"));
+    assert_eq!(profile.auth.as_deref(), Some("sshidentityfile"));
+    assert_eq!(profile.ciphers.as_deref(), Some("aes256-gcm@openssh.com,aes128-ctr"));
+
+    // A second export keeps the files and reports them as skipped
+    let output = run_converter([
+        "export".as_ref(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--output".as_ref(), "json".as_ref(),
+        "--execute".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["exported_files"].as_array().map_or(0, Vec::len), 0);
+    let skipped = report["skipped_files"].as_array().unwrap();
+    assert_eq!(skipped.len(), 3);
+    assert!(skipped.iter().all(|f| f["kind"] == "exists"), "{skipped:?}");
+    assert!(skipped.iter().any(|f| f["path"].as_str().unwrap().ends_with("production_ssh_bastion-eu_bastion-eu-example-com.remmina")));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
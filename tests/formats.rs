@@ -0,0 +1,117 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+use remmina_to_tabby::convert::convert_connections;
+use remmina_to_tabby::report::RunReport;
+use remmina_to_tabby::{Connection, ConnectionProtocols, Connections, ConvertOptions, Importer, ReadOptions, Registry, Result, TabbyConfig};
+
+/// A format plugged in from outside the crate
+struct StaticHosts;
+
+impl Importer for StaticHosts {
+    fn name(&self) -> &'static str {
+        "static"
+    }
+
+    fn description(&self) -> &'static str {
+        "Two hard-coded hosts"
+    }
+
+    fn default_path(&self) -> Option<PathBuf> {
+        None
+    }
+
+    fn import(&self, _source: &Path, _options: &ReadOptions, _report: &mut RunReport) -> Result<Connections> {
+        let host = |name: &str| Connection {
+            name: Some(name.to_string()),
+            host: Some(format!("{name}.example.com")),
+            protocol: ConnectionProtocols::Ssh,
+            group: Some("Static".to_string()),
+            source_id: Some(format!("static:{name}")),
+            ..Default::default()
+        };
+        Ok(Connections {
+            connections: vec![host("alpha"), host("beta")],
+            defaults: None,
+            sources: ["static:alpha", "static:beta"].iter().map(|s| s.to_string()).collect(),
+            scheme: Some("static"),
+        })
+    }
+}
+
+#[test]
+fn registry_selects_formats_by_name() {
+    let mut registry = Registry::default();
+    let importers: Vec<&str> = registry.importers().map(|i| i.name()).collect();
+    assert_eq!(importers, ["remmina", "tabby", "ssh-config", "putty", "kitty", "mobaxterm", "mremoteng", "termius", "windows-terminal"]);
+    assert_eq!(registry.exporter("Ssh-Config").map(|e| e.name()), Some("ssh-config"));
+    assert!(registry.importer("static").is_none());
+
+    registry.register_importer(Box::new(StaticHosts));
+    let importer = registry.importer("static").unwrap();
+    let connections = importer.import(Path::new(""), &ReadOptions::default(), &mut RunReport::default()).unwrap();
+
+    let mut config = TabbyConfig::load_from_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tabby_minimal")).unwrap();
+    let plan = convert_connections(connections, &mut config, ConvertOptions::default()).unwrap();
+    assert_eq!(plan.created_profiles.len(), 2);
+    assert_eq!(plan.created_groups[0].name, "Static");
+}
+
+#[test]
+fn convert_command_writes_any_pair_of_formats() {
+    let work_dir = work_dir("formats");
+    let remmina_dir = work_dir.join("remmina");
+    fs::create_dir_all(&remmina_dir).unwrap();
+    let ssh_config = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ssh_config/config");
+
+    let convert = |args: &[&std::ffi::OsStr]| {
+        let mut all = vec!["convert".as_ref()];
+        all.extend_from_slice(args);
+        run_converter(all)
+    };
+
+    // ssh_config to Remmina, nothing written without --execute
+    let args = ["--from".as_ref(), "ssh-config".as_ref(), "--from-path".as_ref(), ssh_config.as_ref(), "--to".as_ref(), "remmina".as_ref(), "--to-path".as_ref(), remmina_dir.as_os_str()];
+    assert!(convert(&args).status.success());
+    assert_eq!(fs::read_dir(&remmina_dir).unwrap().count(), 0);
+    let mut with_execute = args.to_vec();
+    with_execute.push("--execute".as_ref());
+    let output = convert(&with_execute);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    let api = fs::read_to_string(remmina_dir.join("production_ssh_api_api-example-com.remmina")).unwrap();
+    assert!(api.contains("server=api.example.com"), "{api}");
+    assert!(api.contains("username=deploy"), "{api}");
+
+    // and the Remmina files into Tabby
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+    let output = convert(&["--from".as_ref(), "remmina".as_ref(), "--from-path".as_ref(), remmina_dir.as_os_str(), "--to-path".as_ref(), tabby_dir.as_os_str(), "--execute".as_ref()]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    assert_eq!(find_profile(&config, "api").unwrap()["options"]["host"], "api.example.com");
+    assert!(tabby_dir.join("config.yaml.bak").exists());
+
+    // The merge options belong to the Tabby exporter
+    let mut with_rules = args.to_vec();
+    with_rules.extend_from_slice(&["--rules".as_ref(), "rules.yaml".as_ref(), "--prune".as_ref()]);
+    let output = convert(&with_rules);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--prune, --rules only apply to --to tabby, not to remmina"), "{stderr}");
+
+    let output = convert(&["--from".as_ref(), "nope".as_ref()]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("expected one of: remmina, tabby, ssh-config, putty, kitty, mobaxterm, mremoteng, termius, windows-terminal"), "{stderr}");
+
+    // A usage error, not a missing file named "putty"
+    let output = convert(&["--from".as_ref(), "putty".as_ref(), "--output".as_ref(), "json".as_ref()]);
+    assert_eq!(output.status.code(), Some(2));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["error"]["kind"], "usage");
+    assert_eq!(report["error"]["message"], "the putty format has no default location, use --from-path");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
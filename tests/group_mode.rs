@@ -0,0 +1,97 @@
+mod common;
+
+use std::fs;
+
+use common::{load_yaml, run_converter, work_dir};
+use remmina_to_tabby::Connection;
+use remmina_to_tabby::group_mapping::{GroupMapping, GroupMode};
+
+fn profile(name: &str, group: &str) -> Connection {
+    Connection {
+        name: Some(name.to_string()),
+        group: Some(group.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Group and name of every profile after mapping
+fn map(mode: GroupMode, profiles: &[(&str, &str)]) -> Vec<(String, String)> {
+    let mut profiles: Vec<Connection> = profiles.iter().map(|(name, group)| profile(name, group)).collect();
+    GroupMapping { mode, ..Default::default() }.apply(&mut profiles);
+    profiles.into_iter().map(|p| (p.group.unwrap_or_default(), p.name.unwrap_or_default())).collect()
+}
+
+fn pair(group: &str, name: &str) -> (String, String) {
+    (group.to_string(), name.to_string())
+}
+
+#[test]
+fn literal_keeps_the_remmina_path() {
+    assert_eq!(map(GroupMode::Literal, &[("db-01", "Prod/DB/Primary")]), [pair("Prod/DB/Primary", "db-01")]);
+}
+
+#[test]
+fn flatten_joins_segments_with_the_separator() {
+    let mapped = map(GroupMode::Flatten, &[("db-01", "Prod/DB/Primary"), ("web", "Prod"), ("db-02", " Prod / DB / Primary ")]);
+    assert_eq!(mapped, [pair("Prod › DB › Primary", "db-01"), pair("Prod", "web"), pair("Prod › DB › Primary", "db-02")]);
+
+    let mut profiles = vec![profile("db-01", "Prod/DB")];
+    GroupMapping { mode: GroupMode::Flatten, separator: " - ".to_string() }.apply(&mut profiles);
+    assert_eq!(profiles[0].group.as_deref(), Some("Prod - DB"));
+
+    // A group already named like a flattened path
+    let mapped = map(GroupMode::Flatten, &[("a", "Prod/DB"), ("b", "Prod › DB")]);
+    assert_ne!(mapped[0].0, mapped[1].0);
+}
+
+#[test]
+fn leaf_keeps_parents_only_when_leaves_collide() {
+    let mapped = map(GroupMode::Leaf, &[("db-01", "Prod/DB"), ("db-02", "Staging/DB"), ("web", "Prod/Web")]);
+    assert_eq!(mapped, [pair("Prod › DB", "db-01"), pair("Staging › DB", "db-02"), pair("Web", "web")]);
+}
+
+#[test]
+fn root_merges_sub_groups_and_renames_clashing_names() {
+    let mapped = map(GroupMode::Root, &[("db", "Prod/A"), ("db", "Prod/B"), ("web", "Prod/A"), ("mail", "Office")]);
+    assert_eq!(
+        mapped,
+        [pair("Prod", "db (Prod › A)"), pair("Prod", "db (Prod › B)"), pair("Prod", "web"), pair("Office", "mail")]
+    );
+}
+
+#[test]
+fn prefix_name_moves_the_sub_path_into_the_name() {
+    let mapped = map(GroupMode::PrefixName, &[("db-01", "Prod/DB/Primary"), ("web", "Prod"), ("DB › Primary › db-01", "Prod")]);
+    assert_eq!(mapped[0].0, "Prod");
+    assert_eq!(mapped[1], pair("Prod", "web"));
+    // The prefixed name clashes with a profile already named that way
+    assert_eq!(mapped[0].1, "DB › Primary › db-01 (Prod › DB › Primary)");
+    assert_eq!(mapped[2].1, "DB › Primary › db-01 (Prod)");
+}
+
+#[test]
+fn group_mode_option_is_used_by_import() {
+    let work_dir = work_dir("group-mode");
+    let remmina_dir = work_dir.join("remmina");
+    let tabby_dir = work_dir.join("tabby");
+    fs::create_dir_all(&remmina_dir).unwrap();
+    fs::create_dir_all(&tabby_dir).unwrap();
+    fs::write(tabby_dir.join("config.yaml"), "version: 7\nprofiles: []\ngroups: []\n").unwrap();
+    fs::write(remmina_dir.join("db.remmina"), "[remmina]\nname=db-01\nserver=db.example.com\nprotocol=SSH\ngroup=Prod/DB\n").unwrap();
+
+    let output = run_converter([
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--group-mode".as_ref(), "flatten".as_ref(),
+        "--group-separator".as_ref(), " / ".as_ref(),
+        "--execute".as_ref(),
+        "--yes".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    assert_eq!(config["groups"][0]["name"], "Prod / DB");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
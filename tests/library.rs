@@ -0,0 +1,102 @@
+use std::path::Path;
+
+use remmina_to_tabby::remmina_keyfile::RemminaKeyFile;
+use remmina_to_tabby::remmina_secrets::{RemminaPref, Secret, SecretKind, SecretSink};
+use remmina_to_tabby::tabby_parser::Profile;
+use remmina_to_tabby::{ConnectionProtocols, ConvertOptions, Error, RemminaFiles, RemminaProfile, TabbyConfig, convert};
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Secret sink keeping the plaintext, to check what the library hands over
+#[derive(Default)]
+struct CollectedSecrets(Vec<(String, SecretKind, String)>);
+
+impl SecretSink for CollectedSecrets {
+    fn store(&mut self, profile: &Profile, kind: SecretKind, secret: &Secret) {
+        self.0.push((profile.name.clone(), kind, secret.expose().to_string()));
+    }
+}
+
+#[test]
+fn convert_adds_profiles_and_groups_in_memory() {
+    let mut config = TabbyConfig::load_from_dir(&fixture("tabby_minimal")).unwrap();
+
+    let plan = convert(&fixture("remmina"), &mut config, ConvertOptions::default()).unwrap();
+
+    assert_eq!(plan.profiles.len(), 1);
+    assert!(plan.skipped_files.is_empty());
+    assert_eq!(plan.created_groups.len(), 1);
+    assert_eq!(plan.created_groups[0].name, "Production");
+    assert_eq!(plan.created_profiles.len(), 1);
+
+    let profile = config.get_profile("web-01").unwrap();
+    assert_eq!(profile.id.as_ref(), Some(&plan.created_profiles[0].id));
+    assert_eq!(profile.group.as_ref(), Some(&plan.created_groups[0].id));
+    assert_eq!(profile.options().host.as_deref(), Some("web-01.example.com"));
+    assert_eq!(profile.options().user.as_deref(), Some("deploy"));
+    assert_eq!(profile.options().port_number(), Some(22));
+    assert_eq!(profile.options().auth.as_deref(), Some("password"));
+}
+
+#[test]
+fn convert_twice_reports_conflicts() {
+    let mut config = TabbyConfig::load_from_dir(&fixture("tabby_minimal")).unwrap();
+    convert(&fixture("remmina"), &mut config, ConvertOptions::default()).unwrap();
+
+    let plan = convert(&fixture("remmina"), &mut config, ConvertOptions::default()).unwrap();
+    assert!(plan.created_profiles.is_empty() && plan.created_groups.is_empty());
+    assert_eq!(plan.skipped_profiles.len(), 1);
+    assert_eq!(plan.skipped_profiles[0].kind, "conflict");
+    assert_eq!(config.profiles.len(), 1);
+}
+
+#[test]
+fn convert_hands_decrypted_secrets_to_the_sink() {
+    let source = fixture("remmina_secrets");
+    let pref = RemminaPref::load(&Path::new(&source).join("remmina.pref")).unwrap();
+    let mut config = TabbyConfig::load_from_dir(&fixture("tabby_minimal")).unwrap();
+    let mut secrets = CollectedSecrets::default();
+
+    let options = ConvertOptions {
+        remmina_pref: Some(&pref),
+        secret_sink: Some(&mut secrets),
+        ..Default::default()
+    };
+    let plan = convert(&source, &mut config, options).unwrap();
+
+    let summary = plan.secrets.unwrap();
+    assert_eq!((summary.decrypted, summary.in_keyring, summary.failed), (3, 1, 0));
+
+    let find = |name: &str, kind: SecretKind| {
+        secrets.0.iter().find(|(n, k, _)| n == name && *k == kind).map(|(_, _, v)| v.as_str())
+    };
+    assert_eq!(find("secret-host", SecretKind::Password), Some("s3cr3t-Passw0rd"));
+    assert_eq!(find("secret-host", SecretKind::KeyPassphrase), Some("key phrase"));
+    assert_eq!(find("gw.example.com (jump host)", SecretKind::Password), Some("bastion!"));
+}
+
+#[test]
+fn convert_reports_missing_source_directory() {
+    let mut config = TabbyConfig::load_from_dir(&fixture("tabby_minimal")).unwrap();
+
+    let result = convert("/nonexistent/remmina", &mut config, ConvertOptions::default());
+    assert!(matches!(result, Err(Error::Io { .. })));
+}
+
+#[test]
+fn parser_api_builds_profiles_from_files() {
+    let files = RemminaFiles::find(&fixture("remmina_tunnel")).unwrap();
+    assert_eq!(files.files.len(), 5);
+
+    let path = Path::new(&fixture("remmina")).join("1700000000001.remmina");
+    let keyfile = RemminaKeyFile::load(&path).unwrap();
+    let profile = RemminaProfile::from_keyfile(&keyfile, &path).unwrap();
+    assert_eq!(profile.name.as_deref(), Some("web-01"));
+    assert_eq!(ConnectionProtocols::from_str(profile.protocol.as_deref().unwrap()), ConnectionProtocols::Ssh);
+
+    let rdp = RemminaKeyFile::parse("[remmina]\nprotocol=RDP\nserver=desktop\n").unwrap();
+    let result = RemminaProfile::from_keyfile(&rdp, Path::new("desktop.remmina"));
+    assert!(matches!(result, Err(Error::UnsupportedProtocol { ref protocol, .. }) if protocol == "RDP"));
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+use remmina_to_tabby::mobaxterm_parser::MobaXtermSessions;
+use remmina_to_tabby::report::RunReport;
+use remmina_to_tabby::{AuthMethod, Connection, ConnectionProtocols, Error};
+
+const SESSIONS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mobaxterm/sessions.mxtsessions");
+
+fn session<'a>(sessions: &'a MobaXtermSessions, name: &str) -> &'a Connection {
+    sessions.profiles.iter().find(|p| p.name.as_deref() == Some(name)).unwrap()
+}
+
+#[test]
+fn bookmarks_become_profiles() {
+    let mut report = RunReport::default();
+    let sessions = MobaXtermSessions::load(Path::new(SESSIONS), &mut report).unwrap();
+    let protocols: Vec<(&str, &ConnectionProtocols)> = sessions.profiles.iter().map(|p| (p.name.as_deref().unwrap(), &p.protocol)).collect();
+    assert_eq!(protocols, [
+        ("web", &ConnectionProtocols::Ssh),
+        ("db primary", &ConnectionProtocols::Ssh),
+        ("desktop", &ConnectionProtocols::Rdp),
+        ("kiosk", &ConnectionProtocols::Vnc),
+        ("router", &ConnectionProtocols::Telnet),
+        ("console", &ConnectionProtocols::Serial),
+    ]);
+
+    let web = session(&sessions, "web");
+    assert_eq!((web.host.as_deref(), web.port, web.user.as_deref()), (Some("web.example.com"), Some(22), Some("deploy")));
+    assert_eq!((web.forward_x11, web.compression), (Some(true), Some(false)));
+    assert_eq!(web.remote_command.as_deref(), Some("uptime"));
+    assert_eq!((web.group.as_deref(), web.jump_host.as_ref()), (None, None));
+
+    let db = session(&sessions, "db primary");
+    assert_eq!(db.group.as_deref(), Some("Prod/DB"));
+    assert_eq!(db.source_id.as_deref(), Some("mobaxterm:Prod/DB/db primary"));
+    assert_eq!(db.private_key, Some(format!("{}/.ssh/id_ed25519", std::env::var("HOME").unwrap_or_default())));
+    assert_eq!(db.auth, Some(AuthMethod::PublicKey));
+    // Of the two chained gateways, the last one
+    let jump_host = db.jump_host.as_ref().unwrap();
+    assert_eq!((jump_host.host.as_str(), jump_host.port, jump_host.user.as_deref()), ("bastion.example.com", 2200, Some("ops")));
+
+    let desktop = session(&sessions, "desktop");
+    assert_eq!((desktop.host.as_deref(), desktop.port, desktop.user.as_deref()), (Some("win.example.com"), Some(3389), Some("Administrator")));
+    let console = session(&sessions, "console");
+    assert_eq!((console.host.as_deref(), console.port), (Some("COM3"), None));
+
+    // The local shell is no connection
+    assert_eq!(report.skipped_files.len(), 1);
+    assert_eq!(report.skipped_files[0].kind, "unsupported_protocol");
+    assert_eq!(sessions.sources.len(), 6);
+}
+
+#[test]
+fn malformed_sessions_are_skipped_and_other_files_rejected() {
+    // A line without '#<type>#' is reported, the other sessions are still read
+    let broken = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mobaxterm/broken.mxtsessions");
+    let mut report = RunReport::default();
+    let sessions = MobaXtermSessions::load(Path::new(broken), &mut report).unwrap();
+    let names: Vec<&str> = sessions.profiles.iter().filter_map(|p| p.name.as_deref()).collect();
+    assert_eq!(names, ["web"]);
+    assert_eq!(report.skipped_files.len(), 1);
+    assert_eq!(report.skipped_files[0].kind, "invalid_session_file");
+    assert!(report.skipped_files[0].reason.contains("line 3: session 'broken' does not start with '#<type>#'"), "{}", report.skipped_files[0].reason);
+
+    let not_sessions = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ssh_config/config");
+    let error = MobaXtermSessions::load(Path::new(not_sessions), &mut RunReport::default()).unwrap_err();
+    assert!(matches!(error, Error::InvalidSessionFile { line: None, .. }), "{error}");
+    assert_eq!(error.exit_code(), 17);
+}
+
+#[test]
+fn convert_mobaxterm_sessions_into_tabby() {
+    let work_dir = work_dir("mobaxterm");
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+
+    let output = run_converter([
+        "convert".as_ref(),
+        "--from".as_ref(), "mobaxterm".as_ref(),
+        "--from-path".as_ref(), SESSIONS.as_ref(),
+        "--to-path".as_ref(), tabby_dir.as_os_str(),
+        "--protocol".as_ref(), "SSH,RDP".as_ref(),
+        "--output".as_ref(), "json".as_ref(),
+        "--execute".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let kinds: Vec<&str> = report["skipped_files"].as_array().unwrap().iter().filter_map(|s| s["kind"].as_str()).collect();
+    assert_eq!(kinds.iter().filter(|k| **k == "filtered").count(), 3);
+    assert!(kinds.contains(&"unsupported_protocol"));
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    let db = find_profile(&config, "db primary").unwrap();
+    assert_eq!(db["options"]["host"], "db1.example.com");
+    assert_eq!(db["options"]["port"], 2222);
+    let jump_host = find_profile(&config, "bastion.example.com (jump host)").unwrap();
+    assert_eq!(db["options"]["jumpHost"], jump_host["id"]);
+    assert_eq!(find_profile(&config, "desktop").unwrap()["type"], "rdp");
+    assert!(find_profile(&config, "router").is_none());
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+use remmina_to_tabby::mremoteng_parser::MRemoteNgConnections;
+use remmina_to_tabby::report::RunReport;
+use remmina_to_tabby::{Connection, ConnectionProtocols, Error};
+
+const CONF_CONS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mremoteng/confCons.xml");
+
+fn connection<'a>(file: &'a MRemoteNgConnections, name: &str) -> &'a Connection {
+    file.profiles.iter().find(|p| p.name.as_deref() == Some(name)).unwrap()
+}
+
+#[test]
+fn containers_and_inherited_attributes() {
+    let mut report = RunReport::default();
+    let file = MRemoteNgConnections::load(Path::new(CONF_CONS), &mut report).unwrap();
+    let names: Vec<&str> = file.profiles.iter().filter_map(|p| p.name.as_deref()).collect();
+    assert_eq!(names, ["db primary", "db replica", "web", "desktop"]);
+
+    // Protocol inherited from "Prod" through "DB", port and user from "DB"
+    let primary = connection(&file, "db primary");
+    assert_eq!(primary.group.as_deref(), Some("Prod/DB"));
+    assert_eq!(primary.protocol, ConnectionProtocols::Ssh);
+    assert_eq!((primary.host.as_deref(), primary.port, primary.user.as_deref()), (Some("db1.example.com"), Some(2222), Some("dba")));
+    assert_eq!(primary.source_id.as_deref(), Some("mremoteng:6e1b4a3c-5a4e-4f32-9a51-0c0c6d1e2f03"));
+    assert_eq!(primary.password, None);
+
+    let replica = connection(&file, "db replica");
+    assert_eq!((replica.port, replica.user.as_deref()), (Some(22), Some("postgres")));
+
+    let web = connection(&file, "web");
+    assert_eq!((web.group.as_deref(), &web.protocol, web.user.as_deref()), (Some("Prod"), &ConnectionProtocols::Ssh, Some("admin")));
+
+    // Inheriting from the root gives no value
+    let desktop = connection(&file, "desktop");
+    assert_eq!((desktop.group.as_deref(), &desktop.protocol, desktop.user.as_deref()), (None, &ConnectionProtocols::Rdp, None));
+
+    assert_eq!(report.skipped_files.len(), 1);
+    assert!(report.skipped_files[0].reason.contains("HTTPS"), "{:?}", report.skipped_files[0]);
+    assert_eq!(file.sources.len(), 4);
+}
+
+#[test]
+fn encrypted_and_invalid_files_are_reported() {
+    let encrypted = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mremoteng/encrypted.xml");
+    let error = MRemoteNgConnections::load(Path::new(encrypted), &mut RunReport::default()).unwrap_err();
+    assert!(matches!(error, Error::InvalidSessionFile { line: Some(2), .. }), "{error}");
+    assert!(error.to_string().contains("FullFileEncryption"), "{error}");
+
+    let putty = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/putty/sessions.reg");
+    let error = MRemoteNgConnections::load(Path::new(putty), &mut RunReport::default()).unwrap_err();
+    assert!(error.to_string().contains("invalid XML"), "{error}");
+    assert_eq!(error.exit_code(), 17);
+}
+
+#[test]
+fn convert_mremoteng_connections_into_tabby() {
+    let work_dir = work_dir("mremoteng");
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+
+    let output = run_converter([
+        "convert".as_ref(),
+        "--from".as_ref(), "mremoteng".as_ref(),
+        "--from-path".as_ref(), CONF_CONS.as_ref(),
+        "--to-path".as_ref(), tabby_dir.as_os_str(),
+        "--group-mode".as_ref(), "leaf".as_ref(),
+        "--execute".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    let primary = find_profile(&config, "db primary").unwrap();
+    assert_eq!(primary["options"]["host"], "db1.example.com");
+    assert_eq!(primary["options"]["user"], "dba");
+    let group = config["groups"].as_sequence().unwrap().iter().find(|g| g["id"] == primary["group"]).unwrap();
+    assert_eq!(group["name"], "DB");
+    assert!(find_profile(&config, "desktop").is_none());
+
+    let encrypted = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mremoteng/encrypted.xml");
+    let output = run_converter(["convert", "--from", "mremoteng", "--from-path", encrypted]);
+    assert_eq!(output.status.code(), Some(17));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("FullFileEncryption"));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::{find_profile, load_yaml, run_converter, work_dir};
+use remmina_to_tabby::connection::{TEMPLATE_PLACEHOLDERS, check_template};
+use remmina_to_tabby::{Connection, ConnectionProtocols, JumpHost};
+
+fn profile() -> Connection {
+    Connection {
+        name: Some("web-01".to_string()),
+        host: Some("web-01.example.com".to_string()),
+        port: Some(2222),
+        group: Some("Production".to_string()),
+        protocol: ConnectionProtocols::Ssh,
+        user: Some("deploy".to_string()),
+        forward_x11: Some(true),
+        jump_host: Some(JumpHost { host: "bastion".to_string(), port: 22, user: None, auth: None, private_key: None, password: None }),
+        path: PathBuf::from("/home/me/.local/share/remmina/group_ssh_web-01.remmina"),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn templates_expand_profile_fields() {
+    let profile = profile();
+    assert_eq!(profile.render_name("{group} / {name} ({user}@{server})"), "Production / web-01 (deploy@web-01.example.com)");
+    assert_eq!(profile.render_name("{stem}"), "group_ssh_web-01");
+    assert_eq!(profile.render_name("{protocol}:{host}:{port} x11={forward_x11} via {tunnel}"), "SSH:web-01.example.com:2222 x11=1 via bastion:22");
+    // Unset fields expand to nothing
+    assert_eq!(profile.render_name("{name}{charset}"), "web-01");
+
+    // Every placeholder is known, none is kept as-is
+    for placeholder in TEMPLATE_PLACEHOLDERS {
+        assert!(!profile.render_name(&format!("{{{placeholder}}}")).contains('{'), "{placeholder}");
+    }
+}
+
+#[test]
+fn missing_names_fall_back_to_the_server() {
+    let mut profile = profile();
+    profile.name = None;
+    assert_eq!(profile.render_name("{name}"), "web-01.example.com");
+    assert_eq!(profile.render_name("{group} / {name}"), "Production / web-01.example.com");
+
+    profile.name = Some("  ".to_string());
+    assert_eq!(profile.render_name("{name}"), "web-01.example.com");
+
+    // Never empty
+    profile.host = None;
+    assert_eq!(profile.render_name("{name}"), "group_ssh_web-01");
+    assert_eq!(profile.render_name("{charset}"), "group_ssh_web-01");
+}
+
+#[test]
+fn unknown_placeholders_are_rejected() {
+    assert!(check_template("{name} ({user}@{server})").is_ok());
+    let error = check_template("{name} ({hostname})").unwrap_err();
+    assert!(error.contains("{hostname}"), "{error}");
+}
+
+#[test]
+fn name_template_option_is_used_by_import() {
+    let work_dir = work_dir("name-template");
+    let remmina_dir = work_dir.join("remmina");
+    let tabby_dir = work_dir.join("tabby");
+    fs::create_dir_all(&remmina_dir).unwrap();
+    fs::create_dir_all(&tabby_dir).unwrap();
+    fs::write(tabby_dir.join("config.yaml"), "version: 7\nprofiles: []\ngroups: []\n").unwrap();
+    fs::write(remmina_dir.join("web.remmina"), "[remmina]\nname=web\nserver=web.example.com\nprotocol=SSH\nusername=deploy\n").unwrap();
+    fs::write(remmina_dir.join("noname.remmina"), "[remmina]\nserver=db.example.com\nprotocol=SSH\n").unwrap();
+
+    let import = |template: &str| run_converter([
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--name-template".as_ref(), template.as_ref(),
+        "--execute".as_ref(),
+        "--yes".as_ref(),
+    ]);
+
+    assert_eq!(import("{name} ({unknown})").status.code(), Some(2));
+
+    let output = import("{name} ({user}@{server})");
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    assert!(find_profile(&config, "web (deploy@web.example.com)").is_some());
+    assert!(find_profile(&config, "db.example.com (@db.example.com)").is_some());
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use std::fs;
+
+use common::{copy_fixture, run_converter, work_dir};
+
+#[test]
+fn json_output_is_a_single_document_on_stdout() {
+    let work_dir = work_dir("output-json");
+    let remmina_dir = copy_fixture("remmina_tunnel", &work_dir);
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+
+    let output = run_converter([
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--output".as_ref(), "json".as_ref(),
+        "--yes".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    // Nothing but the report on stdout, logs go to stderr
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("stdout is not JSON");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Importing Profile"));
+
+    assert_eq!(report["command"], "import");
+    assert_eq!(report["execute"], false);
+    assert!(report.get("saved_to").is_none());
+
+    let plan = &report["plan"];
+    let profiles = plan["profiles"].as_array().unwrap();
+    let created = plan["created_profiles"].as_array().unwrap();
+    // Imported profiles plus the jump hosts created for their tunnels
+    assert!(created.len() >= profiles.len());
+    for profile in created {
+        assert!(!profile["id"].as_str().unwrap().is_empty());
+    }
+    for group in plan["created_groups"].as_array().unwrap() {
+        assert!(created.iter().any(|p| p["group"] == group["id"]));
+    }
+
+    // Dry-run, the config is left untouched
+    assert_eq!(fs::read_to_string(tabby_dir.join("config.yaml")).unwrap(), "version: 7\nprofiles: []\ngroups: []\n");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn yaml_output_reports_skipped_files() {
+    let remmina_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/remmina_keyfile");
+
+    let output = run_converter(["list", "--remmina-dir", remmina_dir, "--output", "yaml"]);
+    assert!(output.status.success());
+
+    let report: serde_yaml_ng::Value = serde_yaml_ng::from_slice(&output.stdout).expect("stdout is not YAML");
+    assert_eq!(report["command"], "list");
+    let skipped = report["skipped_files"].as_sequence().unwrap();
+    assert!(skipped.iter().any(|f| f["reason"].as_str().unwrap().contains("does not start with a section")));
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use common::{find_profile, load_yaml, run_converter, work_dir};
+use remmina_to_tabby::{Connection, ConnectionProtocols};
+use remmina_to_tabby::palette::{ColorMode, GROUP_COLORS, Palette, WeightMode, group_color};
+
+fn profile(name: &str, group: Option<&str>, protocol: &str) -> Connection {
+    Connection {
+        name: Some(name.to_string()),
+        group: group.map(str::to_string),
+        protocol: ConnectionProtocols::from_str(protocol),
+        ..Default::default()
+    }
+}
+
+fn map(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+    entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn group_colors_are_stable() {
+    assert_eq!(group_color("Production"), group_color("Production"));
+    assert!(GROUP_COLORS.contains(&group_color("")));
+    // Not every group gets the same color
+    let colors: std::collections::BTreeSet<&str> = ["Production", "Staging", "Office", "Lab", "DMZ"].iter().map(|g| group_color(g)).collect();
+    assert!(colors.len() > 1);
+}
+
+#[test]
+fn palette_fills_colors_icons_and_weights() {
+    let mut profiles = vec![
+        profile("web", Some("Production"), "SSH"),
+        profile("DB", Some("Production"), "SSH"),
+        profile("desktop", Some("Office"), "RDP"),
+        profile("app", Some("Production"), "SSH"),
+        profile("lonely", None, "VNC"),
+    ];
+    profiles[3].color = Some("#123456".to_string());
+
+    let palette = Palette {
+        colors: ColorMode::Group,
+        group_colors: map(&[("Office", "#abc")]),
+        protocol_icons: map(&[("rdp", "fab fa-windows")]),
+        weights: WeightMode::Remmina,
+    };
+    palette.apply(&mut profiles);
+
+    assert_eq!(profiles[0].color.as_deref(), Some(group_color("Production")));
+    assert_eq!(profiles[1].color.as_deref(), Some(group_color("Production")));
+    assert_eq!(profiles[2].color.as_deref(), Some("#abc"));
+    // Set by a rule, kept
+    assert_eq!(profiles[3].color.as_deref(), Some("#123456"));
+    assert_eq!(profiles[4].color.as_deref(), Some(group_color("Default Group")));
+
+    assert_eq!(profiles[2].icon.as_deref(), Some("fab fa-windows"));
+    assert_eq!(profiles[0].icon, None);
+
+    // Names sorted within each group: app, DB, web
+    let weights: Vec<Option<i32>> = profiles.iter().map(|p| p.weight).collect();
+    assert_eq!(weights, [Some(2), Some(1), Some(0), Some(0), Some(0)]);
+}
+
+#[test]
+fn default_palette_changes_nothing() {
+    let mut profiles = vec![profile("web", Some("Production"), "SSH")];
+    Palette::default().apply(&mut profiles);
+    assert_eq!((profiles[0].color.clone(), profiles[0].icon.clone(), profiles[0].weight), (None, None, None));
+}
+
+#[test]
+fn palette_options_are_used_by_import() {
+    let work_dir = work_dir("palette");
+    let remmina_dir = work_dir.join("remmina");
+    let tabby_dir = work_dir.join("tabby");
+    fs::create_dir_all(&remmina_dir).unwrap();
+    fs::create_dir_all(&tabby_dir).unwrap();
+    fs::write(tabby_dir.join("config.yaml"), "version: 7\nprofiles: []\ngroups: []\n").unwrap();
+    for (name, group) in [("web", "Production"), ("db", "Production"), ("lab", "Lab")] {
+        let content = format!("[remmina]\nname={name}\nserver={name}.example.com\nprotocol=SSH\ngroup={group}\n");
+        fs::write(remmina_dir.join(format!("{name}.remmina")), content).unwrap();
+    }
+
+    let import = |extra: &[&str]| {
+        let mut args: Vec<std::ffi::OsString> = vec![
+            "import".into(),
+            "--remmina-dir".into(), remmina_dir.clone().into(),
+            "--tabby-dir".into(), tabby_dir.clone().into(),
+            "--execute".into(),
+            "--yes".into(),
+        ];
+        args.extend(extra.iter().map(Into::into));
+        run_converter(args)
+    };
+
+    assert_eq!(import(&["--group-color", "Lab=orange"]).status.code(), Some(2));
+    assert_eq!(import(&["--protocol-icon", "SSH"]).status.code(), Some(2));
+
+    let output = import(&["--colors", "group", "--group-color", "Lab=#00ff00", "--protocol-icon", "ssh=fas fa-server", "--weights", "remmina"]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    let web = find_profile(&config, "web").unwrap();
+    assert_eq!(web["color"], group_color("Production"));
+    assert_eq!(web["icon"], "fas fa-server");
+    assert_eq!(web["weight"], 1);
+    assert_eq!(find_profile(&config, "db").unwrap()["weight"], 0);
+    assert_eq!(find_profile(&config, "lab").unwrap()["color"], "#00ff00");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
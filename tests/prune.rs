@@ -0,0 +1,93 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+
+fn import(remmina_dir: &Path, tabby_dir: &Path, extra: &[&str]) -> serde_json::Value {
+    let mut args = vec![
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--output".as_ref(), "json".as_ref(),
+        "--yes".as_ref(),
+    ];
+    args.extend(extra.iter().map(std::ffi::OsStr::new));
+    let output = run_converter(args);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn group_names(config: &serde_yaml_ng::Value) -> Vec<&str> {
+    config["groups"].as_sequence().unwrap().iter().map(|g| g["name"].as_str().unwrap()).collect()
+}
+
+#[test]
+fn prune_removes_profiles_of_deleted_files_and_empty_groups() {
+    let work_dir = work_dir("prune");
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+    let config_path = tabby_dir.join("config.yaml");
+    let remmina_dir = work_dir.join("remmina");
+    fs::create_dir_all(&remmina_dir).unwrap();
+    fs::write(remmina_dir.join("web.remmina"), "[remmina]\nname=web\nserver=web.example.com\nprotocol=SSH\ngroup=Production\n").unwrap();
+    fs::write(remmina_dir.join("old.remmina"), "[remmina]\nname=old\nserver=old.example.com\nprotocol=SSH\ngroup=Legacy\n").unwrap();
+
+    import(&remmina_dir, &tabby_dir, &["--execute"]);
+
+    // A profile created in Tabby has no Remmina source and is never pruned
+    let mut config = load_yaml(&config_path);
+    let manual = "type: ssh\nname: manual\nid: ssh:manual\noptions:\n  host: manual.example.com\n";
+    config["profiles"].as_sequence_mut().unwrap().push(serde_yaml_ng::from_str(manual).unwrap());
+    fs::write(&config_path, serde_yaml_ng::to_string(&config).unwrap()).unwrap();
+
+    fs::remove_file(remmina_dir.join("old.remmina")).unwrap();
+
+    // Without --prune nothing changes
+    let report = import(&remmina_dir, &tabby_dir, &[]);
+    assert!(report["plan"].get("removed_profiles").is_none());
+
+    let output = run_converter([
+        "diff".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--prune".as_ref(),
+    ]);
+    assert_eq!(output.status.code(), Some(5));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("- profile 'old' (source old.remmina not found)"), "{stdout}");
+    assert!(stdout.contains("- group   'Legacy'"), "{stdout}");
+
+    // Dry-run lists the profiles without saving
+    let before = fs::read_to_string(&config_path).unwrap();
+    let report = import(&remmina_dir, &tabby_dir, &["--prune"]);
+    assert_eq!(report["plan"]["removed_profiles"][0]["name"], "old");
+    assert_eq!(fs::read_to_string(&config_path).unwrap(), before);
+
+    let report = import(&remmina_dir, &tabby_dir, &["--prune", "--execute"]);
+    assert_eq!(report["plan"]["removed_groups"][0]["name"], "Legacy");
+
+    let config = load_yaml(&config_path);
+    assert!(find_profile(&config, "old").is_none());
+    assert!(find_profile(&config, "web").is_some());
+    assert!(find_profile(&config, "manual").is_some());
+    assert_eq!(group_names(&config), ["Production"]);
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn prune_keeps_profiles_of_files_left_out_by_the_protocol_filter() {
+    let work_dir = work_dir("prune-filter");
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+    let remmina_dir = copy_fixture("remmina", &work_dir);
+
+    import(&remmina_dir, &tabby_dir, &["--execute"]);
+
+    // The SSH file still exists, it is only filtered out
+    let report = import(&remmina_dir, &tabby_dir, &["--protocol", "RDP", "--prune", "--execute"]);
+    assert!(report["plan"].get("removed_profiles").is_none());
+    assert!(find_profile(&load_yaml(&tabby_dir.join("config.yaml")), "web-01").is_some());
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+use remmina_to_tabby::putty_parser::PuttySessions;
+use remmina_to_tabby::report::RunReport;
+use remmina_to_tabby::{AuthMethod, Connection, ConnectionProtocols, Error};
+
+const REG_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/putty/sessions.reg");
+const KITTY_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/kitty_sessions");
+
+fn session<'a>(sessions: &'a PuttySessions, name: &str) -> &'a Connection {
+    sessions.profiles.iter().find(|p| p.name.as_deref() == Some(name)).unwrap()
+}
+
+#[test]
+fn reg_export_sessions_become_profiles() {
+    let sessions = PuttySessions::load_reg(Path::new(REG_FILE)).unwrap();
+    let names: Vec<&str> = sessions.profiles.iter().filter_map(|p| p.name.as_deref()).collect();
+    assert_eq!(names, ["db primary", "web", "router"]);
+
+    let db = session(&sessions, "db primary");
+    assert_eq!(db.group.as_deref(), Some("Prod/DB"));
+    assert_eq!((db.host.as_deref(), db.port, db.user.as_deref()), (Some("db1.example.com"), Some(2222), Some("dba")));
+    assert_eq!(db.private_key.as_deref(), Some(r"C:\Users\me\.ssh\id_ed25519.ppk"));
+    assert_eq!(db.auth, Some(AuthMethod::PublicKey));
+    assert_eq!(db.compression, Some(true));
+    assert_eq!(db.source_id.as_deref(), Some("putty:Prod/DB/db primary"));
+    // SSH proxy: a jump host
+    let jump_host = db.jump_host.as_ref().unwrap();
+    assert_eq!((jump_host.host.as_str(), jump_host.port, jump_host.user.as_deref()), ("bastion.example.com", 22, Some("ops")));
+
+    let web = session(&sessions, "web");
+    assert_eq!((web.host.as_deref(), web.user.as_deref()), (Some("web.example.com"), Some("deploy")));
+    assert_eq!(web.proxy_command.as_deref(), Some("nc -X connect -x proxy.example.com:3128 %h %p"));
+    assert_eq!(web.forward_x11, Some(true));
+    assert_eq!(web.group, None);
+
+    assert_eq!(session(&sessions, "router").protocol, ConnectionProtocols::Telnet);
+    assert_eq!(sessions.sources.len(), 3);
+}
+
+#[test]
+fn kitty_sessions_directory() {
+    let mut report = RunReport::default();
+    let sessions = PuttySessions::load_kitty_dir(Path::new(KITTY_DIR), &mut report).unwrap();
+    assert_eq!(sessions.profiles.len(), 2);
+
+    let jumpy = session(&sessions, "jumpy");
+    assert_eq!(jumpy.group.as_deref(), Some("Office/Lab"));
+    assert_eq!(jumpy.proxy_command.as_deref(), Some("ssh -W %h:%p gw.example.com"));
+    assert_eq!(jumpy.source_id.as_deref(), Some(r"kitty:Lab\jumpy"));
+
+    let backup = session(&sessions, "backup server");
+    assert_eq!(backup.port, Some(2200));
+    assert_eq!(backup.private_key.as_deref(), Some("/home/me/.ssh/backup.ppk"));
+}
+
+#[test]
+fn other_files_are_not_registry_exports() {
+    let error = PuttySessions::load_reg(Path::new(KITTY_DIR).join("backup%20server").as_path()).unwrap_err();
+    assert!(matches!(error, Error::InvalidSessionFile { line: Some(1), .. }), "{error}");
+    assert_eq!(error.exit_code(), 17);
+}
+
+#[test]
+fn convert_putty_sessions_into_tabby() {
+    let work_dir = work_dir("putty");
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+
+    let output = run_converter([
+        "convert".as_ref(),
+        "--from".as_ref(), "putty".as_ref(),
+        "--from-path".as_ref(), REG_FILE.as_ref(),
+        "--to-path".as_ref(), tabby_dir.as_os_str(),
+        "--group-mode".as_ref(), "leaf".as_ref(),
+        "--output".as_ref(), "json".as_ref(),
+        "--execute".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["skipped_files"][0]["kind"], "filtered");
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    let db = find_profile(&config, "db primary").unwrap();
+    assert_eq!(db["options"]["host"], "db1.example.com");
+    assert_eq!(db["remminaSource"], "putty:Prod/DB/db primary");
+    let jump_host = find_profile(&config, "bastion.example.com (jump host)").unwrap();
+    assert_eq!(db["options"]["jumpHost"], jump_host["id"]);
+    let group = config["groups"].as_sequence().unwrap().iter().find(|g| g["id"] == db["group"]).unwrap();
+    assert_eq!(group["name"], "DB");
+    assert!(find_profile(&config, "router").is_none());
+
+    let output = run_converter(["convert", "--from", "putty", "--from-path", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tabby_minimal/config.yaml")]);
+    assert_eq!(output.status.code(), Some(17));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn unmapped_proxy_methods_are_named() {
+    let work_dir = work_dir("putty-proxy");
+    let reg_file = work_dir.join("proxies.reg");
+    let session = |name: &str, method: u8| {
+        format!("[HKEY_CURRENT_USER\\Software\\SimonTatham\\PuTTY\\Sessions\\{name}]\n\"HostName\"=\"{name}.example.com\"\n\"Protocol\"=\"ssh\"\n\"ProxyMethod\"=dword:0000000{method}\n\"ProxyHost\"=\"proxy.example.com\"\n\n")
+    };
+    let content = ["telnet", "exec", "subsystem", "future"].iter().zip([4, 7, 8, 9])
+        .map(|(name, method)| session(name, method))
+        .collect::<String>();
+    fs::write(&reg_file, format!("Windows Registry Editor Version 5.00\n\n{content}")).unwrap();
+
+    let output = run_converter([
+        "convert".as_ref(),
+        "--from".as_ref(), "putty".as_ref(),
+        "--from-path".as_ref(), reg_file.as_os_str(),
+        "--to".as_ref(), "ssh-config".as_ref(),
+        "--to-path".as_ref(), work_dir.join("config").as_os_str(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Session 'telnet': proxy method 4 (Telnet) cannot be mapped"), "{stderr}");
+    assert!(stderr.contains("Session 'exec': proxy method 7 (SSH exec) cannot be mapped"), "{stderr}");
+    assert!(stderr.contains("Session 'subsystem': proxy method 8 (SSH subsystem) cannot be mapped"), "{stderr}");
+    assert!(stderr.contains("Session 'future': unknown proxy method 9, ignored."), "{stderr}");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use std::fs;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+
+#[test]
+fn import_reads_only_the_remmina_section() {
+    let work_dir = work_dir("keyfile");
+    let remmina_dir = copy_fixture("remmina_keyfile", &work_dir);
+    let tabby_dir = copy_fixture("tabby", &work_dir);
+
+    let output = run_converter([
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--execute".as_ref(),
+        "--yes".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+
+    // Duplicate key keeps the last value, escapes are decoded and '=' is kept in the value
+    let profile = find_profile(&config, " app-01 a=b").expect("profile not imported");
+    assert_eq!(profile["options"]["host"], "app-01.example.com");
+    assert_eq!(profile["options"]["port"], 2200);
+    assert_eq!(profile["options"]["user"], "svc");
+
+    // Keys from the plugin section and files without a [remmina] section are ignored
+    assert!(find_profile(&config, "plugin-name").is_none());
+    assert!(find_profile(&config, "no-section").is_none());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("does not start with a section"), "missing parse warning: {stderr}");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
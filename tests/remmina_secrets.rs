@@ -0,0 +1,60 @@
+mod common;
+
+use std::fs;
+
+use common::{copy_fixture, run_converter, work_dir};
+
+const PASSWORDS: [&str; 3] = ["s3cr3t-Passw0rd", "key phrase", "bastion!"];
+
+#[test]
+fn with_secrets_decrypts_without_printing_values() {
+    let work_dir = work_dir("secrets");
+    let remmina_dir = copy_fixture("remmina_secrets", &work_dir);
+    let tabby_dir = copy_fixture("tabby", &work_dir);
+
+    let output = run_converter([
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--with-secrets".as_ref(),
+        "--execute".as_ref(),
+        "--yes".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("3 decrypted, 1 stored in keyring (skipped), 0 failed"), "{stderr}");
+    assert!(stderr.contains("3 secrets handed over"), "{stderr}");
+
+    // Plaintext never reaches the terminal nor the Tabby config
+    let config = fs::read_to_string(tabby_dir.join("config.yaml")).unwrap();
+    for password in PASSWORDS {
+        assert!(!stdout.contains(password) && !stderr.contains(password) && !config.contains(password));
+    }
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn with_secrets_reports_wrong_secret() {
+    let work_dir = work_dir("secrets-wrong");
+    let remmina_dir = copy_fixture("remmina_secrets", &work_dir);
+    let tabby_dir = copy_fixture("tabby", &work_dir);
+
+    let output = run_converter([
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--with-secrets".as_ref(),
+        "--remmina-pref".as_ref(), remmina_dir.join("wrong.pref").as_os_str(),
+        "--yes".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("0 decrypted, 1 stored in keyring (skipped), 3 failed"), "{stderr}");
+    assert!(stderr.contains("cannot decrypt password"));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
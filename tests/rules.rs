@@ -0,0 +1,167 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{run_converter, work_dir};
+use remmina_to_tabby::rules::RuleSet;
+use remmina_to_tabby::{ConvertOptions, Error, TabbyConfig, convert};
+
+const RULES_YAML: &str = r##"
+rules:
+  - name: new domain
+    match: { host: "*.corp.old" }
+    replace: { host: { pattern: '\.corp\.old$', with: ".corp.new" } }
+  - name: prod user
+    match: { group: "prod*", protocol: SSH }
+    set: { user: deploy }
+    color: "#d9534f"
+    icon: fas fa-server
+  - match: { name: 'regex:^tmp-\d+$' }
+    drop: true
+  - name: legacy
+    match: { name: "db-*" }
+    rename: "{name} ({host})"
+    group: Databases
+"##;
+
+const RULES_TOML: &str = r##"
+[[rules]]
+name = "new domain"
+match = { host = "*.corp.old" }
+replace = { host = { pattern = '\.corp\.old$', with = ".corp.new" } }
+
+[[rules]]
+name = "prod user"
+match = { group = "prod*", protocol = "SSH" }
+set = { user = "deploy" }
+color = "#d9534f"
+icon = "fas fa-server"
+
+[[rules]]
+match = { name = 'regex:^tmp-\d+$' }
+drop = true
+
+[[rules]]
+name = "legacy"
+match = { name = "db-*" }
+rename = "{name} ({host})"
+group = "Databases"
+"##;
+
+fn write_remmina_files(dir: &Path) {
+    fs::create_dir_all(dir).unwrap();
+    let profiles = [
+        ("web", "web.corp.old", "Production", "root"),
+        ("tmp-42", "tmp.example.com", "Production", "root"),
+        ("db-01", "db-01.corp.old", "Staging", "postgres"),
+    ];
+    for (name, server, group, user) in profiles {
+        let content = format!("[remmina]\nname={name}\nserver={server}\nprotocol=SSH\ngroup={group}\nusername={user}\n");
+        fs::write(dir.join(format!("{name}.remmina")), content).unwrap();
+    }
+}
+
+fn check_rules(rules_file: &str, content: &str) {
+    let work_dir = work_dir(&format!("rules-{}", rules_file.replace('.', "-")));
+    let remmina_dir = work_dir.join("remmina");
+    write_remmina_files(&remmina_dir);
+    let rules_path = work_dir.join(rules_file);
+    fs::write(&rules_path, content).unwrap();
+    fs::write(work_dir.join("config.yaml"), "version: 7\nprofiles: []\n").unwrap();
+
+    let rules = RuleSet::load(&rules_path).unwrap();
+    let mut config = TabbyConfig::load_from_dir(work_dir.to_str().unwrap()).unwrap();
+    let options = ConvertOptions { rules: Some(&rules), ..Default::default() };
+    let plan = convert(remmina_dir.to_str().unwrap(), &mut config, options).unwrap();
+
+    // Dropped profiles are reported, not imported
+    assert_eq!(plan.skipped_profiles.len(), 1);
+    assert_eq!(plan.skipped_profiles[0].name, "tmp-42");
+    assert_eq!(plan.skipped_profiles[0].kind, "dropped");
+    assert!(config.get_profile("tmp-42").is_none());
+
+    let web = config.get_profile("web").unwrap();
+    assert_eq!(web.options().host.as_deref(), Some("web.corp.new"));
+    assert_eq!(web.options().user.as_deref(), Some("deploy"));
+    assert_eq!(web.color.as_deref(), Some("#d9534f"));
+    assert_eq!(web.icon.as_deref(), Some("fas fa-server"));
+
+    // Later rules see the changes of the earlier ones
+    let db = config.get_profile("db-01 (db-01.corp.new)").unwrap();
+    assert_eq!(db.options().user.as_deref(), Some("postgres"));
+    assert_eq!(plan.created_groups.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(), ["Databases", "Production"]);
+
+    let change = plan.rule_changes.iter().find(|c| c.profile == "web" && c.field == "host").unwrap();
+    assert_eq!(change.rule, "new domain");
+    assert_eq!(change.from.as_deref(), Some("web.corp.old"));
+    assert_eq!(change.to.as_deref(), Some("web.corp.new"));
+    assert!(plan.rule_changes.iter().any(|c| c.profile == "db-01" && c.field == "name" && c.rule == "legacy"));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn yaml_rules_transform_profiles() {
+    check_rules("rules.yaml", RULES_YAML);
+}
+
+#[test]
+fn toml_rules_transform_profiles() {
+    check_rules("rules.toml", RULES_TOML);
+}
+
+#[test]
+fn invalid_rules_are_reported() {
+    let work_dir = work_dir("rules-invalid");
+    let rules_path = work_dir.join("rules.yaml");
+
+    fs::write(&rules_path, "rules:\n  - match: { hostname: web }\n").unwrap();
+    let error = RuleSet::load(&rules_path).unwrap_err();
+    assert!(matches!(error, Error::InvalidRules { .. }), "{error}");
+    assert!(error.to_string().contains("hostname"), "{error}");
+
+    fs::write(&rules_path, "rules:\n  - match: { name: 'regex:(' }\n").unwrap();
+    assert!(matches!(RuleSet::load(&rules_path), Err(Error::InvalidRules { .. })));
+
+    // Ports and auth methods are typed
+    fs::write(&rules_path, "rules:\n  - set: { port: ssh }\n").unwrap();
+    let error = RuleSet::load(&rules_path).unwrap_err();
+    assert!(error.to_string().contains("invalid port 'ssh'"), "{error}");
+    fs::write(&rules_path, "rules:\n  - set: { auth: sshidentityfile }\n").unwrap();
+    assert!(matches!(RuleSet::load(&rules_path), Err(Error::InvalidRules { .. })));
+
+    let remmina_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/remmina");
+    let tabby_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tabby_minimal");
+    let output = run_converter([
+        "diff".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_ref(),
+        "--tabby-dir".as_ref(), tabby_dir.as_ref(),
+        "--rules".as_ref(), rules_path.as_os_str(),
+    ]);
+    assert_eq!(output.status.code(), Some(16));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn dry_run_shows_rule_changes() {
+    let work_dir = work_dir("rules-dry-run");
+    let rules_path = work_dir.join("rules.yaml");
+    fs::write(&rules_path, "rules:\n  - name: ops\n    match: { host: '*.example.com' }\n    set: { user: ops }\n").unwrap();
+
+    let remmina_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/remmina");
+    let tabby_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tabby_minimal");
+    let output = run_converter([
+        "diff".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_ref(),
+        "--tabby-dir".as_ref(), tabby_dir.as_ref(),
+        "--rules".as_ref(), rules_path.as_os_str(),
+    ]);
+    assert_eq!(output.status.code(), Some(5));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("* profile 'web-01' user: 'deploy' -> 'ops' (rule 'ops')"), "{stdout}");
+    assert!(stdout.contains("+ profile 'web-01' (ops@web-01.example.com:22)"), "{stdout}");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
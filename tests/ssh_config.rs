@@ -0,0 +1,150 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{run_converter, work_dir};
+use remmina_to_tabby::{Connection, ConnectionProtocols, JumpHost};
+use remmina_to_tabby::ssh_config::{BEGIN_MARKER, END_MARKER, GroupLayout, SshConfigExport, host_block};
+
+fn profile(name: &str, host: &str, group: Option<&str>) -> Connection {
+    Connection {
+        name: Some(name.to_string()),
+        host: Some(host.to_string()),
+        group: group.map(str::to_string),
+        protocol: ConnectionProtocols::Ssh,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn host_block_has_every_set_option() {
+    let mut app = profile("app eu 1", "10.1.0.11", Some("EU"));
+    app.port = Some(2222);
+    app.user = Some("deploy".to_string());
+    app.private_key = Some("/home/me/.ssh/my key".to_string());
+    app.proxy_command = Some("nc -X connect -x proxy:3128 %h %p".to_string());
+    app.jump_host = Some(JumpHost { host: "gw.example.com".to_string(), port: 2200, user: Some("jump".to_string()), auth: None, private_key: None, password: None });
+
+    assert_eq!(host_block(&app, "app-eu-1"), "\
+# app eu 1
+Host app-eu-1
+    HostName 10.1.0.11
+    Port 2222
+    User deploy
+    IdentityFile \"/home/me/.ssh/my key\"
+    ProxyJump jump@gw.example.com:2200
+    ProxyCommand nc -X connect -x proxy:3128 %h %p
+");
+
+    let bastion = profile("bastion", "bastion.example.com", None);
+    assert_eq!(host_block(&bastion, "bastion"), "Host bastion\n    HostName bastion.example.com\n");
+}
+
+#[test]
+fn groups_become_comments_or_included_files() {
+    let profiles = vec![
+        profile("web", "web.example.com", Some("Prod")),
+        profile("web", "web.staging.example.com", Some("Staging")),
+        profile("laptop", "192.168.1.2", None),
+        Connection { protocol: ConnectionProtocols::Rdp, ..profile("desktop", "10.0.0.5", None) },
+    ];
+    let config = Path::new("/home/me/.ssh/config");
+
+    let files = SshConfigExport::default().files(&profiles, config, None).unwrap();
+    assert_eq!(files.len(), 1);
+    let content = &files[0].content;
+    assert!(content.starts_with("Host laptop\n"), "{content}");
+    assert!(content.contains("# Group: Prod\n\nHost web\n"), "{content}");
+    assert!(content.contains("# Group: Staging\n\n# web\nHost web-2\n"), "{content}");
+    assert!(!content.contains("desktop"), "{content}");
+
+    let export = SshConfigExport { layout: GroupLayout::Include, managed: false };
+    let files = export.files(&profiles, config, None).unwrap();
+    let paths: Vec<_> = files.iter().map(|f| f.path.display().to_string()).collect();
+    assert_eq!(paths, ["/home/me/.ssh/config", "/home/me/.ssh/config.d/prod.conf", "/home/me/.ssh/config.d/staging.conf"]);
+    assert_eq!(
+        files[0].content,
+        "Include /home/me/.ssh/config.d/prod.conf\nInclude /home/me/.ssh/config.d/staging.conf\n\nHost laptop\n    HostName 192.168.1.2\n\n"
+    );
+    assert_eq!(files[2].hosts, 1);
+}
+
+#[test]
+fn managed_region_is_replaced_and_the_rest_kept() {
+    let profiles = vec![profile("web", "web.example.com", None)];
+    let config = Path::new("/home/me/.ssh/config");
+    let export = SshConfigExport { layout: GroupLayout::Comments, managed: true };
+
+    let existing = "Host github.com\n    User git\n";
+    let appended = export.files(&profiles, config, Some(existing)).unwrap().remove(0).content;
+    assert_eq!(appended, format!("{existing}\n{BEGIN_MARKER}\nHost web\n    HostName web.example.com\n\n{END_MARKER}\n"));
+
+    // Running again only replaces the region
+    let profiles = vec![profile("db", "db.example.com", None)];
+    let edited = format!("{appended}Host *\n    ServerAliveInterval 60\n");
+    let updated = export.files(&profiles, config, Some(&edited)).unwrap().remove(0).content;
+    assert_eq!(updated, format!("{existing}\n{BEGIN_MARKER}\nHost db\n    HostName db.example.com\n\n{END_MARKER}\nHost *\n    ServerAliveInterval 60\n"));
+
+    let broken = format!("{existing}{BEGIN_MARKER}\n");
+    assert!(export.files(&profiles, config, Some(&broken)).is_err());
+}
+
+#[test]
+fn export_ssh_config_command_writes_only_with_execute() {
+    let work_dir = work_dir("ssh-config");
+    let config = work_dir.join("config");
+    fs::write(&config, "Host github.com\n    User git\n").unwrap();
+    let remmina_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/remmina_tunnel");
+
+    let export = |execute: bool| {
+        let mut args = vec![
+            "export-ssh-config".as_ref(),
+            "--remmina-dir".as_ref(), remmina_dir.as_ref(),
+            "--ssh-config".as_ref(), config.as_os_str(),
+            "--groups".as_ref(), "include".as_ref(),
+            "--managed".as_ref(),
+        ];
+        if execute {
+            args.push("--execute".as_ref());
+        }
+        run_converter(args)
+    };
+
+    assert!(export(false).status.success());
+    assert_eq!(fs::read_to_string(&config).unwrap(), "Host github.com\n    User git\n");
+
+    let output = export(true);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    let content = fs::read_to_string(&config).unwrap();
+    assert!(content.starts_with("Host github.com\n    User git\n\n"), "{content}");
+    // After the github.com block, the includes must not be scoped to it
+    assert!(content.contains(&format!("{BEGIN_MARKER}\nMatch all\nInclude ")), "{content}");
+    let eu = fs::read_to_string(work_dir.join("config.d/eu.conf")).unwrap();
+    assert!(eu.contains("Host app-eu-1\n    HostName 10.1.0.11\n    User deploy\n    ProxyJump ops@bastion.example.com\n"), "{eu}");
+    let us = fs::read_to_string(work_dir.join("config.d/us.conf")).unwrap();
+    assert!(us.contains("ProxyJump jump@gw.us.example.com:2222"), "{us}");
+
+    // convert --to ssh-config goes through the same exporter and layout options
+    let converted = work_dir.join("converted");
+    fs::write(&converted, "Host github.com\n    User git\n").unwrap();
+    let output = run_converter([
+        "convert".as_ref(),
+        "--from".as_ref(), "remmina".as_ref(),
+        "--from-path".as_ref(), remmina_dir.as_ref(),
+        "--to".as_ref(), "ssh-config".as_ref(),
+        "--to-path".as_ref(), converted.as_os_str(),
+        "--groups".as_ref(), "include".as_ref(),
+        "--managed".as_ref(),
+        "--execute".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read_to_string(&converted).unwrap(), content.replace("config.d", "converted.d"));
+    assert_eq!(fs::read_to_string(work_dir.join("converted.d/eu.conf")).unwrap(), eu);
+
+    let output = run_converter(["convert", "--from", "remmina", "--from-path", remmina_dir, "--to", "tabby", "--managed"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--managed only apply to --to ssh-config, not to tabby"));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
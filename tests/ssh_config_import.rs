@@ -0,0 +1,166 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+use remmina_to_tabby::ssh_config_parser::SshConfig;
+use remmina_to_tabby::{AuthMethod, Connection, ConvertOptions, TabbyConfig, convert_ssh_config};
+
+const SSH_CONFIG: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ssh_config/config");
+
+fn host<'a>(config: &'a SshConfig, alias: &str) -> &'a Connection {
+    config.profiles.iter().find(|p| p.name.as_deref() == Some(alias)).unwrap()
+}
+
+fn position(config: &SshConfig, alias: &str) -> usize {
+    config.profiles.iter().position(|p| p.name.as_deref() == Some(alias)).unwrap()
+}
+
+#[test]
+fn host_patterns_and_match_blocks_are_resolved_per_alias() {
+    let config = SshConfig::load(Path::new(SSH_CONFIG)).unwrap();
+
+    let bastion = host(&config, "bastion");
+    assert_eq!(bastion.host.as_deref(), Some("bastion.example.com"));
+    assert_eq!(bastion.user.as_deref(), Some("ops"));
+    // An IdentityFile without PreferredAuthentications leaves the method to the client
+    assert_eq!(bastion.auth, None);
+    assert_eq!(bastion.private_key, Some(format!("{}/.ssh/id_ed25519", std::env::var("HOME").unwrap_or_default())));
+    assert_eq!(bastion.source_id.as_deref(), Some("ssh_config:bastion"));
+
+    // The first value wins: "Host web db-* !db-old" comes before "Host web"
+    let web = host(&config, "web");
+    assert_eq!((web.host.as_deref(), web.port), (Some("web.internal"), Some(2200)));
+    let db = host(&config, "db-1");
+    assert_eq!((db.host.as_deref(), db.port), (Some("db-1.internal"), Some(2200)));
+    assert_eq!(db.forward_x11, Some(true));
+    let old = host(&config, "db-old");
+    assert_eq!((old.host.as_deref(), old.port), (Some("db-old.internal"), None));
+    assert_eq!(old.forward_x11, None);
+
+    // Options of every host and of "Match exec" are not applied to the hosts
+    assert_eq!(db.user, None);
+    assert!(db.ciphers.is_empty());
+}
+
+#[test]
+fn included_files_and_group_comments() {
+    let config = SshConfig::load(Path::new(SSH_CONFIG)).unwrap();
+    assert_eq!(config.files.len(), 2);
+
+    let api = host(&config, "api");
+    assert_eq!(api.group.as_deref(), Some("Production"));
+    assert_eq!(api.auth, Some(AuthMethod::Password));
+    assert_eq!(host(&config, "bastion").group, None);
+
+    // Lines after the Include still belong to the global options
+    assert_eq!(config.defaults.user.as_deref(), Some("me"));
+    assert_eq!(config.defaults.compression, Some(true));
+    assert_eq!(config.defaults.ciphers, ["aes256-gcm@openssh.com", "aes128-ctr"]);
+    assert_eq!(api.compression, None);
+}
+
+#[test]
+fn proxy_jump_chains_become_jump_hosts() {
+    let config = SshConfig::load(Path::new(SSH_CONFIG)).unwrap();
+
+    let web = host(&config, "web");
+    let jump_host = web.jump_host.as_ref().unwrap();
+    assert_eq!((jump_host.host.as_str(), jump_host.port, jump_host.user.as_deref()), ("bastion.example.com", 22, Some("ops")));
+    assert!(position(&config, "bastion") < position(&config, "web"));
+
+    // gw.example.com,jump@inner.example.com:2022: the host goes through inner, inner through gw
+    let db = host(&config, "db-1");
+    let jump_host = db.jump_host.as_ref().unwrap();
+    assert_eq!((jump_host.host.as_str(), jump_host.port, jump_host.user.as_deref()), ("inner.example.com", 2022, Some("jump")));
+    let inner = host(&config, "inner.example.com (jump host)");
+    assert_eq!(inner.group.as_deref(), Some("Jump Hosts"));
+    assert_eq!(inner.jump_host.as_ref().unwrap().host, "gw.example.com");
+    assert_eq!(inner.auth, None);
+    assert!(position(&config, "inner.example.com (jump host)") < position(&config, "db-1"));
+    assert_eq!(config.profiles.iter().filter(|p| p.name.as_deref() == Some("inner.example.com (jump host)")).count(), 1);
+}
+
+#[test]
+fn hosts_without_preferred_authentications_are_written_without_auth() {
+    let work_dir = work_dir("ssh-config-auth");
+    let ssh_config = work_dir.join("config");
+    fs::write(&ssh_config, "Host x\n    HostName y\n\nHost k\n    HostName k.example.com\n    PreferredAuthentications publickey,password\n").unwrap();
+
+    let mut tabby = TabbyConfig::load_from_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tabby_minimal")).unwrap();
+    convert_ssh_config(&ssh_config, &mut tabby, ConvertOptions::default()).unwrap();
+    let profile = |name: &str| tabby.profiles.iter().find(|p| p.name == name).unwrap();
+    assert_eq!(profile("x").options().auth, None);
+    assert!(serde_yaml_ng::to_value(profile("x")).unwrap()["options"].get("auth").is_none());
+    assert_eq!(profile("k").options().auth.as_deref(), Some("publicKey"));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn global_options_become_tabby_profile_defaults() {
+    let mut tabby = TabbyConfig::load_from_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tabby_minimal")).unwrap();
+    let plan = convert_ssh_config(Path::new(SSH_CONFIG), &mut tabby, ConvertOptions::default()).unwrap();
+
+    let keys: Vec<&str> = plan.profile_defaults.iter().map(|d| d.key.as_str()).collect();
+    assert_eq!(keys, ["ssh.options.user", "ssh.options.algorithms"]);
+    assert_eq!(plan.created_profiles.len(), 7);
+
+    // Defaults already set in the Tabby config are kept
+    let plan = convert_ssh_config(Path::new(SSH_CONFIG), &mut tabby, ConvertOptions::default()).unwrap();
+    assert!(plan.profile_defaults.is_empty());
+}
+
+#[test]
+fn import_command_reads_ssh_config_and_prunes_only_its_profiles() {
+    let work_dir = work_dir("ssh-config-import");
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+    let remmina_dir = work_dir.join("remmina");
+    fs::create_dir_all(&remmina_dir).unwrap();
+    fs::write(remmina_dir.join("desk.remmina"), "[remmina]\nname=desk\nserver=desk.example.com\nprotocol=SSH\n").unwrap();
+    let ssh_config = work_dir.join("config");
+    fs::write(&ssh_config, "Host *\n    User me\n\nHost web\n    HostName web.example.com\n\nHost old\n    HostName old.example.com\n").unwrap();
+
+    let import = |source: &[&std::ffi::OsStr]| {
+        let mut args = vec![
+            "import".as_ref(),
+            "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+            "--prune".as_ref(),
+            "--execute".as_ref(),
+            "--yes".as_ref(),
+        ];
+        args.extend_from_slice(source);
+        let output = run_converter(args);
+        assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    };
+    import(&["--remmina-dir".as_ref(), remmina_dir.as_os_str()]);
+    import(&["--from".as_ref(), "ssh-config".as_ref(), "--from-path".as_ref(), ssh_config.as_os_str()]);
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    assert_eq!(config["profileDefaults"]["ssh"]["options"]["user"], "me");
+    let web = find_profile(&config, "web").unwrap();
+    assert_eq!(web["options"]["host"], "web.example.com");
+    assert_eq!(web["remminaSource"], "ssh_config:web");
+    assert!(find_profile(&config, "desk").is_some());
+
+    // Secrets are read from Remmina profiles only
+    let output = run_converter([
+        "import".as_ref(), "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--from".as_ref(), "ssh-config".as_ref(), "--from-path".as_ref(), ssh_config.as_os_str(),
+        "--with-secrets".as_ref(), "--yes".as_ref(),
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--with-secrets only applies to --from remmina, not to ssh-config"));
+
+    // The Remmina profile is not pruned by an ssh_config import, nor the reverse
+    fs::write(&ssh_config, "Host web\n    HostName web.example.com\n").unwrap();
+    import(&["--from".as_ref(), "ssh-config".as_ref(), "--from-path".as_ref(), ssh_config.as_os_str()]);
+    import(&["--remmina-dir".as_ref(), remmina_dir.as_os_str()]);
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    assert!(find_profile(&config, "old").is_none());
+    assert!(find_profile(&config, "web").is_some());
+    assert!(find_profile(&config, "desk").is_some());
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use std::fs;
+
+use serde_yaml_ng::Value;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+
+#[test]
+fn import_maps_remmina_ssh_options() {
+    let work_dir = work_dir("ssh-options");
+    let remmina_dir = copy_fixture("remmina_ssh_options", &work_dir);
+    let tabby_dir = copy_fixture("tabby", &work_dir);
+
+    let output = run_converter([
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--execute".as_ref(),
+        "--yes".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    let options = &find_profile(&config, "db-replica").expect("profile not imported")["options"];
+
+    assert_eq!(options["host"], "db-replica.example.com");
+    assert_eq!(options["port"], 2022);
+    assert_eq!(options["user"], "postgres");
+    assert_eq!(options["auth"], "publicKey");
+    assert_eq!(options["privateKeys"][0], "file:///home/ops/.ssh/id_ed25519");
+    assert_eq!(options["proxyCommand"], "ssh -W %h:%p gateway.example.com");
+    assert_eq!(options["algorithms"]["kex"][1], "diffie-hellman-group14-sha256");
+    assert_eq!(options["algorithms"]["cipher"][1], "aes128-ctr");
+    assert_eq!(options["algorithms"]["serverHostKey"][0], "ssh-ed25519");
+    assert_eq!(options["input"]["encoding"], "ISO-8859-1");
+    assert_eq!(options["scripts"][0]["send"], "sudo -iu postgres");
+    assert_eq!(options["x11"], Value::Bool(true));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
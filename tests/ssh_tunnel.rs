@@ -0,0 +1,47 @@
+mod common;
+
+use std::fs;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+
+#[test]
+fn import_converts_ssh_tunnels_into_shared_jump_hosts() {
+    let work_dir = work_dir("ssh-tunnel");
+    let remmina_dir = copy_fixture("remmina_tunnel", &work_dir);
+    let tabby_dir = copy_fixture("tabby", &work_dir);
+
+    let output = run_converter([
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--execute".as_ref(),
+        "--yes".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+
+    // The imported bastion profile is reused by both EU targets
+    let bastion = find_profile(&config, "bastion").expect("bastion not imported");
+    for name in ["app-eu-1", "app-eu-2"] {
+        let target = find_profile(&config, name).expect("target not imported");
+        assert_eq!(target["options"]["jumpHost"], bastion["id"], "{name} jump host");
+    }
+
+    // A single jump host is synthesized for the US targets
+    let jump = find_profile(&config, "gw.us.example.com (jump host)").expect("jump host not created");
+    assert_eq!(jump["options"]["port"], 2222);
+    assert_eq!(jump["options"]["user"], "jump");
+    assert_eq!(jump["options"]["auth"], "publicKey");
+    assert_eq!(jump["options"]["privateKeys"][0], "file:///home/ops/.ssh/jump_ed25519");
+    for name in ["app-us-1", "app-us-2"] {
+        let target = find_profile(&config, name).expect("target not imported");
+        assert_eq!(target["options"]["jumpHost"], jump["id"], "{name} jump host");
+    }
+    let jump_hosts = config["profiles"].as_sequence().unwrap().iter()
+        .filter(|p| p["name"].as_str().is_some_and(|n| n.ends_with("(jump host)")))
+        .count();
+    assert_eq!(jump_hosts, 1);
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+use remmina_to_tabby::{ConvertOptions, TabbyConfig, convert};
+use remmina_to_tabby::tabby_parser::ImportOptions;
+
+fn import(remmina_dir: &Path, tabby_dir: &Path, sync: bool) -> serde_json::Value {
+    let mut args = vec![
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--output".as_ref(), "json".as_ref(),
+        "--execute".as_ref(),
+        "--yes".as_ref(),
+    ];
+    if sync {
+        args.push("--sync".as_ref());
+    }
+    let output = run_converter(args);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn sync_updates_imported_profiles_and_leaves_manual_ones_alone() {
+    let work_dir = work_dir("sync-update");
+    let remmina_dir = copy_fixture("remmina", &work_dir);
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+    let config_path = tabby_dir.join("config.yaml");
+
+    let report = import(&remmina_dir, &tabby_dir, false);
+    assert_eq!(report["plan"]["created_profiles"].as_array().unwrap().len(), 1);
+    let config = load_yaml(&config_path);
+    assert_eq!(find_profile(&config, "web-01").unwrap()["remminaSource"], "1700000000001.remmina");
+
+    // A profile created in Tabby, pointing to the same host
+    let manual = "type: ssh\nname: web-01 (manual)\nid: ssh:manual\noptions:\n  host: web-01.example.com\n  user: admin\n";
+    let mut config = config;
+    config["profiles"].as_sequence_mut().unwrap().push(serde_yaml_ng::from_str(manual).unwrap());
+    fs::write(&config_path, serde_yaml_ng::to_string(&config).unwrap()).unwrap();
+
+    // The server moved
+    let file = remmina_dir.join("1700000000001.remmina");
+    let content = fs::read_to_string(&file).unwrap()
+        .replace("server=web-01.example.com", "server=web-01.example.org")
+        .replace("port=22", "port=2222")
+        .replace("username=deploy", "username=ops");
+    fs::write(&file, content).unwrap();
+
+    // Without sync the profile is left as is
+    let report = import(&remmina_dir, &tabby_dir, false);
+    assert_eq!(report["plan"]["skipped_profiles"][0]["kind"], "conflict");
+    assert_eq!(find_profile(&load_yaml(&config_path), "web-01").unwrap()["options"]["host"], "web-01.example.com");
+
+    let report = import(&remmina_dir, &tabby_dir, true);
+    let plan = &report["plan"];
+    assert!(plan["created_profiles"].as_array().unwrap().is_empty());
+    assert_eq!(plan["updated_profiles"][0]["name"], "web-01");
+    assert_eq!(plan["updated_profiles"][0]["changed"], serde_json::json!(["host", "port", "user"]));
+
+    let config = load_yaml(&config_path);
+    let profile = find_profile(&config, "web-01").unwrap();
+    assert_eq!(profile["options"]["host"], "web-01.example.org");
+    assert_eq!(profile["options"]["port"], 2222);
+    assert_eq!(profile["options"]["user"], "ops");
+    let manual = find_profile(&config, "web-01 (manual)").unwrap();
+    assert_eq!(manual["options"]["host"], "web-01.example.com");
+    assert_eq!(manual["options"]["user"], "admin");
+    assert_eq!(config["profiles"].as_sequence().unwrap().len(), 2);
+
+    // Nothing left to sync
+    let report = import(&remmina_dir, &tabby_dir, true);
+    assert!(report["plan"].get("updated_profiles").is_none());
+    assert_eq!(report["plan"]["unchanged_profiles"][0]["name"], "web-01");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn source_id_key_survives_file_renames() {
+    let work_dir = work_dir("sync-source-id");
+    let remmina_dir = work_dir.join("remmina");
+    fs::create_dir_all(&remmina_dir).unwrap();
+    let profile = "[remmina]\nname=db-01\nserver=db-01.example.com\nprotocol=SSH\ngroup=Databases\ntabby_source_id=db-01\n";
+    fs::write(remmina_dir.join("first.remmina"), profile).unwrap();
+
+    let tabby_minimal = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tabby_minimal");
+    let mut config = TabbyConfig::load_from_dir(tabby_minimal).unwrap();
+    let plan = convert(remmina_dir.to_str().unwrap(), &mut config, ConvertOptions::default()).unwrap();
+    assert_eq!(plan.created_profiles.len(), 1);
+    assert_eq!(config.get_profile("db-01").unwrap().remmina_source.as_deref(), Some("db-01"));
+
+    fs::remove_file(remmina_dir.join("first.remmina")).unwrap();
+    fs::write(remmina_dir.join("renamed.remmina"), profile.replace("group=Databases", "group=Legacy")).unwrap();
+
+    let options = ConvertOptions { import: ImportOptions { sync: true, ..Default::default() }, ..Default::default() };
+    let plan = convert(remmina_dir.to_str().unwrap(), &mut config, options).unwrap();
+    assert!(plan.created_profiles.is_empty());
+    assert_eq!(plan.updated_profiles[0].changed, ["group"]);
+    assert_eq!(plan.created_groups[0].name, "Legacy");
+    assert_eq!(config.profiles.len(), 1);
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use std::fs;
+
+use serde_yaml_ng::Value;
+
+use common::{copy_fixture, load_yaml, run_converter, work_dir};
+
+/// Find a profile (or group) by id in a YAML sequence
+fn find_by_id<'a>(items: &'a Value, id: &Value) -> Option<&'a Value> {
+    items.as_sequence()?.iter().find(|item| item.get("id") == Some(id))
+}
+
+#[test]
+fn import_keeps_existing_tabby_config_unchanged() {
+    let work_dir = work_dir("roundtrip");
+    let remmina_dir = copy_fixture("remmina", &work_dir);
+    let tabby_dir = copy_fixture("tabby", &work_dir);
+
+    // A hand-written profile with neither type nor options
+    let mut config = load_yaml(&tabby_dir.join("config.yaml"));
+    let bare: Value = serde_yaml_ng::from_str("id: bare-profile\nname: bare\n").unwrap();
+    config["profiles"].as_sequence_mut().unwrap().push(bare.clone());
+    fs::write(tabby_dir.join("config.yaml"), serde_yaml_ng::to_string(&config).unwrap()).unwrap();
+
+    let output = run_converter([
+        "import".as_ref(),
+        "--remmina-dir".as_ref(), remmina_dir.as_os_str(),
+        "--tabby-dir".as_ref(), tabby_dir.as_os_str(),
+        "--execute".as_ref(),
+        "--yes".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let before = load_yaml(&tabby_dir.join("config.yaml.bak"));
+    let after = load_yaml(&tabby_dir.join("config.yaml"));
+
+    // Every top level key other than profiles and groups is untouched
+    for (key, value) in before.as_mapping().unwrap() {
+        if key == "profiles" || key == "groups" {
+            continue;
+        }
+        assert_eq!(after.get(key), Some(value), "top level key {key:?} changed");
+    }
+
+    // Every existing profile and group is written back unchanged
+    for key in ["profiles", "groups"] {
+        let old_items = before[key].as_sequence().unwrap();
+        for item in old_items {
+            let new_item = find_by_id(&after[key], &item["id"]);
+            assert_eq!(new_item, Some(item), "{key} entry {:?} changed", item["id"]);
+        }
+    }
+
+    // No type or options were added to it
+    assert_eq!(find_by_id(&after["profiles"], &bare["id"]), Some(&bare));
+
+    // The Remmina profile was appended on top of the existing ones
+    let profiles = after["profiles"].as_sequence().unwrap();
+    assert_eq!(profiles.len(), before["profiles"].as_sequence().unwrap().len() + 1);
+    assert!(profiles.iter().any(|p| p["name"] == "web-01"));
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use serde_json::json;
+
+use common::{copy_fixture, decrypt_vault, find_profile, load_yaml, work_dir};
+
+fn run_with_vault(remmina_dir: &Path, tabby_dir: &Path, passphrase: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_remmina-to-tabby"))
+        .arg("import")
+        .arg("--remmina-dir").arg(remmina_dir)
+        .arg("--tabby-dir").arg(tabby_dir)
+        .args(["--with-secrets", "--vault", "--execute", "--yes"])
+        .env("TABBY_VAULT_PASSPHRASE", passphrase)
+        .output()
+        .unwrap()
+}
+
+/// Find the value of a vault secret by type and key
+fn secret_value<'a>(vault: &'a serde_json::Value, r#type: &str, key: &serde_json::Value) -> Option<&'a str> {
+    vault["secrets"].as_array()?.iter()
+        .find(|s| s["type"] == r#type && &s["key"] == key)?["value"]
+        .as_str()
+}
+
+#[test]
+fn vault_is_created_with_migrated_secrets() {
+    let work_dir = work_dir("vault-create");
+    let remmina_dir = copy_fixture("remmina_secrets", &work_dir);
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+
+    // Point the profile at a private key so its passphrase can be keyed by the key hash
+    let key_path = work_dir.join("id_test");
+    fs::write(&key_path, "not a real key\n").unwrap();
+    let profile_path = remmina_dir.join("1700000000020.remmina");
+    let profile = fs::read_to_string(&profile_path).unwrap();
+    fs::write(&profile_path, format!("{profile}ssh_privatekey={}\n", key_path.display())).unwrap();
+
+    let output = run_with_vault(&remmina_dir, &tabby_dir, "migration passphrase");
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let raw_config = fs::read_to_string(tabby_dir.join("config.yaml")).unwrap();
+    assert!(!raw_config.contains("s3cr3t-Passw0rd"));
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    let vault = decrypt_vault(&config["vault"], "migration passphrase");
+
+    let key = json!({ "user": "admin", "host": "secret.example.com", "port": 22 });
+    assert_eq!(secret_value(&vault, "ssh:password", &key), Some("s3cr3t-Passw0rd"));
+
+    let jump = find_profile(&config, "gw.example.com (jump host)").expect("jump host not created");
+    assert_eq!(jump["options"]["user"], "jump");
+    let key = json!({ "user": "jump", "host": "gw.example.com", "port": 22 });
+    assert_eq!(secret_value(&vault, "ssh:password", &key), Some("bastion!"));
+
+    // Key passphrases are keyed by the SHA-512 of the private key file content ("not a real key\n")
+    let key = json!({ "hash": "5e530928538f0a253905b4b2a84e15463f536f615145e4d39f6228de31c5bb1adc1920155998167292ad055acd926605cafd00b1f92c0be2015f31cc5c88c414" });
+    assert_eq!(secret_value(&vault, "ssh:key-passphrase", &key), Some("key phrase"));
+    // The key has exactly the shape the Tabby SSH plugin looks up: { hash } and nothing else
+    let passphrases: Vec<&serde_json::Value> = vault["secrets"].as_array().unwrap().iter().filter(|s| s["type"] == "ssh:key-passphrase").collect();
+    assert_eq!(passphrases.len(), 1);
+    let fields: Vec<&String> = passphrases[0]["key"].as_object().unwrap().keys().collect();
+    assert_eq!(fields, ["hash"]);
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
+
+#[test]
+fn existing_vault_keeps_its_secrets() {
+    let work_dir = work_dir("vault-existing");
+    let remmina_dir = copy_fixture("remmina_secrets", &work_dir);
+    let tabby_dir = copy_fixture("tabby_vault", &work_dir);
+
+    let output = run_with_vault(&remmina_dir, &tabby_dir, "wrong passphrase");
+    assert!(!output.status.success(), "wrong passphrase must be rejected");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Cannot unlock Tabby vault"));
+
+    let output = run_with_vault(&remmina_dir, &tabby_dir, "correct horse");
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    let vault = decrypt_vault(&config["vault"], "correct horse");
+
+    let key = json!({ "user": "root", "host": "old.example.com", "port": 22 });
+    assert_eq!(secret_value(&vault, "ssh:password", &key), Some("old-password"));
+    let key = json!({ "user": "admin", "host": "secret.example.com", "port": 22 });
+    assert_eq!(secret_value(&vault, "ssh:password", &key), Some("s3cr3t-Passw0rd"));
+    assert_eq!(vault["settings"]["autoLockInterval"], 600);
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+use remmina_to_tabby::termius_parser::TermiusHosts;
+use remmina_to_tabby::{Connection, ConnectionProtocols, Error};
+
+const HOSTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/termius/hosts.json");
+
+fn host<'a>(hosts: &'a TermiusHosts, name: &str) -> &'a Connection {
+    hosts.profiles.iter().find(|p| p.name.as_deref() == Some(name)).unwrap()
+}
+
+#[test]
+fn hosts_with_group_settings() {
+    let hosts = TermiusHosts::load(Path::new(HOSTS)).unwrap();
+    let names: Vec<&str> = hosts.profiles.iter().filter_map(|p| p.name.as_deref()).collect();
+    assert_eq!(names, ["db primary", "web", "lab.example.com", "router"]);
+
+    // User from "DB", port from its parent "Prod"
+    let primary = host(&hosts, "db primary");
+    assert_eq!(primary.group.as_deref(), Some("Prod/DB"));
+    assert_eq!((primary.host.as_deref(), primary.port, primary.user.as_deref()), (Some("db1.example.com"), Some(2222), Some("dba")));
+    assert_eq!(primary.source_id.as_deref(), Some("termius:10"));
+    assert_eq!(primary.private_key, None);
+
+    let web = host(&hosts, "web");
+    assert_eq!((web.group.as_deref(), web.port, web.user.as_deref()), (Some("Prod"), Some(22), Some("deploy")));
+
+    let lab = host(&hosts, "lab.example.com");
+    assert_eq!((lab.group.as_deref(), &lab.protocol, lab.user.as_deref()), (None, &ConnectionProtocols::Ssh, None));
+
+    let router = host(&hosts, "router");
+    assert_eq!((&router.protocol, router.port, router.user.as_deref()), (&ConnectionProtocols::Telnet, Some(23), None));
+}
+
+#[test]
+fn other_json_is_not_a_termius_export() {
+    let settings = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/windows_terminal/settings.json");
+    let error = TermiusHosts::load(Path::new(settings)).unwrap_err();
+    assert!(matches!(error, Error::InvalidSessionFile { line: Some(1), .. }), "{error}");
+    assert_eq!(error.exit_code(), 17);
+}
+
+#[test]
+fn convert_termius_hosts_into_tabby() {
+    let work_dir = work_dir("termius");
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+
+    let output = run_converter([
+        "convert".as_ref(),
+        "--from".as_ref(), "termius".as_ref(),
+        "--from-path".as_ref(), HOSTS.as_ref(),
+        "--to-path".as_ref(), tabby_dir.as_os_str(),
+        "--execute".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    let primary = find_profile(&config, "db primary").unwrap();
+    assert_eq!(primary["options"]["host"], "db1.example.com");
+    assert_eq!(primary["options"]["port"], 2222);
+    assert_eq!(primary["remminaSource"], "termius:10");
+    assert!(find_profile(&config, "lab.example.com").is_some());
+    assert!(find_profile(&config, "router").is_none());
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}
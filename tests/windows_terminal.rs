@@ -0,0 +1,91 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{copy_fixture, find_profile, load_yaml, run_converter, work_dir};
+use remmina_to_tabby::report::RunReport;
+use remmina_to_tabby::ssh_config_parser::{command_line_profile, split_arguments};
+use remmina_to_tabby::windows_terminal_parser::WindowsTerminalProfiles;
+use remmina_to_tabby::Error;
+
+const SETTINGS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/windows_terminal/settings.json");
+
+#[test]
+fn ssh_command_lines() {
+    let profile = |line: &str| command_line_profile(&split_arguments(line));
+
+    let db = profile("-p2222 -l dba -C db.example.com").unwrap();
+    assert_eq!((db.name.as_deref(), db.host.as_deref(), db.port, db.user.as_deref()), (Some("db.example.com"), Some("db.example.com"), Some(2222), Some("dba")));
+    assert_eq!(db.compression, Some(true));
+
+    // Options win over the destination, as in ssh
+    let web = profile("-o \"User=deploy\" -o HostName=10.0.0.5 root@web:2200 sudo -i").unwrap();
+    assert_eq!((web.host.as_deref(), web.port, web.user.as_deref()), (Some("10.0.0.5"), Some(2200), Some("deploy")));
+    assert_eq!(web.remote_command.as_deref(), Some("sudo -i"));
+
+    let jumpy = profile("-J gw.example.com,ops@bastion:2200 -- lab").unwrap();
+    let jump_host = jumpy.jump_host.unwrap();
+    assert_eq!((jump_host.host.as_str(), jump_host.port, jump_host.user.as_deref()), ("bastion", 2200, Some("ops")));
+    assert_eq!(jumpy.host.as_deref(), Some("lab"));
+
+    assert_eq!(profile("-p 22").unwrap_err(), "no destination host");
+    assert_eq!(profile("host -p").unwrap().remote_command.as_deref(), Some("-p"));
+    assert_eq!(profile("-i").unwrap_err(), "option -i needs a value");
+}
+
+#[test]
+fn ssh_profiles_of_the_settings() {
+    let mut report = RunReport::default();
+    let terminal = WindowsTerminalProfiles::load(Path::new(SETTINGS), &mut report).unwrap();
+    let names: Vec<&str> = terminal.profiles.iter().filter_map(|p| p.name.as_deref()).collect();
+    assert_eq!(names, ["db primary", "web"]);
+
+    let db = &terminal.profiles[0];
+    assert_eq!((db.host.as_deref(), db.port, db.user.as_deref()), (Some("db1.example.com"), Some(2222), Some("dba")));
+    assert_eq!(db.private_key, Some(format!("{}/.ssh/id_ed25519", std::env::var("HOME").unwrap_or_default())));
+    // -i adds a key, ssh still tries the other methods
+    assert_eq!(db.auth, None);
+    assert_eq!(db.jump_host.as_ref().map(|j| (j.host.as_str(), j.port)), Some(("bastion.example.com", 2200)));
+    assert_eq!(db.source_id.as_deref(), Some("windows-terminal:0c4b1f1e-3a51-4c1c-9d0e-2b1c7a3c0001"));
+
+    let web = &terminal.profiles[1];
+    assert_eq!((web.host.as_deref(), web.port, web.user.as_deref()), (Some("web.example.com"), Some(2200), Some("deploy")));
+    assert_eq!((web.forward_x11, web.compression), (Some(true), Some(true)));
+    assert_eq!(web.remote_command.as_deref(), Some("uptime -p"));
+
+    // PowerShell and the broken ssh command line are reported, the generated Azure Cloud Shell has no command line
+    assert_eq!(report.skipped_files.len(), 2);
+    assert!(report.skipped_files[0].reason.contains("Windows PowerShell"), "{:?}", report.skipped_files[0]);
+    assert_eq!(report.skipped_files[1].kind, "invalid_session_file");
+    assert!(report.skipped_files[1].reason.contains("'half typed': option -i needs a value"), "{:?}", report.skipped_files[1]);
+    assert!(!terminal.sources.iter().any(|s| s.ends_with("0003")));
+}
+
+#[test]
+fn convert_windows_terminal_profiles_into_tabby() {
+    let work_dir = work_dir("windows_terminal");
+    let tabby_dir = copy_fixture("tabby_minimal", &work_dir);
+
+    let output = run_converter([
+        "convert".as_ref(),
+        "--from".as_ref(), "windows-terminal".as_ref(),
+        "--from-path".as_ref(), SETTINGS.as_ref(),
+        "--to-path".as_ref(), tabby_dir.as_os_str(),
+        "--execute".as_ref(),
+    ]);
+    assert!(output.status.success(), "converter failed: {}", String::from_utf8_lossy(&output.stderr));
+
+    let config = load_yaml(&tabby_dir.join("config.yaml"));
+    let db = find_profile(&config, "db primary").unwrap();
+    assert_eq!(db["options"]["host"], "db1.example.com");
+    let jump_host = find_profile(&config, "bastion.example.com (jump host)").unwrap();
+    assert_eq!(db["options"]["jumpHost"], jump_host["id"]);
+    assert_eq!(find_profile(&config, "web").unwrap()["options"]["user"], "deploy");
+
+    let hosts = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/termius/hosts.json");
+    let error = WindowsTerminalProfiles::load(Path::new(hosts), &mut RunReport::default()).unwrap_err();
+    assert!(matches!(error, Error::InvalidSessionFile { .. }), "{error}");
+
+    fs::remove_dir_all(&work_dir).unwrap();
+}